@@ -1,7 +1,4 @@
 mod bridge;
-mod command;
-mod response;
 
 pub use bridge::*;
-pub use command::*;
-pub use response::*;
+pub use manager_bridge::*;
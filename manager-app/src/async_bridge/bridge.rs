@@ -1,35 +1,47 @@
 use std::sync::Arc;
 
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 
 use manager_fut::TokioFuture;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 
 use super::{
     AddrWrappedPayload, BridgeCommand, BridgeResponse, ConnectionFailedResponse,
-    SetEqualizerPayload, TaggedStateResponse,
+    SetEqualizerPayload, TaggedConfigApplyReport, TaggedConfigSnapshot, TaggedEqFrequencyResponse,
+    TaggedStateChange, TaggedStateResponse,
 };
-use soundcore_lib::models::MonoEQ;
+use crate::settings::SettingsStore;
+use soundcore_lib::api::{EQPreset, EQPresetLibrary, EqualizerFeatures};
+use soundcore_lib::error::{SoundcoreLibError, SoundcoreLibResult};
 use soundcore_lib::{
     ble::BLEConnectionManager,
+    btaddr::BluetoothAdrr,
     device::SoundcoreBLEDevice,
-    device_manager::{create_device_manager, DeviceManager},
-    models::EQConfiguration,
+    device_manager::{create_device_manager, DeviceManager, DiscoveredDevice},
 };
 
+/// Number of points in the EQ frequency responses sent to the UI
+const EQ_RESPONSE_POINTS: usize = 128;
+
 struct CommandLoopState<B: BLEConnectionManager> {
-    manager: DeviceManager<B, TokioFuture>,
+    /// Shared so long running operations don't have to hold the state's lock
+    manager: Arc<DeviceManager<B, TokioFuture>>,
+    settings: SettingsStore,
 }
 
 impl<B: BLEConnectionManager> CommandLoopState<B> {
-    fn new(manager: DeviceManager<B, TokioFuture>) -> Self {
-        Self { manager }
+    fn new(manager: DeviceManager<B, TokioFuture>, settings: SettingsStore) -> Self {
+        Self {
+            manager: Arc::new(manager),
+            settings,
+        }
     }
 }
 
 pub async fn async_bridge(
     mut input_rx: mpsc::Receiver<BridgeCommand>,
     output_tx: mpsc::Sender<BridgeResponse>,
+    settings: SettingsStore,
 ) {
     tokio::spawn(async move {
         let manager = create_device_manager().await;
@@ -46,12 +58,75 @@ pub async fn async_bridge(
             }
         });
 
+        // Merged state events of all connected devices
+        let mut state_events = manager.state_events();
+        let state_tx = output_tx.clone();
+        tokio::task::spawn(async move {
+            loop {
+                match state_events.recv().await {
+                    Ok((addr, state)) => {
+                        trace!("Got new state {:?} for {:?}, sending event...", state, addr);
+                        let res = state_tx
+                            .send(BridgeResponse::NewState(TaggedStateResponse {
+                                addr,
+                                state,
+                            }))
+                            .await;
+                        if let Err(e) = res {
+                            debug!("Failed to send new state event: {:?}", e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("State event receiver lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            info!("State event channel closed");
+        });
+
+        // Field-level state changes of all connected devices
+        let mut state_change_events = manager.state_change_events();
+        let state_change_tx = output_tx.clone();
+        tokio::task::spawn(async move {
+            loop {
+                match state_change_events.recv().await {
+                    Ok((addr, change)) => {
+                        let res = state_change_tx
+                            .send(BridgeResponse::StateChanged(TaggedStateChange {
+                                addr,
+                                change,
+                            }))
+                            .await;
+                        if let Err(e) = res {
+                            debug!("Failed to send state change event: {:?}", e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("State change receiver lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            info!("State change event channel closed");
+        });
+
+        let auto_connect_addrs = settings.settings().auto_connect_devices();
+        let command_loop_state = Arc::new(Mutex::new(CommandLoopState::new(manager, settings)));
+        if !auto_connect_addrs.is_empty() {
+            // Commands shouldn't wait for the auto-connect scan
+            tokio::task::spawn(auto_connect(
+                command_loop_state.clone(),
+                auto_connect_addrs,
+                output_tx.clone(),
+            ));
+        }
+
         // Main command loop
-        let command_loop_state = Arc::new(Mutex::new(CommandLoopState::new(manager)));
         loop {
             while let Some(command) = input_rx.recv().await {
                 let command_loop_state = command_loop_state.clone();
-                let response = handle_command(command_loop_state, command, output_tx.clone()).await;
+                let response = handle_command(command_loop_state, command).await;
                 output_tx
                     .send(response)
                     .await
@@ -64,16 +139,17 @@ pub async fn async_bridge(
 async fn handle_command<B: BLEConnectionManager>(
     command_loop_state: Arc<Mutex<CommandLoopState<B>>>,
     command: BridgeCommand,
-    output_tx: mpsc::Sender<BridgeResponse>,
 ) -> BridgeResponse {
     match command {
-        BridgeCommand::Scan => command_loop_state
-            .lock()
-            .await
-            .manager
-            .ble_scan(None)
-            .await
-            .map(BridgeResponse::ScanResult),
+        BridgeCommand::Scan => {
+            let command_loop_state = command_loop_state.lock().await;
+            let scan_duration = command_loop_state.settings.settings().scan_duration();
+            command_loop_state
+                .manager
+                .ble_scan(Some(scan_duration))
+                .await
+                .map(BridgeResponse::ScanResult)
+        }
         BridgeCommand::Disconnect(addr) => {
             let addr_clone = addr.clone();
             command_loop_state
@@ -92,47 +168,114 @@ async fn handle_command<B: BLEConnectionManager>(
             .await
             .map(|_| BridgeResponse::DisconnectedAll),
         BridgeCommand::Connect(d) => {
-            let addr = d.clone().descriptor.addr;
-            let device = command_loop_state.lock().await.manager.connect(d).await;
-            let addr_clone = addr.clone();
-            if let Ok(device) = device {
-                // Get the state channel and listen for changes in the background
-                let mut state_channel = device.state_channel().await;
-
-                // TODO: Investigate this
-                #[allow(clippy::let_underscore_future)]
-                let _ = tokio::task::spawn(async move {
-                    info!("Listening for state changes for {:?}", addr_clone);
-                    while let Ok(()) = state_channel.changed().await {
-                        let state = state_channel.borrow().clone();
-                        trace!(
-                            "Got new state {:?} for {:?}, sending event...",
-                            state,
-                            addr_clone
-                        );
-                        let res = output_tx
-                            .send(BridgeResponse::NewState(TaggedStateResponse {
-                                addr: addr_clone.clone(),
-                                state,
-                            }))
-                            .await;
-
-                        if let Err(e) = res {
-                            debug!("Failed to send new state event: {:?}", e);
-                        }
-                    }
-                    // TODO: Send a StateChannelClosed event
-                    trace!("State channel for {:?} closed", addr_clone);
-                });
-                Ok(BridgeResponse::ConnectionEstablished(TaggedStateResponse {
-                    addr,
-                    state: device.latest_state().await,
-                }))
-            } else {
-                Ok(BridgeResponse::ConnectionFailed(ConnectionFailedResponse {
+            let manager = command_loop_state.lock().await.manager.clone();
+            Ok(connect(&manager, d).await)
+        }
+        BridgeCommand::GetSnapshot => Ok(BridgeResponse::Snapshot(
+            command_loop_state.lock().await.manager.snapshot().await,
+        )),
+        BridgeCommand::GetSettings => Ok(BridgeResponse::Settings(
+            command_loop_state.lock().await.settings.settings().clone(),
+        )),
+        BridgeCommand::UpdateSettings(settings) => {
+            let res = command_loop_state
+                .lock()
+                .await
+                .settings
+                .update(settings.clone());
+            match res {
+                Ok(()) => Ok(BridgeResponse::Settings(settings)),
+                Err(e) => Ok(BridgeResponse::GenericError(format!(
+                    "Failed to save settings: {}",
+                    e
+                ))),
+            }
+        }
+        BridgeCommand::SaveEqPreset(payload) => {
+            import_eq_presets(
+                &command_loop_state,
+                AddrWrappedPayload {
+                    addr: payload.addr,
+                    payload: vec![payload.payload],
+                },
+            )
+            .await
+        }
+        BridgeCommand::ImportEqPresets(payload) => {
+            import_eq_presets(&command_loop_state, payload).await
+        }
+        BridgeCommand::RenameEqPreset(payload) => {
+            update_eq_presets(&command_loop_state, |presets| {
+                presets.rename(&payload.name, &payload.new_name)
+            })
+            .await
+        }
+        BridgeCommand::DeleteEqPreset(name) => {
+            update_eq_presets(&command_loop_state, |presets| {
+                presets.delete(&name).map(|_| ())
+            })
+            .await
+        }
+        BridgeCommand::ApplyEqPreset(payload) => {
+            apply_eq_preset(&command_loop_state, payload).await
+        }
+        BridgeCommand::GetEqFrequencyResponse(addr) => {
+            let device = command_loop_state
+                .lock()
+                .await
+                .manager
+                .get_device(addr.clone())
+                .await;
+            match device {
+                Some(device) => device
+                    .latest_state()
+                    .await
+                    .eq_frequency_response(EQ_RESPONSE_POINTS)
+                    .map(|response| {
+                        BridgeResponse::EqFrequencyResponse(TaggedEqFrequencyResponse {
+                            addr,
+                            response,
+                        })
+                    })
+                    .ok_or(SoundcoreLibError::FeatureNotSupported(
+                        "Equalizer".to_string(),
+                    )),
+                None => Ok(BridgeResponse::DeviceNotFound(addr)),
+            }
+        }
+        BridgeCommand::ExportConfig(addr) => {
+            let device = command_loop_state
+                .lock()
+                .await
+                .manager
+                .get_device(addr.clone())
+                .await;
+            match device {
+                Some(device) => Ok(BridgeResponse::ConfigExported(TaggedConfigSnapshot {
+                    snapshot: device.export_config().await,
                     addr,
-                    reason: device.err().unwrap().to_string(),
-                }))
+                })),
+                None => Ok(BridgeResponse::DeviceNotFound(addr)),
+            }
+        }
+        BridgeCommand::ApplyConfig(payload) => {
+            let device = command_loop_state
+                .lock()
+                .await
+                .manager
+                .get_device(payload.addr.clone())
+                .await;
+            match device {
+                Some(device) => {
+                    trace!("Applying config to {:?}", payload.addr);
+                    device.apply_config(&payload.payload).await.map(|report| {
+                        BridgeResponse::ConfigApplied(TaggedConfigApplyReport {
+                            addr: payload.addr,
+                            report,
+                        })
+                    })
+                }
+                None => Ok(BridgeResponse::DeviceNotFound(payload.addr)),
             }
         }
         BridgeCommand::SetSoundMode(payload) => {
@@ -156,6 +299,24 @@ async fn handle_command<B: BLEConnectionManager>(
                 Ok(BridgeResponse::DeviceNotFound(addr_clone))
             }
         }
+        BridgeCommand::SetSoundModeCycle(payload) => {
+            let device = command_loop_state
+                .lock()
+                .await
+                .manager
+                .get_device(payload.addr.clone())
+                .await;
+            match device {
+                Some(device) => {
+                    trace!("Setting sound mode cycle for {:?}", payload.addr);
+                    device
+                        .set_sound_mode_cycle(payload.payload)
+                        .await
+                        .map(|_| BridgeResponse::SoundModeCycleUpdated(payload.addr))
+                }
+                None => Ok(BridgeResponse::DeviceNotFound(payload.addr)),
+            }
+        }
         BridgeCommand::SetEqualizer(payload) => {
             let device = command_loop_state
                 .lock()
@@ -176,20 +337,142 @@ async fn handle_command<B: BLEConnectionManager>(
     .unwrap_or_else(|e| e)
 }
 
+async fn equalizer_features<B: BLEConnectionManager>(
+    command_loop_state: &Mutex<CommandLoopState<B>>,
+    addr: BluetoothAdrr,
+) -> SoundcoreLibResult<EqualizerFeatures> {
+    let device = command_loop_state
+        .lock()
+        .await
+        .manager
+        .get_device(addr)
+        .await
+        .ok_or(SoundcoreLibError::DeviceNotFound)?;
+    device
+        .latest_state()
+        .await
+        .feature_set
+        .equalizer_features
+        .ok_or(SoundcoreLibError::FeatureNotSupported(
+            "Equalizer".to_string(),
+        ))
+}
+
+async fn import_eq_presets<B: BLEConnectionManager>(
+    command_loop_state: &Mutex<CommandLoopState<B>>,
+    payload: AddrWrappedPayload<Vec<EQPreset>>,
+) -> SoundcoreLibResult<BridgeResponse> {
+    let features = equalizer_features(command_loop_state, payload.addr).await?;
+    update_eq_presets(command_loop_state, |presets| {
+        presets.import(payload.payload, &features)
+    })
+    .await
+}
+
+async fn apply_eq_preset<B: BLEConnectionManager>(
+    command_loop_state: &Mutex<CommandLoopState<B>>,
+    payload: AddrWrappedPayload<String>,
+) -> SoundcoreLibResult<BridgeResponse> {
+    let preset = command_loop_state
+        .lock()
+        .await
+        .settings
+        .settings()
+        .eq_presets
+        .get(&payload.payload)
+        .cloned()
+        .ok_or(SoundcoreLibError::EQPresetNotFound(payload.payload))?;
+    let features = equalizer_features(command_loop_state, payload.addr.clone()).await?;
+    preset.validate(&features)?;
+
+    let device = command_loop_state
+        .lock()
+        .await
+        .manager
+        .get_device(payload.addr.clone())
+        .await;
+    match device {
+        Some(device) => device
+            .set_eq(preset.eq_configuration())
+            .await
+            .map(|_| BridgeResponse::EqualizerUpdated(payload.addr)),
+        None => Ok(BridgeResponse::DeviceNotFound(payload.addr)),
+    }
+}
+
+/// Applies the update to the preset library and persists the settings
+async fn update_eq_presets<B: BLEConnectionManager>(
+    command_loop_state: &Mutex<CommandLoopState<B>>,
+    update: impl FnOnce(&mut EQPresetLibrary) -> SoundcoreLibResult<()>,
+) -> SoundcoreLibResult<BridgeResponse> {
+    let mut command_loop_state = command_loop_state.lock().await;
+    let mut settings = command_loop_state.settings.settings().clone();
+    update(&mut settings.eq_presets)?;
+    match command_loop_state.settings.update(settings.clone()) {
+        Ok(()) => Ok(BridgeResponse::Settings(settings)),
+        Err(e) => Ok(BridgeResponse::GenericError(format!(
+            "Failed to save settings: {}",
+            e
+        ))),
+    }
+}
+
+async fn auto_connect<B: BLEConnectionManager>(
+    command_loop_state: Arc<Mutex<CommandLoopState<B>>>,
+    addrs: Vec<BluetoothAdrr>,
+    output_tx: mpsc::Sender<BridgeResponse>,
+) {
+    info!("Auto-connecting to {:?}", addrs);
+    let (manager, scan_duration) = {
+        let command_loop_state = command_loop_state.lock().await;
+        (
+            command_loop_state.manager.clone(),
+            command_loop_state.settings.settings().scan_duration(),
+        )
+    };
+
+    let discovered = match manager.ble_scan(Some(scan_duration)).await {
+        Ok(discovered) => discovered,
+        Err(e) => {
+            warn!("Auto-connect scan failed: {:?}", e);
+            return;
+        }
+    };
+
+    for device in discovered
+        .into_iter()
+        .filter(|device| addrs.contains(&device.descriptor.addr))
+    {
+        let response = connect(&manager, device).await;
+        if let Err(e) = output_tx.send(response).await {
+            warn!("Failed to send auto-connect response: {:?}", e);
+            return;
+        }
+    }
+}
+
+async fn connect<B: BLEConnectionManager>(
+    manager: &DeviceManager<B, TokioFuture>,
+    device: DiscoveredDevice,
+) -> BridgeResponse {
+    let addr = device.descriptor.addr.clone();
+    match manager.connect(device).await {
+        Ok(device) => BridgeResponse::ConnectionEstablished(TaggedStateResponse {
+            addr,
+            state: device.latest_state().await,
+        }),
+        Err(e) => BridgeResponse::ConnectionFailed(ConnectionFailedResponse {
+            addr,
+            reason: e.to_string(),
+        }),
+    }
+}
+
 async fn handle_set_eq<B: BLEConnectionManager>(
     device: Arc<SoundcoreBLEDevice<<B as BLEConnectionManager>::Connection, TokioFuture>>,
     wrapped_payload: AddrWrappedPayload<SetEqualizerPayload>,
 ) -> BridgeResponse {
-    let eq_configuration = match wrapped_payload.payload {
-        SetEqualizerPayload::SetCustomEqualizer(values) => {
-            EQConfiguration::mono_custom(MonoEQ::from_signed_bytes(values))
-        }
-        SetEqualizerPayload::SetEqualizerPreset(profile) => {
-            EQConfiguration::stereo_with_profile(profile)
-        }
-    };
-
-    match device.set_eq(eq_configuration).await {
+    match device.set_eq(wrapped_payload.payload.into()).await {
         Ok(_) => BridgeResponse::EqualizerUpdated(wrapped_payload.addr),
         Err(e) => BridgeResponse::GenericError(e.to_string()),
     }
@@ -197,12 +480,15 @@ async fn handle_set_eq<B: BLEConnectionManager>(
 
 #[cfg(test)]
 mod test {
+    use crate::async_bridge::RenameEqPresetPayload;
+    use crate::settings::AppSettings;
+
     use super::*;
 
     async fn create_bridge() -> (mpsc::Sender<BridgeCommand>, mpsc::Receiver<BridgeResponse>) {
         let (input_tx, input_rx) = mpsc::channel(1);
         let (output_tx, output_rx) = mpsc::channel(1);
-        async_bridge(input_rx, output_tx).await;
+        async_bridge(input_rx, output_tx, SettingsStore::in_memory()).await;
         (input_tx, output_rx)
     }
 
@@ -224,6 +510,62 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn should_handle_settings_commands() {
+        let (input_tx, mut output_rx) = create_bridge().await;
+        input_tx
+            .send(BridgeCommand::GetSettings)
+            .await
+            .expect("Failed to send command");
+
+        let response = output_rx.recv().await.expect("Failed to receive response");
+        let mut settings = match response {
+            BridgeResponse::Settings(settings) => settings,
+            _ => panic!("Unexpected response: {:?}", response),
+        };
+        assert_eq!(settings, AppSettings::default());
+
+        settings.scan_duration_secs = 1;
+        input_tx
+            .send(BridgeCommand::UpdateSettings(settings.clone()))
+            .await
+            .expect("Failed to send command");
+
+        let response = output_rx.recv().await.expect("Failed to receive response");
+        match response {
+            BridgeResponse::Settings(updated) => assert_eq!(updated, settings),
+            _ => panic!("Unexpected response: {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reject_unknown_eq_presets() {
+        let (input_tx, mut output_rx) = create_bridge().await;
+        let commands = [
+            BridgeCommand::DeleteEqPreset("Commute".to_string()),
+            BridgeCommand::RenameEqPreset(RenameEqPresetPayload {
+                name: "Commute".to_string(),
+                new_name: "Train".to_string(),
+            }),
+            BridgeCommand::ApplyEqPreset(AddrWrappedPayload {
+                addr: BluetoothAdrr::default(),
+                payload: "Commute".to_string(),
+            }),
+        ];
+
+        for command in commands {
+            input_tx
+                .send(command)
+                .await
+                .expect("Failed to send command");
+            let response = output_rx.recv().await.expect("Failed to receive response");
+            match response {
+                BridgeResponse::GenericError(e) => assert!(e.contains("Commute")),
+                _ => panic!("Unexpected response: {:?}", response),
+            }
+        }
+    }
+
     #[tokio::test]
     async fn should_handle_connect_command_and_produce_response() {
         let (input_tx, mut output_rx) = create_bridge().await;
@@ -246,7 +588,13 @@ mod test {
             .await
             .expect("Failed to send command");
 
-        let response = output_rx.recv().await.expect("Failed to receive response");
+        // The merged state stream may emit the initial state before the command response
+        let response = loop {
+            match output_rx.recv().await.expect("Failed to receive response") {
+                BridgeResponse::NewState(_) | BridgeResponse::StateChanged(_) => continue,
+                response => break response,
+            }
+        };
 
         // TODO: Fix this test
         match response {
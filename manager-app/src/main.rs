@@ -6,27 +6,34 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use log::trace;
+use log::{debug, trace, warn};
 use mpsc::channel;
+use tauri::api::notification::Notification;
 use tauri::async_runtime::Mutex;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_log::LogTarget;
 use tokio::sync::mpsc;
 
-use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::api::{ChangeSource, SoundcoreDeviceState};
+use soundcore_lib::ble::BLEAdapterEvent;
 use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::DiscoveredDevice;
 
-use crate::async_bridge::{async_bridge, BridgeCommand, BridgeResponse};
+use crate::async_bridge::{async_bridge, BridgeCommand, BridgeResponse, TaggedStateChange};
+use crate::notifications::{DeviceNotification, NotificationSettings};
+use crate::settings::{AppSettings, SettingsStore, SETTINGS_FILE_NAME};
 
 pub(crate) mod async_bridge;
-// Remove for now since this uses legacy code
-// TODO: Migrate it to the new async system
-// mod tray;
+mod notifications;
+mod settings;
+mod tray;
 
 struct SoundcoreAppState {
     bridge_tx: Mutex<mpsc::Sender<BridgeCommand>>,
     scan_in_progress: Arc<Mutex<bool>>,
     last_states: Arc<Mutex<HashMap<BluetoothAdrr, SoundcoreDeviceState>>>,
+    last_scan: Mutex<Vec<DiscoveredDevice>>,
+    settings: Mutex<AppSettings>,
 }
 
 #[tokio::main]
@@ -35,10 +42,24 @@ async fn main() {
 
     let (input_tx, input_rx) = channel(255);
     let (output_tx, mut output_rx) = channel(255);
+    let settings_tx = input_tx.clone();
 
     tauri::Builder::default()
         .setup(|app| {
-            tokio::spawn(async_bridge(input_rx, output_tx));
+            let settings = match app.path_resolver().app_config_dir() {
+                Some(config_dir) => SettingsStore::load(config_dir.join(SETTINGS_FILE_NAME)),
+                None => {
+                    warn!("Could not resolve the config directory, settings will not be saved");
+                    SettingsStore::in_memory()
+                }
+            };
+            tokio::spawn(async_bridge(input_rx, output_tx, settings));
+            // Fetch the persisted settings, e.g. the notification preferences and nicknames
+            tokio::spawn(async move {
+                if let Err(e) = settings_tx.send(BridgeCommand::GetSettings).await {
+                    warn!("Failed to request settings: {:?}", e);
+                }
+            });
 
             let app_handle = app.handle();
             tokio::spawn(async move {
@@ -50,18 +71,16 @@ async fn main() {
             });
             Ok(())
         })
-        // .system_tray(tray::get_system_tray())
-        // .on_system_tray_event(tray::handle_tray_event)
+        .system_tray(tray::get_system_tray())
+        .on_system_tray_event(tray::handle_tray_event)
         .manage(SoundcoreAppState {
             bridge_tx: Mutex::new(input_tx),
             scan_in_progress: Arc::new(Mutex::new(false)),
-            last_states: Arc::new(Mutex::new(HashMap::new()))
+            last_states: Arc::new(Mutex::new(HashMap::new())),
+            last_scan: Mutex::new(Vec::new()),
+            settings: Mutex::new(AppSettings::default())
         })
-        .invoke_handler(tauri::generate_handler![
-            // tray::set_tray_device_status,
-            // tray::set_tray_menu,
-            send_bridge_command
-        ])
+        .invoke_handler(tauri::generate_handler![send_bridge_command])
         .plugin(tauri_plugin_log::Builder::default().targets([
             LogTarget::LogDir,
             LogTarget::Stdout,
@@ -83,7 +102,7 @@ async fn main() {
                         let win = app_handle.get_window(label.as_str()).unwrap();
                         win.hide().unwrap();
                         /* Fix show/hide tray item */
-                        let item = app_handle.tray_handle().get_item("hide");
+                        let item = app_handle.tray_handle().get_item(&tray::TrayAction::ToggleWindow.id());
                         item.set_title("Show").unwrap();
                     }
                 }
@@ -95,21 +114,67 @@ async fn main() {
 async fn handle_bridge_output<R: tauri::Runtime>(resp: BridgeResponse, manager: &impl Manager<R>) {
     trace!("Received response from bridge, emitting event...");
     trace!("Response: {:?}", resp);
-    if let BridgeResponse::ScanResult(_) = resp {
+    if let BridgeResponse::ScanResult(discovered) = resp.clone() {
         let state = manager.state::<SoundcoreAppState>();
         let mut scan_in_progress = state.scan_in_progress.lock().await;
         *scan_in_progress = false;
-    } else if let BridgeResponse::NewState(new_state) = resp.clone() {
+        *state.last_scan.lock().await = discovered;
+    } else if let BridgeResponse::StateChanged(tagged_change) = resp.clone() {
         let state = manager.state::<SoundcoreAppState>();
-        let mut device_states = state.last_states.lock().await;
-        let last_state = device_states.insert(new_state.addr, new_state.state.clone());
-        handle_state_update(last_state, new_state.state, manager.app_handle());
+        let last_state = state.last_states.lock().await.insert(
+            tagged_change.addr.clone(),
+            tagged_change.change.state.clone(),
+        );
+        let settings = state.settings.lock().await;
+        handle_state_change(
+            last_state,
+            tagged_change,
+            &settings.notifications,
+            manager.app_handle(),
+        );
+    } else if let BridgeResponse::Settings(settings) = resp.clone() {
+        let state = manager.state::<SoundcoreAppState>();
+        *state.settings.lock().await = settings;
+    } else if let BridgeResponse::Disconnected(addr) = resp.clone() {
+        // Disconnected by the user, no need to notify
+        let state = manager.state::<SoundcoreAppState>();
+        state.last_states.lock().await.remove(&addr);
+    } else if let BridgeResponse::DisconnectedAll = resp {
+        let state = manager.state::<SoundcoreAppState>();
+        state.last_states.lock().await.clear();
+    } else if let BridgeResponse::AdapterEvent(BLEAdapterEvent::DeviceDisconnected(addr)) =
+        resp.clone()
+    {
+        let state = manager.state::<SoundcoreAppState>();
+        // Only notify for devices which were connected through the app
+        if state.last_states.lock().await.remove(&addr).is_some() {
+            let settings = state.settings.lock().await;
+            let device_name = settings.display_name(&addr);
+            if let Some(notification) =
+                notifications::disconnect_notification(&settings.notifications, device_name)
+            {
+                show_notification(&manager.app_handle(), &notification);
+            }
+        }
     } else if let BridgeResponse::ConnectionEstablished(conn) = resp.clone() {
         // This is the initial state so we don't want to show anything to the user
         let state = manager.state::<SoundcoreAppState>();
         let mut device_states = state.last_states.lock().await;
         device_states.insert(conn.addr, conn.state.clone());
     }
+
+    if matches!(
+        resp,
+        BridgeResponse::ScanResult(_)
+            | BridgeResponse::ConnectionEstablished(_)
+            | BridgeResponse::StateChanged(_)
+            | BridgeResponse::Disconnected(_)
+            | BridgeResponse::DisconnectedAll
+            | BridgeResponse::AdapterEvent(_)
+            | BridgeResponse::Settings(_)
+    ) {
+        tray::update_tray_menu(&manager.app_handle()).await;
+    }
     manager.emit_all("async-bridge-event", resp).unwrap();
 }
 
@@ -117,6 +182,13 @@ async fn handle_bridge_output<R: tauri::Runtime>(resp: BridgeResponse, manager:
 async fn send_bridge_command(
     app_state: tauri::State<'_, SoundcoreAppState>,
     payload: BridgeCommand,
+) -> Result<(), String> {
+    dispatch_bridge_command(&app_state, payload).await
+}
+
+async fn dispatch_bridge_command(
+    app_state: &SoundcoreAppState,
+    payload: BridgeCommand,
 ) -> Result<(), String> {
     if let BridgeCommand::Scan = payload {
         let mut scan_in_progress = app_state.scan_in_progress.lock().await;
@@ -130,24 +202,40 @@ async fn send_bridge_command(
     tx.send(payload).await.map_err(|e| e.to_string())
 }
 
-fn handle_state_update<R: tauri::Runtime>(
+fn handle_state_change<R: tauri::Runtime>(
     last_state: Option<SoundcoreDeviceState>,
-    new_state: SoundcoreDeviceState,
-    _app_handle: AppHandle<R>,
+    tagged_change: TaggedStateChange,
+    settings: &NotificationSettings,
+    app_handle: AppHandle<R>,
 ) {
+    let TaggedStateChange { addr, change } = tagged_change;
+    if change.source == ChangeSource::Device {
+        let changed = change
+            .changed
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        debug!("Device {} changed: {}", addr, changed);
+    }
+
+    // No notifications for the initial state
     if let Some(last_state) = last_state {
-        if last_state == new_state {}
-        // This is a bit too verbose for now
-        // TODO: Add a setting to enable/disable this
-        // TODO: Improve the notification message based on the values changed
-        // TODO: Don't show this if we are the originator of the change
-        // Notification::new("soundcore-manager")
-        //     .title("Device state update")
-        //     .body(format!(
-        //         "Device state update: {:?} -> {:?}",
-        //         last_state, new_state
-        //     ))
-        //     .show()
-        //     .expect("Failed to show notification");
+        notifications::state_notifications(settings, &last_state, &change)
+            .iter()
+            .for_each(|notification| show_notification(&app_handle, notification));
+    }
+}
+
+fn show_notification<R: tauri::Runtime>(
+    app_handle: &AppHandle<R>,
+    notification: &DeviceNotification,
+) {
+    let res = Notification::new(&app_handle.config().tauri.bundle.identifier)
+        .title(notification.title())
+        .body(notification.body())
+        .show();
+    if let Err(e) = res {
+        warn!("Failed to show notification: {:?}", e);
     }
 }
@@ -0,0 +1,304 @@
+pub use manager_bridge::NotificationSettings;
+use soundcore_lib::api::{ChangeSource, SoundcoreDeviceState, StateChange, StateField};
+use soundcore_lib::models::{Battery, CurrentSoundMode, SingleBattery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatterySide {
+    Single,
+    Left,
+    Right,
+    Case,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceNotification {
+    /// Holds the battery's percentage
+    LowBattery(BatterySide, u8),
+    ChargingStarted(BatterySide),
+    ChargingFinished(BatterySide),
+    SoundModeChanged(CurrentSoundMode),
+    /// Holds the device's display name
+    Disconnected(String),
+}
+
+impl DeviceNotification {
+    pub fn title(&self) -> String {
+        match self {
+            DeviceNotification::LowBattery(..) => "Low battery".to_string(),
+            DeviceNotification::ChargingStarted(_) => "Charging started".to_string(),
+            DeviceNotification::ChargingFinished(_) => "Charging finished".to_string(),
+            DeviceNotification::SoundModeChanged(_) => "Sound mode changed".to_string(),
+            DeviceNotification::Disconnected(_) => "Device disconnected".to_string(),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            DeviceNotification::LowBattery(side, percent) => {
+                format!("{} battery is at {}%", side_label(*side), percent)
+            }
+            DeviceNotification::ChargingStarted(side) => {
+                format!("{} battery is charging", side_label(*side))
+            }
+            DeviceNotification::ChargingFinished(side) => {
+                format!("{} battery is fully charged", side_label(*side))
+            }
+            DeviceNotification::SoundModeChanged(mode) => format!("Switched to {}", mode),
+            DeviceNotification::Disconnected(name) => format!("{} has disconnected", name),
+        }
+    }
+}
+
+fn side_label(side: BatterySide) -> &'static str {
+    match side {
+        BatterySide::Single => "The",
+        BatterySide::Left => "Left earbud",
+        BatterySide::Right => "Right earbud",
+        BatterySide::Case => "The charging case's",
+    }
+}
+
+/// Compares the last known state with the new one and returns the notifications
+/// which are enabled in the settings.
+pub fn state_notifications(
+    settings: &NotificationSettings,
+    last_state: &SoundcoreDeviceState,
+    change: &StateChange,
+) -> Vec<DeviceNotification> {
+    let mut notifications = vec![];
+
+    let mut pairs = vec![];
+    if change.contains(StateField::Battery) {
+        pairs.extend(battery_pairs(&last_state.battery, &change.state.battery));
+    }
+    if change.contains(StateField::CaseBattery) {
+        if let (Some(old), Some(new)) = (last_state.case_battery, change.state.case_battery) {
+            pairs.push((BatterySide::Case, old, new));
+        }
+    }
+
+    for (side, old, new) in pairs {
+        let (enabled, threshold) = match side {
+            BatterySide::Case => (
+                settings.low_case_battery,
+                settings.low_case_battery_threshold,
+            ),
+            _ => (settings.low_battery, settings.low_battery_threshold),
+        };
+        if let (Some(_), Some(percent)) = (old.percent(), new.percent()) {
+            if enabled && !new.charging && old.level > threshold && new.level <= threshold {
+                notifications.push(DeviceNotification::LowBattery(side, percent));
+            }
+        }
+        if settings.charging_started && !old.charging && new.charging {
+            notifications.push(DeviceNotification::ChargingStarted(side));
+        }
+        if settings.charging_finished && old.charging && !old.is_full() && new.is_full() {
+            notifications.push(DeviceNotification::ChargingFinished(side));
+        }
+    }
+
+    if settings.anc_changed
+        && change.source == ChangeSource::Device
+        && last_state.sound_mode.current != change.state.sound_mode.current
+    {
+        notifications.push(DeviceNotification::SoundModeChanged(
+            change.state.sound_mode.current,
+        ));
+    }
+
+    notifications
+}
+
+pub fn disconnect_notification(
+    settings: &NotificationSettings,
+    device_name: String,
+) -> Option<DeviceNotification> {
+    settings
+        .disconnected
+        .then_some(DeviceNotification::Disconnected(device_name))
+}
+
+fn battery_pairs(old: &Battery, new: &Battery) -> Vec<(BatterySide, SingleBattery, SingleBattery)> {
+    match (old, new) {
+        (Battery::Single(old), Battery::Single(new)) => vec![(BatterySide::Single, *old, *new)],
+        (Battery::Dual(old), Battery::Dual(new)) => vec![
+            (BatterySide::Left, old.left, new.left),
+            (BatterySide::Right, old.right, new.right),
+        ],
+        // The battery type only changes when the initial state was incomplete
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use soundcore_lib::models::{DualBattery, SoundMode};
+
+    use super::*;
+
+    fn dual_battery(left: (u8, bool), right: (u8, bool)) -> Battery {
+        Battery::Dual(DualBattery {
+            left: SingleBattery {
+                level: left.0,
+                charging: left.1,
+            },
+            right: SingleBattery {
+                level: right.0,
+                charging: right.1,
+            },
+        })
+    }
+
+    fn change(
+        source: ChangeSource,
+        last_state: &SoundcoreDeviceState,
+        new_state: SoundcoreDeviceState,
+    ) -> StateChange {
+        StateChange::new(source, last_state, &new_state).expect("The states should differ")
+    }
+
+    fn all_enabled() -> NotificationSettings {
+        NotificationSettings {
+            low_battery: true,
+            low_battery_threshold: 1,
+            low_case_battery: true,
+            low_case_battery_threshold: 2,
+            charging_started: true,
+            charging_finished: true,
+            disconnected: true,
+            anc_changed: true,
+        }
+    }
+
+    #[test]
+    fn should_notify_low_battery_once_per_bud() {
+        let last_state = SoundcoreDeviceState {
+            battery: dual_battery((2, false), (2, false)),
+            ..Default::default()
+        };
+        let low_left = SoundcoreDeviceState {
+            battery: dual_battery((1, false), (2, false)),
+            ..Default::default()
+        };
+        let still_low_left = SoundcoreDeviceState {
+            battery: dual_battery((0, false), (2, false)),
+            ..Default::default()
+        };
+
+        let first = change(ChangeSource::Device, &last_state, low_left.clone());
+        assert_eq!(
+            state_notifications(&all_enabled(), &last_state, &first),
+            vec![DeviceNotification::LowBattery(BatterySide::Left, 20)]
+        );
+
+        let second = change(ChangeSource::Device, &low_left, still_low_left);
+        assert!(state_notifications(&all_enabled(), &low_left, &second).is_empty());
+    }
+
+    #[test]
+    fn should_notify_charging_started_and_finished() {
+        let last_state = SoundcoreDeviceState {
+            battery: dual_battery((4, true), (3, false)),
+            ..Default::default()
+        };
+        let new_state = SoundcoreDeviceState {
+            battery: dual_battery((5, true), (3, true)),
+            ..Default::default()
+        };
+
+        let change = change(ChangeSource::Device, &last_state, new_state);
+        assert_eq!(
+            state_notifications(&all_enabled(), &last_state, &change),
+            vec![
+                DeviceNotification::ChargingFinished(BatterySide::Left),
+                DeviceNotification::ChargingStarted(BatterySide::Right)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_respect_disabled_events() {
+        let settings = NotificationSettings {
+            charging_started: false,
+            ..all_enabled()
+        };
+        let last_state = SoundcoreDeviceState::default();
+        let new_state = SoundcoreDeviceState {
+            battery: Battery::Single(SingleBattery {
+                level: 3,
+                charging: true,
+            }),
+            ..Default::default()
+        };
+
+        let change = change(ChangeSource::Device, &last_state, new_state);
+        assert!(state_notifications(&settings, &last_state, &change).is_empty());
+        assert_eq!(
+            disconnect_notification(
+                &NotificationSettings {
+                    disconnected: false,
+                    ..all_enabled()
+                },
+                "Soundcore Liberty 4".to_string()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn should_notify_low_case_battery_with_its_own_threshold() {
+        let last_state = SoundcoreDeviceState {
+            battery: dual_battery((3, false), (3, false)),
+            case_battery: Some(SingleBattery {
+                level: 3,
+                charging: false,
+            }),
+            ..Default::default()
+        };
+        let new_state = SoundcoreDeviceState {
+            battery: dual_battery((2, false), (2, false)),
+            case_battery: Some(SingleBattery {
+                level: 2,
+                charging: false,
+            }),
+            ..Default::default()
+        };
+
+        let change = change(ChangeSource::Device, &last_state, new_state);
+        assert_eq!(
+            state_notifications(&all_enabled(), &last_state, &change),
+            vec![DeviceNotification::LowBattery(BatterySide::Case, 40)]
+        );
+        assert!(state_notifications(
+            &NotificationSettings {
+                low_case_battery: false,
+                ..all_enabled()
+            },
+            &last_state,
+            &change
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn should_only_notify_sound_mode_changes_from_device() {
+        let last_state = SoundcoreDeviceState::default();
+        let new_state = SoundcoreDeviceState {
+            sound_mode: SoundMode {
+                current: CurrentSoundMode::ANC,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let local = change(ChangeSource::Local, &last_state, new_state.clone());
+        assert!(state_notifications(&all_enabled(), &last_state, &local).is_empty());
+
+        let device = change(ChangeSource::Device, &last_state, new_state);
+        assert_eq!(
+            state_notifications(&all_enabled(), &last_state, &device),
+            vec![DeviceNotification::SoundModeChanged(CurrentSoundMode::ANC)]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use log::{debug, warn};
+
+pub use manager_bridge::AppSettings;
+
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// Holds the app settings and persists them as JSON
+#[derive(Debug)]
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: AppSettings,
+}
+
+impl SettingsStore {
+    /// Loads the settings from the given path, falling back to the defaults
+    /// if the file is missing or can't be parsed.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Failed to parse settings file {:?}: {:?}", path, e);
+                AppSettings::default()
+            }),
+            Err(e) => {
+                debug!("Could not read settings file {:?}: {:?}", path, e);
+                AppSettings::default()
+            }
+        };
+        Self {
+            path: Some(path),
+            settings,
+        }
+    }
+
+    /// A store which is never persisted
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            settings: AppSettings::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &AppSettings {
+        &self.settings
+    }
+
+    pub fn update(&mut self, settings: AppSettings) -> io::Result<()> {
+        self.settings = settings;
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.settings)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_settings_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("soundcore-manager-{}-{}", name, std::process::id()))
+            .join(SETTINGS_FILE_NAME)
+    }
+
+    #[test]
+    fn should_fall_back_to_defaults_when_missing() {
+        let store = SettingsStore::load(temp_settings_path("missing"));
+        assert_eq!(store.settings(), &AppSettings::default());
+    }
+
+    #[test]
+    fn should_persist_settings() {
+        let path = temp_settings_path("persist");
+        let settings = AppSettings {
+            scan_duration_secs: 10,
+            ..Default::default()
+        };
+
+        let mut store = SettingsStore::load(path.clone());
+        store.update(settings.clone()).unwrap();
+
+        let reloaded = SettingsStore::load(path.clone());
+        assert_eq!(reloaded.settings(), &settings);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
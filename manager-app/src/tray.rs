@@ -1,292 +1,525 @@
-use log::debug;
+use std::collections::HashMap;
+use std::str::FromStr;
 
-use soundcore_lib::types::{SupportedModels, BatteryCharging};
+use log::{debug, warn};
 use tauri::{
-    AppHandle, CustomMenuItem, Manager, State, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    AppHandle, CustomMenuItem, Manager, Runtime, SystemTray, SystemTrayEvent, SystemTrayMenu,
     SystemTrayMenuItem, SystemTraySubmenu,
 };
 
-use crate::{
-    frontend_types::{ANCModes, NewTrayDeviceStatus},
-    SoundcoreAppState,
+use soundcore_lib::api::{SoundModeFeatures, SoundcoreDeviceState};
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::DiscoveredDevice;
+use soundcore_lib::models::{
+    ANCMode, AdaptiveANCMode, Battery, CurrentSoundMode, CustomizableTransparencyMode, EQProfile,
+    NonCustomizableTransparencyMode, SceneBasedANCMode, SingleBattery, SoundMode, TransparencyMode,
 };
 
-/* Sets the tray menu to either the basic or the extended one */
-#[tauri::command]
-pub(crate) async fn set_tray_menu(app_handle: AppHandle, is_connected: bool) {
-    debug!("Setting tray menu to connected status: {}", is_connected);
-    let tray_handle = app_handle.tray_handle();
-    /* Set appropriate menu */
-    match is_connected {
-        true => {
-            tray_handle.set_menu(build_extended_menu(None)).unwrap();
-        }
-        false => {
-            tray_handle.set_menu(build_base_tray_menu()).unwrap();
-        }
-    }
+use crate::async_bridge::{AddrWrappedPayload, BridgeCommand, SetEqualizerPayload};
+use crate::settings::AppSettings;
+use crate::{dispatch_bridge_command, SoundcoreAppState};
+
+/// Separates the parts of a menu item id, addresses already contain colons
+const ID_SEPARATOR: char = '/';
+
+/// A sound mode entry, the indices point to the allowed modes of the device's SoundModeFeatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TraySoundMode {
+    Normal,
+    Anc(usize),
+    Transparency(usize),
 }
 
-/* Handles tray menu updates coming from frontend for now */
-/* When the event system menu is done updates should be emmited from the backend to avoid unnecessary overhead */
-/* Also fixes the issue where when the window is hidden the setIntervals from the FE are not timed properly  */
-#[tauri::command]
-pub(crate) async fn set_tray_device_status(app_handle: AppHandle, status: NewTrayDeviceStatus) {
-    debug!("Updating tray menu: {:?}", status);
-    let tray_handle = app_handle.tray_handle();
-    let state: State<SoundcoreAppState> = app_handle.state();
-    /* Try to fix PoisonError bug which occurs randomly while refreshing the app */
-    /* Remove set_tray_menu and use only this command? */
-    match status.is_connected {
-        true => {
-            let model = state.model.read().await;
-            tray_handle
-                .set_menu(build_extended_menu(model.as_ref()))
-                .unwrap();
-        }
-        false => {
-            tray_handle.set_menu(build_base_tray_menu()).unwrap();
-            return;
+/// An action triggered by a tray menu item, encoded in the item's id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TrayAction {
+    ToggleWindow,
+    Quit,
+    Scan,
+    Connect(BluetoothAdrr),
+    Disconnect(BluetoothAdrr),
+    SetSoundMode(BluetoothAdrr, TraySoundMode),
+    SetEqProfile(BluetoothAdrr, EQProfile),
+}
+
+impl TrayAction {
+    pub(crate) fn id(&self) -> String {
+        match self {
+            // The id is also used by the macOS close handler in main.rs
+            TrayAction::ToggleWindow => "hide".to_string(),
+            TrayAction::Quit => "quit".to_string(),
+            TrayAction::Scan => "scan".to_string(),
+            TrayAction::Connect(addr) => format!("connect{}{}", ID_SEPARATOR, addr),
+            TrayAction::Disconnect(addr) => format!("disconnect{}{}", ID_SEPARATOR, addr),
+            TrayAction::SetSoundMode(addr, mode) => {
+                let mode = match mode {
+                    TraySoundMode::Normal => "normal".to_string(),
+                    TraySoundMode::Anc(idx) => format!("anc{}{}", ID_SEPARATOR, idx),
+                    TraySoundMode::Transparency(idx) => {
+                        format!("transparency{}{}", ID_SEPARATOR, idx)
+                    }
+                };
+                format!("sound_mode{sep}{}{sep}{}", addr, mode, sep = ID_SEPARATOR)
+            }
+            TrayAction::SetEqProfile(addr, profile) => {
+                format!("eq{sep}{}{sep}{}", addr, profile, sep = ID_SEPARATOR)
+            }
         }
     }
-    /* Update menu items */
-    let conn_status = tray_handle.get_item("conn_status");
-    let charging_status = tray_handle.get_item("batt_charging_status");
-    let battery_level = tray_handle.get_item("batt_level_status");
-    match status.is_connected {
-        true => {
-            /* When connected the menu should be the extended one already */
-            conn_status.set_title("Connected").unwrap();
-            battery_level
-                .set_title(format!(
-                    "Battery Level: L: {}% R: {}%",
-                    status.level.left * 2 * 10,
-                    status.level.right * 2 * 10
+
+    pub(crate) fn from_id(id: &str) -> Option<Self> {
+        let parts = id.split(ID_SEPARATOR).collect::<Vec<_>>();
+        match parts.as_slice() {
+            ["hide"] => Some(TrayAction::ToggleWindow),
+            ["quit"] => Some(TrayAction::Quit),
+            ["scan"] => Some(TrayAction::Scan),
+            ["connect", addr] => Some(TrayAction::Connect(BluetoothAdrr::from_str(addr).ok()?)),
+            ["disconnect", addr] => {
+                Some(TrayAction::Disconnect(BluetoothAdrr::from_str(addr).ok()?))
+            }
+            ["sound_mode", addr, mode @ ..] => {
+                let mode = match mode {
+                    ["normal"] => TraySoundMode::Normal,
+                    ["anc", idx] => TraySoundMode::Anc(idx.parse().ok()?),
+                    ["transparency", idx] => TraySoundMode::Transparency(idx.parse().ok()?),
+                    _ => return None,
+                };
+                Some(TrayAction::SetSoundMode(
+                    BluetoothAdrr::from_str(addr).ok()?,
+                    mode,
                 ))
-                .unwrap()
-        }
-        false => {
-            conn_status.set_title("Disconnected").unwrap();
+            }
+            ["eq", addr, profile] => Some(TrayAction::SetEqProfile(
+                BluetoothAdrr::from_str(addr).ok()?,
+                EQProfile::from_str(profile).ok()?,
+            )),
+            _ => None,
         }
     }
+}
 
-    let anc_items = [
-        "anc_sub_normal_mode",
-        "anc_sub_transport_mode",
-        "anc_sub_outdoor_mode",
-        "anc_sub_indoor_mode",
-        "anc_sub_fully_transparent_mode",
-        "anc_sub_vocal_mode",
-    ];
-
-    let idx_to_enable = match status.anc_mode {
-        ANCModes::NormalMode => 0,
-        ANCModes::AncTransportMode => 1,
-        ANCModes::AncOutdoorMode => 2,
-        ANCModes::AncIndoorMode => 3,
-        ANCModes::TransparencyFullyTransparentMode => 4,
-        ANCModes::TransparencyVocalMode => 5,
-        _ => 0,
-    };
+pub(crate) fn get_system_tray() -> SystemTray {
+    SystemTray::new().with_menu(build_tray_menu(
+        &HashMap::new(),
+        &[],
+        &AppSettings::default(),
+        true,
+    ))
+}
 
-    anc_items.iter().enumerate().for_each(|(idx, item)| {
-        let item = tray_handle.get_item(item);
-        if idx == idx_to_enable {
-            item.set_selected(true).unwrap();
-        } else {
-            item.set_selected(false).unwrap();
-        }
-    });
-
-    match status {
-        NewTrayDeviceStatus {
-            is_connected: true,
-            charging: BatteryCharging {
-                left: true,
-                right: true,
-            },
-            ..
-        } => {
-            // Both Charging
-            charging_status.set_title("Both Charging").unwrap();
-        }
-        NewTrayDeviceStatus {
-            is_connected: true,
-            charging: BatteryCharging {
-                left: true,
-                right: false,
-            },
-            ..
-        } => {
-            // Left Charging
-            charging_status.set_title("Left Charging").unwrap();
-        }
-        NewTrayDeviceStatus {
-            is_connected: true,
-            charging: BatteryCharging {
-                left: false,
-                right: true,
-            },
-            ..
-        } => {
-            // Right Charging
-            charging_status.set_title("Right Charging").unwrap();
-        }
-        NewTrayDeviceStatus {
-            is_connected: true,
-            charging: BatteryCharging {
-                left: false,
-                right: false,
-            },
-            ..
-        } => {
-            // Not Charging
-            charging_status.set_title("None Charging").unwrap();
+/// Rebuilds the tray menu from the latest device states
+pub(crate) async fn update_tray_menu<R: Runtime>(app_handle: &AppHandle<R>) {
+    let state = app_handle.state::<SoundcoreAppState>();
+    let window_visible = app_handle
+        .get_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(true);
+    let menu = build_tray_menu(
+        &*state.last_states.lock().await,
+        &state.last_scan.lock().await,
+        &*state.settings.lock().await,
+        window_visible,
+    );
+    if let Err(e) = app_handle.tray_handle().set_menu(menu) {
+        warn!("Failed to update tray menu: {:?}", e);
+    }
+}
+
+fn build_tray_menu(
+    states: &HashMap<BluetoothAdrr, SoundcoreDeviceState>,
+    discovered: &[DiscoveredDevice],
+    settings: &AppSettings,
+    window_visible: bool,
+) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new();
+
+    let mut connected = states.iter().collect::<Vec<_>>();
+    connected.sort_by(|(a, _), (b, _)| a.cmp(b));
+    if connected.is_empty() {
+        menu = menu.add_item(CustomMenuItem::new("no_devices", "No devices connected").disabled());
+    }
+    for (addr, state) in connected {
+        menu = menu.add_submenu(SystemTraySubmenu::new(
+            settings.display_name(addr),
+            build_device_menu(addr, state),
+        ));
+    }
+
+    let mut connect_menu = SystemTrayMenu::new();
+    for device in discovered
+        .iter()
+        .filter(|device| !states.contains_key(&device.descriptor.addr))
+    {
+        let addr = &device.descriptor.addr;
+        let name = settings
+            .device(addr)
+            .and_then(|preferences| preferences.nickname.clone())
+            .unwrap_or_else(|| device.descriptor.name.clone());
+        connect_menu = connect_menu.add_item(CustomMenuItem::new(
+            TrayAction::Connect(addr.to_owned()).id(),
+            name,
+        ));
+    }
+    connect_menu = connect_menu
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(
+            TrayAction::Scan.id(),
+            "Scan for devices",
+        ));
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_submenu(SystemTraySubmenu::new("Connect", connect_menu))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(
+            TrayAction::ToggleWindow.id(),
+            if window_visible { "Hide" } else { "Show" },
+        ))
+        .add_item(CustomMenuItem::new(TrayAction::Quit.id(), "Quit"))
+}
+
+fn build_device_menu(addr: &BluetoothAdrr, state: &SoundcoreDeviceState) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("battery", battery_label(&state.battery)).disabled());
+
+    if let Some(features) = &state.feature_set.sound_mode_features {
+        menu = menu.add_native_item(SystemTrayMenuItem::Separator);
+        for (mode, label) in sound_mode_entries(features) {
+            let mut item =
+                CustomMenuItem::new(TrayAction::SetSoundMode(addr.to_owned(), mode).id(), label);
+            if resolve_sound_mode(state.sound_mode, features, mode) == Some(state.sound_mode) {
+                item = item.selected();
+            }
+            menu = menu.add_item(item);
         }
-        _ => {
-            charging_status.set_title("Not Charging").unwrap();
+    }
+
+    if state.feature_set.equalizer_features.is_some() {
+        let current_profile = state.eq_configuration.get_profile();
+        let mut eq_menu = SystemTrayMenu::new();
+        for profile in EQProfile::regular_profiles() {
+            let mut item = CustomMenuItem::new(
+                TrayAction::SetEqProfile(addr.to_owned(), profile).id(),
+                profile.to_string(),
+            );
+            if profile == current_profile {
+                item = item.selected();
+            }
+            eq_menu = eq_menu.add_item(item);
         }
+        menu = menu.add_submenu(SystemTraySubmenu::new("Equalizer", eq_menu));
     }
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(
+            TrayAction::Disconnect(addr.to_owned()).id(),
+            "Disconnect",
+        ))
 }
 
-/* Menu used while disconnected */
-fn build_base_tray_menu() -> SystemTrayMenu {
-    let conn_status = CustomMenuItem::new("conn_status".to_string(), "Disconnected").disabled();
-    let hide = CustomMenuItem::new("hide".to_string(), "Hide");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    SystemTrayMenu::new()
-        .add_item(conn_status)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(hide)
-        .add_item(quit)
+pub(crate) fn sound_mode_entries(features: &SoundModeFeatures) -> Vec<(TraySoundMode, String)> {
+    let anc = features
+        .allowed_anc_modes()
+        .iter()
+        .enumerate()
+        .map(|(idx, mode)| (TraySoundMode::Anc(idx), anc_mode_label(mode)));
+    let normal = features
+        .has_normal_mode()
+        .then(|| (TraySoundMode::Normal, "Normal".to_string()));
+    let transparency = features
+        .allowed_transparency_modes()
+        .iter()
+        .enumerate()
+        .map(|(idx, mode)| {
+            (
+                TraySoundMode::Transparency(idx),
+                transparency_mode_label(mode),
+            )
+        });
+    anc.chain(normal).chain(transparency).collect()
 }
 
-fn build_anc_menu(model: &SupportedModels) -> SystemTrayMenu {
-    let normal_mode = CustomMenuItem::new("anc_sub_normal_mode".to_string(), "Normal Mode");
-    let transport_mode =
-        CustomMenuItem::new("anc_sub_transport_mode".to_string(), "ANC: Transport Mode");
-    let outdoor_mode = CustomMenuItem::new("anc_sub_outdoor_mode".to_string(), "ANC: Outdoor Mode");
-    let indoor_mode = CustomMenuItem::new("anc_sub_indoor_mode".to_string(), "ANC: Indoor Mode");
-    let fully_transparent = CustomMenuItem::new(
-        "anc_sub_fully_transparent_mode".to_string(),
-        "Transparency: Fully Transparent Mode",
-    );
-    let vocal_mode =
-        CustomMenuItem::new("anc_sub_vocal_mode".to_string(), "Transparency: Vocal Mode");
-
-    match model {
-        SupportedModels::A3951 => SystemTrayMenu::new()
-            .add_item(indoor_mode)
-            .add_item(outdoor_mode)
-            .add_item(transport_mode)
-            .add_native_item(SystemTrayMenuItem::Separator)
-            .add_item(normal_mode)
-            .add_native_item(SystemTrayMenuItem::Separator)
-            .add_item(fully_transparent)
-            .add_item(vocal_mode),
-        SupportedModels::A3027 | SupportedModels::A3028 | SupportedModels::A3029 => {
-            SystemTrayMenu::new()
-                .add_item(indoor_mode)
-                .add_item(outdoor_mode)
-                .add_item(transport_mode)
-                .add_native_item(SystemTrayMenuItem::Separator)
-                .add_item(normal_mode)
-                .add_native_item(SystemTrayMenuItem::Separator)
-                .add_item(fully_transparent)
+/// Applies the tray entry on top of the current sound mode, keeping the custom values
+pub(crate) fn resolve_sound_mode(
+    current: SoundMode,
+    features: &SoundModeFeatures,
+    mode: TraySoundMode,
+) -> Option<SoundMode> {
+    match mode {
+        TraySoundMode::Normal => features.has_normal_mode().then_some(SoundMode {
+            current: CurrentSoundMode::Normal,
+            ..current
+        }),
+        TraySoundMode::Anc(idx) => {
+            features
+                .allowed_anc_modes()
+                .get(idx)
+                .map(|anc_mode| SoundMode {
+                    current: CurrentSoundMode::ANC,
+                    anc_mode: *anc_mode,
+                    ..current
+                })
+        }
+        TraySoundMode::Transparency(idx) => {
+            features
+                .allowed_transparency_modes()
+                .get(idx)
+                .map(|trans_mode| SoundMode {
+                    current: CurrentSoundMode::Transparency,
+                    trans_mode: *trans_mode,
+                    ..current
+                })
         }
-        _ => SystemTrayMenu::new()
-            .add_item(CustomMenuItem::new("unsoported".to_string(), "Not supported"))
     }
 }
 
-/* Menu used while connected */
-fn build_extended_menu(model: Option<&SupportedModels>) -> SystemTrayMenu {
-    let conn_status = CustomMenuItem::new("conn_status".to_string(), "Disconnected").disabled();
-    let anc_submenu = match model {
-        Some(model) => SystemTraySubmenu::new("ANC Profiles", build_anc_menu(model)),
-        None => SystemTraySubmenu::new("ANC Profiles", SystemTrayMenu::new()),
+fn anc_mode_label(mode: &ANCMode) -> String {
+    let name = match mode {
+        ANCMode::SceneBased(SceneBasedANCMode::Transport) => "Transport",
+        ANCMode::SceneBased(SceneBasedANCMode::Outdoor) => "Outdoor",
+        ANCMode::SceneBased(SceneBasedANCMode::Indoor) => "Indoor",
+        ANCMode::SceneBased(SceneBasedANCMode::Custom) => "Custom",
+        ANCMode::Adaptive(AdaptiveANCMode::Adaptive) => "Adaptive",
+        ANCMode::Adaptive(AdaptiveANCMode::Custom) => "Custom",
     };
-    let batt_charging_status = CustomMenuItem::new(
-        "batt_charging_status".to_string(),
-        "Battery: Is it charging?",
-    )
-    .disabled();
-    let batt_level =
-        CustomMenuItem::new("batt_level_status".to_string(), "Battery: L:?% R:?%").disabled();
-    let hide = CustomMenuItem::new("hide".to_string(), "Hide");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    SystemTrayMenu::new()
-        .add_item(conn_status)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_submenu(anc_submenu)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(batt_charging_status)
-        .add_item(batt_level)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(hide)
-        .add_item(quit)
+    format!("ANC: {}", name)
 }
 
-/* Gets called on init, we are disconnected so return base menu */
-pub(crate) fn get_system_tray() -> SystemTray {
-    SystemTray::new().with_menu(build_base_tray_menu())
+fn transparency_mode_label(mode: &TransparencyMode) -> String {
+    let name = match mode {
+        TransparencyMode::NonCustomizable(NonCustomizableTransparencyMode::FullyTransparent) => {
+            "Fully Transparent"
+        }
+        TransparencyMode::NonCustomizable(NonCustomizableTransparencyMode::Vocal) => "Vocal",
+        TransparencyMode::Customizable(CustomizableTransparencyMode::TalkMode) => "Talk",
+        TransparencyMode::Customizable(CustomizableTransparencyMode::Custom) => "Custom",
+    };
+    format!("Transparency: {}", name)
 }
 
-fn handle_anc_submenu(app: &AppHandle, id: String) {
-    let anc_mode = match id.as_str() {
-        "anc_sub_normal_mode" => ANCModes::NormalMode,
-        "anc_sub_transport_mode" => ANCModes::AncTransportMode,
-        "anc_sub_outdoor_mode" => ANCModes::AncOutdoorMode,
-        "anc_sub_indoor_mode" => ANCModes::AncIndoorMode,
-        "anc_sub_fully_transparent_mode" => ANCModes::TransparencyFullyTransparentMode,
-        "anc_sub_vocal_mode" => ANCModes::TransparencyVocalMode,
-        _ => ANCModes::NormalMode,
-    };
-    app.emit_all("anc_sub_change", anc_mode).unwrap();
+pub(crate) fn battery_label(battery: &Battery) -> String {
+    fn level(battery: &SingleBattery) -> String {
+        let level = match battery.percent() {
+            Some(percent) => format!("{}%", percent),
+            None => "?%".to_string(),
+        };
+        match battery.charging {
+            true => format!("{} (charging)", level),
+            false => level,
+        }
+    }
+
+    match battery {
+        Battery::Single(battery) => format!("Battery: {}", level(battery)),
+        Battery::Dual(battery) => format!(
+            "Battery: L {} R {}",
+            level(&battery.left),
+            level(&battery.right)
+        ),
+    }
 }
 
-/* Tray event handler */
-pub(crate) fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
+pub(crate) fn handle_tray_event<R: Runtime>(app: &AppHandle<R>, event: SystemTrayEvent) {
     match event {
         SystemTrayEvent::MenuItemClick { id, .. } => {
-            let item_handle = app.tray_handle().get_item(&id);
             debug!("Menu item clicked: {}", id);
-            if id.starts_with("anc_sub") {
-                handle_anc_submenu(&app.clone(), id);
-                return;
-            }
-            match id.as_str() {
-                "hide" => {
-                    let window = app.get_window("main").unwrap_or_else(|| panic!("Could not get window"));
-                    match window.is_visible() {
-                        Ok(true) => {
-                            window.hide().unwrap();
-                            item_handle.set_title("Show").unwrap();
-                        }
-                        Ok(false) => {
-                            window.show().unwrap();
-                            item_handle.set_title("Hide").unwrap();
-                        },
-                        Err(e) => {
-                            panic!("Could not get window visibility: {}", e);
+            match TrayAction::from_id(&id) {
+                Some(TrayAction::ToggleWindow) => toggle_window(app, &id),
+                Some(TrayAction::Quit) => app.exit(0),
+                Some(action) => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = dispatch_tray_action(&app, action).await {
+                            warn!("Failed to handle tray action: {}", e);
                         }
-                    }
+                    });
                 }
-                "quit" => {
-                    app.exit(0);
-                }
-                _ => {}
+                None => debug!("No action for tray item {}", id),
             }
         }
         SystemTrayEvent::LeftClick { .. } => {
-            let window = app.get_window("main").unwrap();
-            app.tray_handle()
-                .get_item("hide")
-                .set_title("Hide")
-                .unwrap();
-            window.show().unwrap();
-            window.set_focus().unwrap();
+            if let Some(window) = app.get_window("main") {
+                let _ = app
+                    .tray_handle()
+                    .get_item(&TrayAction::ToggleWindow.id())
+                    .set_title("Hide");
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
         }
         _ => {}
     }
 }
+
+fn toggle_window<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    let Some(window) = app.get_window("main") else {
+        warn!("Could not get the main window");
+        return;
+    };
+    let item_handle = app.tray_handle().get_item(id);
+    let res = match window.is_visible() {
+        Ok(true) => window.hide().and_then(|_| item_handle.set_title("Show")),
+        Ok(false) => window.show().and_then(|_| item_handle.set_title("Hide")),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = res {
+        warn!("Could not toggle window visibility: {:?}", e);
+    }
+}
+
+async fn dispatch_tray_action<R: Runtime>(
+    app: &AppHandle<R>,
+    action: TrayAction,
+) -> Result<(), String> {
+    let state = app.state::<SoundcoreAppState>();
+    let command = match action {
+        TrayAction::Scan => BridgeCommand::Scan,
+        TrayAction::Connect(addr) => state
+            .last_scan
+            .lock()
+            .await
+            .iter()
+            .find(|device| device.descriptor.addr == addr)
+            .cloned()
+            .map(BridgeCommand::Connect)
+            .ok_or(format!("Device {} was not found in the last scan", addr))?,
+        TrayAction::Disconnect(addr) => BridgeCommand::Disconnect(addr),
+        TrayAction::SetSoundMode(addr, mode) => {
+            let states = state.last_states.lock().await;
+            let device_state = states
+                .get(&addr)
+                .ok_or(format!("Device {} is not connected", addr))?;
+            let sound_mode = device_state
+                .feature_set
+                .sound_mode_features
+                .as_ref()
+                .and_then(|features| resolve_sound_mode(device_state.sound_mode, features, mode))
+                .ok_or(format!(
+                    "Sound mode {:?} is not supported by {}",
+                    mode, addr
+                ))?;
+            BridgeCommand::SetSoundMode(AddrWrappedPayload {
+                addr,
+                payload: sound_mode,
+            })
+        }
+        TrayAction::SetEqProfile(addr, profile) => {
+            BridgeCommand::SetEqualizer(AddrWrappedPayload {
+                addr,
+                payload: SetEqualizerPayload::SetEqualizerPreset(profile),
+            })
+        }
+        TrayAction::ToggleWindow | TrayAction::Quit => return Ok(()),
+    };
+    dispatch_bridge_command(&state, command).await
+}
+
+#[cfg(test)]
+mod test {
+    use soundcore_lib::models::DualBattery;
+
+    use super::*;
+
+    fn addr() -> BluetoothAdrr {
+        BluetoothAdrr::from_str("AC:12:2F:6A:D2:07").unwrap()
+    }
+
+    #[test]
+    fn should_roundtrip_action_ids() {
+        let actions = [
+            TrayAction::ToggleWindow,
+            TrayAction::Quit,
+            TrayAction::Scan,
+            TrayAction::Connect(addr()),
+            TrayAction::Disconnect(addr()),
+            TrayAction::SetSoundMode(addr(), TraySoundMode::Normal),
+            TrayAction::SetSoundMode(addr(), TraySoundMode::Anc(2)),
+            TrayAction::SetSoundMode(addr(), TraySoundMode::Transparency(1)),
+            TrayAction::SetEqProfile(addr(), EQProfile::HipHop),
+        ];
+        for action in actions {
+            assert_eq!(TrayAction::from_id(&action.id()), Some(action));
+        }
+    }
+
+    #[test]
+    fn should_ignore_unknown_ids() {
+        assert_eq!(TrayAction::from_id("battery"), None);
+        assert_eq!(TrayAction::from_id("connect/not-an-address"), None);
+        assert_eq!(
+            TrayAction::from_id("sound_mode/AC:12:2F:6A:D2:07/anc"),
+            None
+        );
+        assert_eq!(
+            TrayAction::from_id("eq/AC:12:2F:6A:D2:07/NotAProfile"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_list_sound_modes_from_features() {
+        let features =
+            SoundModeFeatures::scene_based_non_customizable_anc_non_customizable_transparency();
+        let labels = sound_mode_entries(&features)
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                "ANC: Indoor",
+                "ANC: Outdoor",
+                "ANC: Transport",
+                "Normal",
+                "Transparency: Fully Transparent",
+                "Transparency: Vocal"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_sound_mode_from_entry() {
+        let features =
+            SoundModeFeatures::scene_based_non_customizable_anc_non_customizable_transparency();
+        let current = SoundMode::default();
+
+        let anc = resolve_sound_mode(current, &features, TraySoundMode::Anc(1)).unwrap();
+        assert_eq!(anc.current, CurrentSoundMode::ANC);
+        assert_eq!(
+            anc.anc_mode,
+            ANCMode::SceneBased(SceneBasedANCMode::Outdoor)
+        );
+        assert_eq!(anc.trans_mode, current.trans_mode);
+
+        let transparency =
+            resolve_sound_mode(current, &features, TraySoundMode::Transparency(1)).unwrap();
+        assert_eq!(transparency.current, CurrentSoundMode::Transparency);
+        assert_eq!(
+            transparency.trans_mode,
+            TransparencyMode::NonCustomizable(NonCustomizableTransparencyMode::Vocal)
+        );
+
+        assert_eq!(
+            resolve_sound_mode(current, &features, TraySoundMode::Anc(10)),
+            None
+        );
+    }
+
+    #[test]
+    fn should_format_battery_label() {
+        let battery = Battery::Dual(DualBattery {
+            left: SingleBattery {
+                level: 4,
+                charging: true,
+            },
+            right: SingleBattery {
+                level: 2,
+                charging: false,
+            },
+        });
+        assert_eq!(battery_label(&battery), "Battery: L 80% (charging) R 40%");
+        assert_eq!(
+            battery_label(&Battery::Single(SingleBattery::default())),
+            "Battery: ?%"
+        );
+    }
+}
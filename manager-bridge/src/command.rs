@@ -0,0 +1,90 @@
+use serde::Deserialize;
+use typeshare::typeshare;
+
+use soundcore_lib::api::{DeviceConfigSnapshot, EQPreset};
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::DiscoveredDevice;
+use soundcore_lib::models::{EQConfiguration, EQProfile, MonoEQ, SoundMode, SoundModeCycle};
+
+use crate::AppSettings;
+
+#[typeshare]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", tag = "command", content = "payload")]
+pub enum BridgeCommand {
+    Scan,
+    Connect(DiscoveredDevice),
+    Disconnect(BluetoothAdrr),
+    DisconnectAll,
+    GetSnapshot,
+    SetSoundMode(AddrWrappedPayload<SoundMode>),
+    /// Sets the sound modes the device's button cycles through
+    SetSoundModeCycle(AddrWrappedPayload<SoundModeCycle>),
+    SetEqualizer(AddrWrappedPayload<SetEqualizerPayload>),
+    GetSettings,
+    UpdateSettings(AppSettings),
+    /// Saves the preset, validated against the equalizer of the given device
+    SaveEqPreset(AddrWrappedPayload<EQPreset>),
+    ImportEqPresets(AddrWrappedPayload<Vec<EQPreset>>),
+    RenameEqPreset(RenameEqPresetPayload),
+    DeleteEqPreset(String),
+    /// Applies the preset with the given name
+    ApplyEqPreset(AddrWrappedPayload<String>),
+    /// Computes the frequency response of the device's current EQ for plotting
+    GetEqFrequencyResponse(BluetoothAdrr),
+    ExportConfig(BluetoothAdrr),
+    /// Applies a config exported from the same or a compatible model
+    ApplyConfig(AddrWrappedPayload<DeviceConfigSnapshot>),
+}
+#[derive(Debug, Deserialize, Clone)]
+#[typeshare]
+#[serde(rename_all = "camelCase")]
+pub struct AddrWrappedPayload<T> {
+    pub addr: BluetoothAdrr,
+    pub payload: T,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[typeshare]
+#[serde(rename_all = "camelCase")]
+pub struct RenameEqPresetPayload {
+    pub name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[typeshare]
+#[serde(rename_all = "camelCase", tag = "command", content = "payload")]
+pub enum SetEqualizerPayload {
+    SetCustomEqualizer(Vec<i8>),
+    SetCustomStereoEqualizer(StereoEqualizerValues),
+    SetEqualizerPreset(EQProfile),
+}
+
+/// Separate curves for the left and right channels, in the same range as SetCustomEqualizer
+#[derive(Debug, Deserialize, Clone)]
+#[typeshare]
+#[serde(rename_all = "camelCase")]
+pub struct StereoEqualizerValues {
+    pub left: Vec<i8>,
+    pub right: Vec<i8>,
+}
+
+impl From<SetEqualizerPayload> for EQConfiguration {
+    fn from(payload: SetEqualizerPayload) -> Self {
+        match payload {
+            SetEqualizerPayload::SetCustomEqualizer(values) => {
+                EQConfiguration::mono_custom(MonoEQ::from_signed_bytes(values))
+            }
+            SetEqualizerPayload::SetCustomStereoEqualizer(values) => {
+                EQConfiguration::stereo_custom(
+                    MonoEQ::from_signed_bytes(values.left),
+                    MonoEQ::from_signed_bytes(values.right),
+                )
+            }
+            SetEqualizerPayload::SetEqualizerPreset(profile) => {
+                EQConfiguration::stereo_with_profile(profile)
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! The messages exchanged between the UI and a backend managing the devices,
+//! shared by the Tauri app and the HTTP server.
+
+mod command;
+mod response;
+mod settings;
+
+pub use command::*;
+pub use response::*;
+pub use settings::*;
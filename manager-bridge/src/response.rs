@@ -0,0 +1,83 @@
+use serde::Serialize;
+
+use soundcore_lib::api::{
+    ConfigApplyReport, DeviceConfigSnapshot, EQFrequencyResponse, SoundcoreDeviceState, StateChange,
+};
+use soundcore_lib::ble::BLEAdapterEvent;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::{DeviceSnapshot, DiscoveredDevice};
+use typeshare::typeshare;
+
+use crate::AppSettings;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase", tag = "kind", content = "payload")]
+#[typeshare]
+pub enum BridgeResponse {
+    ScanResult(Vec<DiscoveredDevice>),
+    ConnectionEstablished(TaggedStateResponse),
+    NewState(TaggedStateResponse),
+    StateChanged(TaggedStateChange),
+    Disconnected(BluetoothAdrr),
+    DisconnectedAll,
+    Snapshot(Vec<DeviceSnapshot>),
+    AdapterEvent(BLEAdapterEvent),
+    ConnectionFailed(ConnectionFailedResponse),
+    GenericError(String),
+    DeviceNotFound(BluetoothAdrr),
+    SoundModeUpdated(BluetoothAdrr),
+    SoundModeCycleUpdated(BluetoothAdrr),
+    EqualizerUpdated(BluetoothAdrr),
+    Settings(AppSettings),
+    EqFrequencyResponse(TaggedEqFrequencyResponse),
+    ConfigExported(TaggedConfigSnapshot),
+    ConfigApplied(TaggedConfigApplyReport),
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaggedStateResponse {
+    pub addr: BluetoothAdrr,
+    pub state: SoundcoreDeviceState,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaggedStateChange {
+    pub addr: BluetoothAdrr,
+    pub change: StateChange,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaggedEqFrequencyResponse {
+    pub addr: BluetoothAdrr,
+    pub response: EQFrequencyResponse,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaggedConfigSnapshot {
+    pub addr: BluetoothAdrr,
+    pub snapshot: DeviceConfigSnapshot,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaggedConfigApplyReport {
+    pub addr: BluetoothAdrr,
+    pub report: ConfigApplyReport,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct ConnectionFailedResponse {
+    pub addr: BluetoothAdrr,
+    pub reason: String,
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use soundcore_lib::api::EQPresetLibrary;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::models::EQProfile;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+#[typeshare]
+pub struct AppSettings {
+    /// Stored as a list since BluetoothAdrr can't be used as a JSON object key
+    pub devices: Vec<DevicePreferences>,
+    pub notifications: NotificationSettings,
+    pub scan_duration_secs: u32,
+    pub eq_presets: EQPresetLibrary,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            devices: vec![],
+            notifications: NotificationSettings::default(),
+            scan_duration_secs: 5,
+            eq_presets: EQPresetLibrary::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn scan_duration(&self) -> Duration {
+        Duration::from_secs(self.scan_duration_secs.into())
+    }
+
+    pub fn device(&self, addr: &BluetoothAdrr) -> Option<&DevicePreferences> {
+        self.devices.iter().find(|device| device.addr == *addr)
+    }
+
+    /// The nickname of the device if one is set, otherwise its address
+    pub fn display_name(&self, addr: &BluetoothAdrr) -> String {
+        self.device(addr)
+            .and_then(|device| device.nickname.clone())
+            .unwrap_or_else(|| addr.to_string())
+    }
+
+    pub fn auto_connect_devices(&self) -> Vec<BluetoothAdrr> {
+        self.devices
+            .iter()
+            .filter(|device| device.auto_connect)
+            .map(|device| device.addr.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct DevicePreferences {
+    pub addr: BluetoothAdrr,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub auto_connect: bool,
+    #[serde(default)]
+    pub favorite_eq_profiles: Vec<EQProfile>,
+}
+
+/// Per-event toggles for desktop notifications
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+#[typeshare]
+pub struct NotificationSettings {
+    pub low_battery: bool,
+    /// A low battery notification is shown once a bud drops to this level (0-5)
+    pub low_battery_threshold: u8,
+    pub low_case_battery: bool,
+    /// Same as `low_battery_threshold`, for the charging case
+    pub low_case_battery_threshold: u8,
+    pub charging_started: bool,
+    pub charging_finished: bool,
+    pub disconnected: bool,
+    /// Only changes made using the headset buttons are reported
+    pub anc_changed: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            low_battery: true,
+            low_battery_threshold: 1,
+            low_case_battery: true,
+            low_case_battery_threshold: 1,
+            charging_started: false,
+            charging_finished: true,
+            disconnected: true,
+            anc_changed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod settings_tests {
+    use super::*;
+
+    fn device_preferences(auto_connect: bool) -> DevicePreferences {
+        DevicePreferences {
+            addr: BluetoothAdrr::default(),
+            nickname: Some("Work buds".to_string()),
+            auto_connect,
+            favorite_eq_profiles: vec![EQProfile::Acoustic, EQProfile::Podcast],
+        }
+    }
+
+    #[test]
+    fn should_fill_in_missing_fields() {
+        let settings: AppSettings = serde_json::from_str(r#"{"scanDurationSecs": 3}"#).unwrap();
+        assert_eq!(settings.scan_duration(), Duration::from_secs(3));
+        assert_eq!(settings.notifications, NotificationSettings::default());
+        assert!(settings.devices.is_empty());
+    }
+
+    #[test]
+    fn should_list_auto_connect_devices() {
+        let settings = AppSettings {
+            devices: vec![device_preferences(true)],
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.auto_connect_devices(),
+            vec![BluetoothAdrr::default()]
+        );
+        assert_eq!(
+            settings.display_name(&BluetoothAdrr::default()),
+            "Work buds".to_string()
+        );
+
+        let settings = AppSettings {
+            devices: vec![device_preferences(false)],
+            ..Default::default()
+        };
+        assert!(settings.auto_connect_devices().is_empty());
+    }
+}
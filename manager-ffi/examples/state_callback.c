@@ -0,0 +1,55 @@
+/*
+ * Connects to a device, prints its state changes and applies an EQ preset.
+ *
+ *   cargo build -p manager-ffi
+ *   cc examples/state_callback.c -Iinclude -L../target/debug -lsoundcore_ffi -o state_callback
+ *   LD_LIBRARY_PATH=../target/debug ./state_callback AC:12:2F:00:00:01
+ */
+#include <stdio.h>
+#include <unistd.h>
+
+#include "soundcore_ffi.h"
+
+static void print_state(const char *addr, const char *state_json, void *user_data) {
+    (void)user_data;
+    printf("%s: %s\n", addr, state_json);
+}
+
+static int check(SoundcoreStatus status, const char *action) {
+    if (status != SOUNDCORE_STATUS_OK) {
+        const char *error = soundcore_last_error();
+        fprintf(stderr, "Failed to %s (%d): %s\n", action, status, error ? error : "unknown");
+        return 1;
+    }
+    return 0;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "Usage: %s <address>\n", argv[0]);
+        return 2;
+    }
+    const char *addr = argv[1];
+
+    SoundcoreManager *manager = NULL;
+    if (check(soundcore_manager_new(&manager), "create the manager")) {
+        return 1;
+    }
+    int failed = check(soundcore_set_state_callback(manager, print_state, NULL), "register the callback");
+
+    char *discovered = NULL;
+    failed = failed || check(soundcore_scan(manager, 5000, &discovered), "scan");
+    if (!failed) {
+        printf("Discovered: %s\n", discovered);
+        soundcore_string_free(discovered);
+    }
+    failed = failed || check(soundcore_connect(manager, addr), "connect");
+    failed = failed || check(soundcore_set_eq_profile(manager, addr, "SoundcoreSignature"), "set the EQ");
+
+    if (!failed) {
+        /* States are delivered from a thread of the library */
+        sleep(1);
+    }
+    soundcore_manager_free(manager);
+    return failed;
+}
@@ -0,0 +1,125 @@
+#ifndef SOUNDCORE_FFI_H
+#define SOUNDCORE_FFI_H
+
+/* Generated by cbindgen from manager-ffi, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum SoundcoreStatus {
+  SOUNDCORE_STATUS_OK = 0,
+  // A null pointer, invalid UTF-8 or invalid JSON was passed
+  SOUNDCORE_STATUS_INVALID_ARGUMENT = 1,
+  // The device isn't connected, or wasn't found by the last scan
+  SOUNDCORE_STATUS_DEVICE_NOT_FOUND = 2,
+  SOUNDCORE_STATUS_DEVICE_ERROR = 3,
+  // A panic was caught at the FFI boundary
+  SOUNDCORE_STATUS_PANIC = 4,
+} SoundcoreStatus;
+
+// The devices and the runtime driving them, created with `soundcore_manager_new`
+typedef struct SoundcoreManager SoundcoreManager;
+
+// Called with the address, e.g. `AC:12:2F:00:00:01`, and the JSON `SoundcoreDeviceState`
+// of a device whenever its state changes. Both strings are only valid during the call.
+// It's called from a dedicated thread of the library, one state at a time, so it may call
+// the other functions, except `soundcore_set_state_callback` and `soundcore_manager_free`.
+typedef void (*SoundcoreStateCallback)(const char *addr, const char *state_json, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The message of the last error on the calling thread, or null if the last call succeeded.
+// The string is owned by the library and valid until the next call on the same thread.
+const char *soundcore_last_error(void);
+
+// Creates a manager for the enabled backend, to be freed with `soundcore_manager_free`
+//
+// # Safety
+// `out` must be a valid pointer
+enum SoundcoreStatus soundcore_manager_new(struct SoundcoreManager **out);
+
+// Disconnects all devices and frees the manager, null is ignored
+//
+// # Safety
+// `manager` must have been created by `soundcore_manager_new` and not freed before,
+// and mustn't be freed from the state callback
+void soundcore_manager_free(struct SoundcoreManager *manager);
+
+// Scans for devices, for `duration_ms` or the backend's default duration if 0.
+// Writes the JSON array of the `DiscoveredDevice`s to `out_json`.
+//
+// # Safety
+// `manager` and `out_json` must be valid pointers
+enum SoundcoreStatus soundcore_scan(const struct SoundcoreManager *manager,
+                                    uint32_t duration_ms,
+                                    char **out_json);
+
+// Connects to a device found by the last scan
+//
+// # Safety
+// `manager` must be a valid pointer and `addr` a null-terminated string
+enum SoundcoreStatus soundcore_connect(const struct SoundcoreManager *manager, const char *addr);
+
+// # Safety
+// `manager` must be a valid pointer and `addr` a null-terminated string
+enum SoundcoreStatus soundcore_disconnect(const struct SoundcoreManager *manager, const char *addr);
+
+// Writes the JSON `SoundcoreDeviceState` of a connected device to `out_json`
+//
+// # Safety
+// `manager` and `out_json` must be valid pointers and `addr` a null-terminated string
+enum SoundcoreStatus soundcore_get_state(const struct SoundcoreManager *manager,
+                                         const char *addr,
+                                         char **out_json);
+
+// Sets the sound mode from the JSON of a `SoundMode`
+//
+// # Safety
+// `manager` must be a valid pointer, `addr` and `sound_mode_json` null-terminated strings
+enum SoundcoreStatus soundcore_set_sound_mode(const struct SoundcoreManager *manager,
+                                              const char *addr,
+                                              const char *sound_mode_json);
+
+// Applies an EQ preset to both channels, e.g. `SoundcoreSignature`
+//
+// # Safety
+// `manager` must be a valid pointer, `addr` and `profile` null-terminated strings
+enum SoundcoreStatus soundcore_set_eq_profile(const struct SoundcoreManager *manager,
+                                              const char *addr,
+                                              const char *profile);
+
+// Sets the EQ from the JSON of an `EQConfiguration`
+//
+// # Safety
+// `manager` must be a valid pointer, `addr` and `eq_json` null-terminated strings
+enum SoundcoreStatus soundcore_set_eq(const struct SoundcoreManager *manager,
+                                      const char *addr,
+                                      const char *eq_json);
+
+// Registers the callback receiving the states of all connected devices,
+// replacing the previous one. A null callback unregisters it.
+// Returns once the previous callback can no longer be called, which is why calling it
+// from the state callback fails with `SoundcoreStatus::InvalidArgument`.
+//
+// # Safety
+// `manager` must be a valid pointer, `user_data` must be usable from any thread
+// until the callback is replaced or the manager freed
+enum SoundcoreStatus soundcore_set_state_callback(const struct SoundcoreManager *manager,
+                                                  SoundcoreStateCallback callback,
+                                                  void *user_data);
+
+// Frees a string returned by the library, null is ignored
+//
+// # Safety
+// `s` must have been returned by this library and not freed before
+void soundcore_string_free(char *s);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* SOUNDCORE_FFI_H */
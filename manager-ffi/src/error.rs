@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::fmt::Display;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use soundcore_lib::error::SoundcoreLibError;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundcoreStatus {
+    Ok = 0,
+    /// A null pointer, invalid UTF-8 or invalid JSON was passed
+    InvalidArgument = 1,
+    /// The device isn't connected, or wasn't found by the last scan
+    DeviceNotFound = 2,
+    DeviceError = 3,
+    /// A panic was caught at the FFI boundary
+    Panic = 4,
+}
+
+#[derive(Debug)]
+pub(crate) enum FfiError {
+    InvalidArgument(String),
+    DeviceNotFound(String),
+    Device(SoundcoreLibError),
+}
+
+pub(crate) type FfiResult<T> = Result<T, FfiError>;
+
+impl From<SoundcoreLibError> for FfiError {
+    fn from(e: SoundcoreLibError) -> Self {
+        FfiError::Device(e)
+    }
+}
+
+impl Display for FfiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FfiError::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            FfiError::DeviceNotFound(addr) => write!(f, "Device {} not found", addr),
+            FfiError::Device(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl FfiError {
+    fn status(&self) -> SoundcoreStatus {
+        match self {
+            FfiError::InvalidArgument(_) => SoundcoreStatus::InvalidArgument,
+            FfiError::DeviceNotFound(_) => SoundcoreStatus::DeviceNotFound,
+            FfiError::Device(_) => SoundcoreStatus::DeviceError,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: Option<String>) {
+    let message = message.map(|m| CString::new(m.replace('\0', "")).unwrap_or_default());
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Runs the body of an exported function, recording its error for `soundcore_last_error`.
+/// Panics are reported as `SoundcoreStatus::Panic`, since unwinding into C is undefined behavior.
+pub(crate) fn ffi_call(f: impl FnOnce() -> FfiResult<()>) -> SoundcoreStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            set_last_error(None);
+            SoundcoreStatus::Ok
+        }
+        Ok(Err(e)) => {
+            set_last_error(Some(e.to_string()));
+            e.status()
+        }
+        Err(_) => {
+            set_last_error(Some("Panicked, see the logs".to_string()));
+            SoundcoreStatus::Panic
+        }
+    }
+}
+
+/// The message of the last error on the calling thread, or null if the last call succeeded.
+/// The string is owned by the library and valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn soundcore_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
@@ -0,0 +1,15 @@
+//! A C ABI for soundcore-lib. Devices are driven through an opaque `SoundcoreManager` handle,
+//! states and settings are exchanged as the JSON of the soundcore-lib types.
+//!
+//! Every fallible function returns a `SoundcoreStatus`, the message of the last error on the
+//! calling thread is available from `soundcore_last_error`. The header `include/soundcore_ffi.h`
+//! is regenerated after changing the API, CI checks that it's up to date:
+//! `cbindgen --config cbindgen.toml --output include/soundcore_ffi.h`
+
+mod error;
+mod manager;
+mod strings;
+
+pub use error::*;
+pub use manager::*;
+pub use strings::*;
@@ -0,0 +1,562 @@
+use std::cell::Cell;
+use std::ffi::{c_char, c_void, CString};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::{debug, warn};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::oneshot;
+
+use manager_fut::TokioFuture;
+use soundcore_lib::ble::BLEConnectionManager;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device::SoundcoreBLEDevice;
+use soundcore_lib::device_manager::{
+    create_device_manager, DefaultDeviceManager, DeviceManager, DiscoveredDevice,
+};
+use soundcore_lib::models::{EQConfiguration, EQProfile, SoundMode};
+
+use crate::strings::{json_arg, str_arg, to_json, write_json};
+use crate::{ffi_call, FfiError, FfiResult, SoundcoreStatus};
+
+/// Called with the address, e.g. `AC:12:2F:00:00:01`, and the JSON `SoundcoreDeviceState`
+/// of a device whenever its state changes. Both strings are only valid during the call.
+/// It's called from a dedicated thread of the library, one state at a time, so it may call
+/// the other functions, except `soundcore_set_state_callback` and `soundcore_manager_free`.
+pub type SoundcoreStateCallback = Option<
+    unsafe extern "C" fn(addr: *const c_char, state_json: *const c_char, user_data: *mut c_void),
+>;
+
+/// The devices and the runtime driving them, created with `soundcore_manager_new`
+pub struct SoundcoreManager {
+    runtime: Runtime,
+    manager: Arc<DefaultDeviceManager>,
+    /// Devices found by the last scan, which can be connected to
+    discovered: Mutex<Vec<DiscoveredDevice>>,
+    callback_thread: Mutex<Option<CallbackThread>>,
+}
+
+/// The thread calling the state callback, outside of the runtime so the callback can block on it
+struct CallbackThread {
+    stop: oneshot::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+thread_local! {
+    static IS_CALLBACK_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The callback's user data, which the caller guarantees to be usable from the library's threads
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+
+impl SoundcoreManager {
+    fn new() -> FfiResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| {
+                FfiError::InvalidArgument(format!("Failed to start the runtime: {}", e))
+            })?;
+        let manager = Arc::new(runtime.block_on(create_device_manager()));
+        Ok(Self {
+            runtime,
+            manager,
+            discovered: Mutex::new(Vec::new()),
+            callback_thread: Mutex::new(None),
+        })
+    }
+
+    fn scan(&self, duration: Option<Duration>) -> FfiResult<Vec<DiscoveredDevice>> {
+        let discovered = self.runtime.block_on(self.manager.ble_scan(duration))?;
+        *self.discovered.lock().unwrap() = discovered.to_owned();
+        Ok(discovered)
+    }
+
+    fn connect(&self, addr: BluetoothAdrr) -> FfiResult<()> {
+        let discovered = self
+            .discovered
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|device| device.descriptor.addr == addr)
+            .cloned()
+            .ok_or_else(|| FfiError::DeviceNotFound(addr.to_string()))?;
+        self.runtime.block_on(self.manager.connect(discovered))?;
+        Ok(())
+    }
+
+    /// Stops the callback thread, waiting for a call in progress to return
+    fn stop_callback(callback_thread: Option<CallbackThread>) {
+        if let Some(CallbackThread { stop, thread }) = callback_thread {
+            // The thread stops once the sender is gone, whether or not it's still receiving
+            drop(stop);
+            if thread.thread().id() != thread::current().id() && thread.join().is_err() {
+                warn!("State callback panicked");
+            }
+        }
+    }
+
+    fn set_state_callback(
+        &self,
+        callback: SoundcoreStateCallback,
+        user_data: UserData,
+    ) -> FfiResult<()> {
+        if IS_CALLBACK_THREAD.with(Cell::get) {
+            return Err(FfiError::InvalidArgument(
+                "The state callback can't be replaced from within it".to_string(),
+            ));
+        }
+        let mut callback_thread = self.callback_thread.lock().unwrap();
+        Self::stop_callback(callback_thread.take());
+        let Some(callback) = callback else {
+            return Ok(());
+        };
+        let mut states = self.manager.state_events();
+        let runtime = self.runtime.handle().to_owned();
+        let (stop, mut stopped) = oneshot::channel();
+        let thread = thread::Builder::new()
+            .name("soundcore-state-callback".to_string())
+            .spawn(move || {
+                let user_data = user_data;
+                IS_CALLBACK_THREAD.with(|is_callback_thread| is_callback_thread.set(true));
+                loop {
+                    let event = runtime.block_on(async {
+                        tokio::select! {
+                            _ = &mut stopped => None,
+                            event = states.recv() => Some(event),
+                        }
+                    });
+                    let (addr, state) = match event {
+                        Some(Ok(event)) => event,
+                        Some(Err(RecvError::Lagged(skipped))) => {
+                            warn!("State callback skipped {} state updates", skipped);
+                            continue;
+                        }
+                        Some(Err(RecvError::Closed)) | None => break,
+                    };
+                    let (Ok(addr), Ok(state)) = (CString::new(addr.to_string()), to_json(&state))
+                    else {
+                        continue;
+                    };
+                    unsafe { callback(addr.as_ptr(), state.as_ptr(), user_data.0) };
+                }
+                debug!("State callback finished");
+            })
+            .map_err(|e| {
+                FfiError::InvalidArgument(format!("Failed to start the callback thread: {}", e))
+            })?;
+        *callback_thread = Some(CallbackThread { stop, thread });
+        Ok(())
+    }
+}
+
+impl Drop for SoundcoreManager {
+    fn drop(&mut self) {
+        Self::stop_callback(self.callback_thread.lock().unwrap().take());
+    }
+}
+
+async fn connected_device<B: BLEConnectionManager>(
+    manager: &DeviceManager<B, TokioFuture>,
+    addr: &BluetoothAdrr,
+) -> FfiResult<Arc<SoundcoreBLEDevice<B::Connection, TokioFuture>>> {
+    manager
+        .get_device(addr.to_owned())
+        .await
+        .ok_or_else(|| FfiError::DeviceNotFound(addr.to_string()))
+}
+
+unsafe fn manager_arg<'a>(manager: *const SoundcoreManager) -> FfiResult<&'a SoundcoreManager> {
+    manager
+        .as_ref()
+        .ok_or_else(|| FfiError::InvalidArgument("manager is null".to_string()))
+}
+
+unsafe fn addr_arg(addr: *const c_char) -> FfiResult<BluetoothAdrr> {
+    let addr = str_arg(addr, "addr")?;
+    BluetoothAdrr::from_str(addr)
+        .map_err(|_| FfiError::InvalidArgument(format!("Invalid address {}", addr)))
+}
+
+/// Creates a manager for the enabled backend, to be freed with `soundcore_manager_free`
+///
+/// # Safety
+/// `out` must be a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_manager_new(out: *mut *mut SoundcoreManager) -> SoundcoreStatus {
+    ffi_call(|| {
+        if out.is_null() {
+            return Err(FfiError::InvalidArgument("out is null".to_string()));
+        }
+        *out = Box::into_raw(Box::new(SoundcoreManager::new()?));
+        Ok(())
+    })
+}
+
+/// Disconnects all devices and frees the manager, null is ignored
+///
+/// # Safety
+/// `manager` must have been created by `soundcore_manager_new` and not freed before,
+/// and mustn't be freed from the state callback
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_manager_free(manager: *mut SoundcoreManager) {
+    if !manager.is_null() {
+        drop(Box::from_raw(manager));
+    }
+}
+
+/// Scans for devices, for `duration_ms` or the backend's default duration if 0.
+/// Writes the JSON array of the `DiscoveredDevice`s to `out_json`.
+///
+/// # Safety
+/// `manager` and `out_json` must be valid pointers
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_scan(
+    manager: *const SoundcoreManager,
+    duration_ms: u32,
+    out_json: *mut *mut c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| {
+        let manager = manager_arg(manager)?;
+        let duration = (duration_ms > 0).then(|| Duration::from_millis(duration_ms.into()));
+        write_json(out_json, &manager.scan(duration)?)
+    })
+}
+
+/// Connects to a device found by the last scan
+///
+/// # Safety
+/// `manager` must be a valid pointer and `addr` a null-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_connect(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| manager_arg(manager)?.connect(addr_arg(addr)?))
+}
+
+/// # Safety
+/// `manager` must be a valid pointer and `addr` a null-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_disconnect(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| {
+        let manager = manager_arg(manager)?;
+        let addr = addr_arg(addr)?;
+        manager.runtime.block_on(manager.manager.disconnect(addr))?;
+        Ok(())
+    })
+}
+
+/// Writes the JSON `SoundcoreDeviceState` of a connected device to `out_json`
+///
+/// # Safety
+/// `manager` and `out_json` must be valid pointers and `addr` a null-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_get_state(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+    out_json: *mut *mut c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| {
+        let manager = manager_arg(manager)?;
+        let addr = addr_arg(addr)?;
+        let state = manager.runtime.block_on(async {
+            let device = connected_device(&manager.manager, &addr).await?;
+            Ok::<_, FfiError>(device.latest_state().await)
+        })?;
+        write_json(out_json, &state)
+    })
+}
+
+/// Sets the sound mode from the JSON of a `SoundMode`
+///
+/// # Safety
+/// `manager` must be a valid pointer, `addr` and `sound_mode_json` null-terminated strings
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_set_sound_mode(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+    sound_mode_json: *const c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| {
+        let manager = manager_arg(manager)?;
+        let addr = addr_arg(addr)?;
+        let sound_mode: SoundMode = json_arg(sound_mode_json, "sound_mode_json")?;
+        manager.runtime.block_on(async {
+            let device = connected_device(&manager.manager, &addr).await?;
+            Ok(device.set_sound_mode(sound_mode).await?)
+        })
+    })
+}
+
+/// Applies an EQ preset to both channels, e.g. `SoundcoreSignature`
+///
+/// # Safety
+/// `manager` must be a valid pointer, `addr` and `profile` null-terminated strings
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_set_eq_profile(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+    profile: *const c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| {
+        let profile = str_arg(profile, "profile")?;
+        let profile = EQProfile::from_str(profile)
+            .map_err(|_| FfiError::InvalidArgument(format!("Unknown EQ profile {}", profile)))?;
+        set_eq(manager, addr, EQConfiguration::stereo_with_profile(profile))
+    })
+}
+
+/// Sets the EQ from the JSON of an `EQConfiguration`
+///
+/// # Safety
+/// `manager` must be a valid pointer, `addr` and `eq_json` null-terminated strings
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_set_eq(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+    eq_json: *const c_char,
+) -> SoundcoreStatus {
+    ffi_call(|| set_eq(manager, addr, json_arg(eq_json, "eq_json")?))
+}
+
+unsafe fn set_eq(
+    manager: *const SoundcoreManager,
+    addr: *const c_char,
+    eq: EQConfiguration,
+) -> FfiResult<()> {
+    let manager = manager_arg(manager)?;
+    let addr = addr_arg(addr)?;
+    manager.runtime.block_on(async {
+        let device = connected_device(&manager.manager, &addr).await?;
+        Ok(device.set_eq(eq).await?)
+    })
+}
+
+/// Registers the callback receiving the states of all connected devices,
+/// replacing the previous one. A null callback unregisters it.
+/// Returns once the previous callback can no longer be called, which is why calling it
+/// from the state callback fails with `SoundcoreStatus::InvalidArgument`.
+///
+/// # Safety
+/// `manager` must be a valid pointer, `user_data` must be usable from any thread
+/// until the callback is replaced or the manager freed
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_set_state_callback(
+    manager: *const SoundcoreManager,
+    callback: SoundcoreStateCallback,
+    user_data: *mut c_void,
+) -> SoundcoreStatus {
+    ffi_call(|| manager_arg(manager)?.set_state_callback(callback, UserData(user_data)))
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod manager_tests {
+    use std::ffi::CStr;
+    use std::ptr;
+
+    use soundcore_lib::api::SoundcoreDeviceState;
+    use soundcore_lib::models::CurrentSoundMode;
+
+    use crate::soundcore_last_error;
+
+    use super::*;
+
+    type States = Mutex<Vec<(String, SoundcoreDeviceState)>>;
+
+    unsafe extern "C" fn collect_state(
+        addr: *const c_char,
+        state_json: *const c_char,
+        user_data: *mut c_void,
+    ) {
+        let states = &*(user_data as *const States);
+        let addr = CStr::from_ptr(addr).to_str().unwrap().to_string();
+        let state = serde_json::from_str(CStr::from_ptr(state_json).to_str().unwrap()).unwrap();
+        states.lock().unwrap().push((addr, state));
+    }
+
+    unsafe fn take_json<T: serde::de::DeserializeOwned>(json: *mut c_char) -> T {
+        let value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        crate::soundcore_string_free(json);
+        value
+    }
+
+    fn wait_for(states: &States, f: impl Fn(&SoundcoreDeviceState) -> bool) {
+        for _ in 0..100 {
+            if states.lock().unwrap().iter().any(|(_, state)| f(state)) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("No matching state in {:?}", states.lock().unwrap());
+    }
+
+    #[test]
+    fn should_drive_mock_devices() {
+        unsafe {
+            let mut manager = ptr::null_mut();
+            assert_eq!(soundcore_manager_new(&mut manager), SoundcoreStatus::Ok);
+            let states = Box::new(States::default());
+            let user_data = &*states as *const States as *mut c_void;
+            let status = soundcore_set_state_callback(manager, Some(collect_state), user_data);
+            assert_eq!(status, SoundcoreStatus::Ok);
+
+            let mut json = ptr::null_mut();
+            assert_eq!(soundcore_scan(manager, 0, &mut json), SoundcoreStatus::Ok);
+            let discovered: Vec<DiscoveredDevice> = take_json(json);
+            let addr = CString::new(discovered[0].descriptor.addr.to_string()).unwrap();
+            assert_eq!(
+                soundcore_connect(manager, addr.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+
+            assert_eq!(
+                soundcore_get_state(manager, addr.as_ptr(), &mut json),
+                SoundcoreStatus::Ok
+            );
+            let state: SoundcoreDeviceState = take_json(json);
+            let sound_mode = SoundMode {
+                current: CurrentSoundMode::Transparency,
+                ..state.sound_mode
+            };
+            let sound_mode = CString::new(serde_json::to_string(&sound_mode).unwrap()).unwrap();
+            assert_eq!(
+                soundcore_set_sound_mode(manager, addr.as_ptr(), sound_mode.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+            wait_for(&states, |state| {
+                state.sound_mode.current == CurrentSoundMode::Transparency
+            });
+
+            let profile = CString::new("Podcast").unwrap();
+            assert_eq!(
+                soundcore_set_eq_profile(manager, addr.as_ptr(), profile.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+            wait_for(&states, |state| {
+                state.eq_configuration.get_profile() == EQProfile::Podcast
+            });
+            assert_eq!(states.lock().unwrap()[0].0, addr.to_str().unwrap());
+
+            // The callback isn't called anymore once unregistering returned
+            assert_eq!(
+                soundcore_set_state_callback(manager, None, ptr::null_mut()),
+                SoundcoreStatus::Ok
+            );
+            let received = states.lock().unwrap().len();
+            let profile = CString::new("Acoustic").unwrap();
+            assert_eq!(
+                soundcore_set_eq_profile(manager, addr.as_ptr(), profile.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+            std::thread::sleep(Duration::from_millis(50));
+            assert_eq!(states.lock().unwrap().len(), received);
+
+            soundcore_manager_free(manager);
+        }
+    }
+
+    struct Reentrant {
+        manager: *const SoundcoreManager,
+        statuses: Mutex<Vec<(SoundcoreStatus, SoundcoreStatus)>>,
+    }
+
+    unsafe extern "C" fn call_from_callback(
+        addr: *const c_char,
+        _state_json: *const c_char,
+        user_data: *mut c_void,
+    ) {
+        let reentrant = &*(user_data as *const Reentrant);
+        let mut json = ptr::null_mut();
+        let get_state = soundcore_get_state(reentrant.manager, addr, &mut json);
+        if get_state == SoundcoreStatus::Ok {
+            crate::soundcore_string_free(json);
+        }
+        let set_callback = soundcore_set_state_callback(reentrant.manager, None, ptr::null_mut());
+        reentrant
+            .statuses
+            .lock()
+            .unwrap()
+            .push((get_state, set_callback));
+    }
+
+    #[test]
+    fn should_allow_calls_from_the_callback() {
+        unsafe {
+            let mut manager = ptr::null_mut();
+            assert_eq!(soundcore_manager_new(&mut manager), SoundcoreStatus::Ok);
+            let reentrant = Box::new(Reentrant {
+                manager,
+                statuses: Mutex::default(),
+            });
+            let user_data = &*reentrant as *const Reentrant as *mut c_void;
+            let status = soundcore_set_state_callback(manager, Some(call_from_callback), user_data);
+            assert_eq!(status, SoundcoreStatus::Ok);
+
+            let mut json = ptr::null_mut();
+            assert_eq!(soundcore_scan(manager, 0, &mut json), SoundcoreStatus::Ok);
+            let discovered: Vec<DiscoveredDevice> = take_json(json);
+            let addr = CString::new(discovered[0].descriptor.addr.to_string()).unwrap();
+            assert_eq!(
+                soundcore_connect(manager, addr.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+            let profile = CString::new("Podcast").unwrap();
+            assert_eq!(
+                soundcore_set_eq_profile(manager, addr.as_ptr(), profile.as_ptr()),
+                SoundcoreStatus::Ok
+            );
+
+            for _ in 0..100 {
+                if !reentrant.statuses.lock().unwrap().is_empty() {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            assert_eq!(
+                reentrant.statuses.lock().unwrap().first(),
+                Some(&(SoundcoreStatus::Ok, SoundcoreStatus::InvalidArgument))
+            );
+
+            soundcore_manager_free(manager);
+        }
+    }
+
+    #[test]
+    fn should_report_errors() {
+        unsafe {
+            let mut manager = ptr::null_mut();
+            assert_eq!(soundcore_manager_new(&mut manager), SoundcoreStatus::Ok);
+
+            let invalid = CString::new("not an address").unwrap();
+            assert_eq!(
+                soundcore_connect(manager, invalid.as_ptr()),
+                SoundcoreStatus::InvalidArgument
+            );
+            assert!(!soundcore_last_error().is_null());
+
+            let unknown = CString::new("AC:12:2F:00:00:01").unwrap();
+            let mut json = ptr::null_mut();
+            assert_eq!(
+                soundcore_get_state(manager, unknown.as_ptr(), &mut json),
+                SoundcoreStatus::DeviceNotFound
+            );
+            let error = CStr::from_ptr(soundcore_last_error()).to_str().unwrap();
+            assert_eq!(error, "Device AC:12:2F:00:00:01 not found");
+            assert_eq!(
+                soundcore_connect(ptr::null(), unknown.as_ptr()),
+                SoundcoreStatus::InvalidArgument
+            );
+
+            soundcore_manager_free(manager);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::ffi::{c_char, CStr, CString};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{FfiError, FfiResult};
+
+/// Borrows a string argument, which must be null-terminated UTF-8
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(FfiError::InvalidArgument(format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::InvalidArgument(format!("{} isn't valid UTF-8", name)))
+}
+
+pub(crate) unsafe fn json_arg<T: DeserializeOwned>(ptr: *const c_char, name: &str) -> FfiResult<T> {
+    serde_json::from_str(str_arg(ptr, name)?)
+        .map_err(|e| FfiError::InvalidArgument(format!("{}: {}", name, e)))
+}
+
+pub(crate) fn to_json<T: Serialize>(value: &T) -> FfiResult<CString> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| FfiError::InvalidArgument(format!("Failed to serialize: {}", e)))?;
+    // JSON escapes control characters, so it never contains a nul byte
+    Ok(CString::new(json).expect("JSON has no nul bytes"))
+}
+
+/// Hands the JSON of `value` over to the caller, who frees it with `soundcore_string_free`
+pub(crate) unsafe fn write_json<T: Serialize>(out: *mut *mut c_char, value: &T) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(FfiError::InvalidArgument("out is null".to_string()));
+    }
+    *out = to_json(value)?.into_raw();
+    Ok(())
+}
+
+/// Frees a string returned by the library, null is ignored
+///
+/// # Safety
+/// `s` must have been returned by this library and not freed before
+#[no_mangle]
+pub unsafe extern "C" fn soundcore_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
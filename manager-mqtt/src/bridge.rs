@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use rumqttc::{AsyncClient, ClientError, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use manager_fut::{ManagerFuture, ManagerJoinHandle};
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::ble::BLEConnectionManager;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::{DeviceConnectionStatus, DeviceManager};
+
+use crate::{discovery_payload, entities, state_payloads, Command, MqttBridgeResult, Topics};
+
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+/// Delay before polling again after a connection error, the event loop reconnects on poll
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const CLIENT_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttBridgeConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub credentials: Option<(String, String)>,
+    pub base_topic: String,
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttBridgeConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "soundcore-manager".to_string(),
+            credentials: None,
+            base_topic: "soundcore".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+/// Publishes the states of the manager's devices and forwards commands to them
+pub struct MqttBridge<B, F>
+where
+    B: BLEConnectionManager,
+    F: ManagerFuture,
+{
+    manager: Arc<DeviceManager<B, F>>,
+    client: AsyncClient,
+    topics: Topics,
+    /// Devices whose discovery configs were published since the last (re)connect
+    announced: HashSet<BluetoothAdrr>,
+}
+
+impl<B, F> MqttBridge<B, F>
+where
+    B: BLEConnectionManager,
+    F: ManagerFuture,
+{
+    pub fn new(manager: Arc<DeviceManager<B, F>>, config: &MqttBridgeConfig) -> (Self, EventLoop) {
+        let topics = Topics::new(&config.base_topic, &config.discovery_prefix);
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            topics.availability(),
+            OFFLINE,
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some((username, password)) = &config.credentials {
+            options.set_credentials(username, password);
+        }
+        let (client, eventloop) = AsyncClient::new(options, CLIENT_CAPACITY);
+        let bridge = Self {
+            manager,
+            client,
+            topics,
+            announced: HashSet::new(),
+        };
+        (bridge, eventloop)
+    }
+
+    /// Runs until the manager's state stream closes.
+    /// The event loop is polled in its own task, so that it keeps reconnecting
+    /// while the bridge waits on the client.
+    pub async fn run(mut self, eventloop: EventLoop) -> MqttBridgeResult<()> {
+        let (incoming_tx, mut incoming) = mpsc::unbounded_channel();
+        let poller = F::spawn(poll_eventloop(eventloop, incoming_tx));
+        let result = self.forward(&mut incoming).await;
+        poller.abort();
+        result
+    }
+
+    async fn forward(
+        &mut self,
+        incoming: &mut mpsc::UnboundedReceiver<Packet>,
+    ) -> MqttBridgeResult<()> {
+        let mut states = self.manager.state_events();
+        loop {
+            tokio::select! {
+                Some(packet) = incoming.recv() => match packet {
+                    Packet::ConnAck(_) => self.on_connected().await?,
+                    Packet::Publish(publish) => {
+                        let payload = String::from_utf8_lossy(&publish.payload);
+                        self.handle_command(&publish.topic, &payload).await;
+                    }
+                    _ => {}
+                },
+                event = states.recv() => match event {
+                    Ok((addr, state)) => self.publish_state(&addr, &state)?,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("MQTT bridge skipped {} state updates", skipped);
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+
+    async fn on_connected(&mut self) -> MqttBridgeResult<()> {
+        info!("Connected to the MQTT broker");
+        self.announced.clear();
+        self.client
+            .subscribe(self.topics.command_filter(), QoS::AtLeastOnce)
+            .await?;
+        self.client
+            .publish(self.topics.availability(), QoS::AtLeastOnce, true, ONLINE)
+            .await?;
+        for snapshot in self.manager.snapshot().await {
+            if snapshot.status == DeviceConnectionStatus::Connected {
+                self.publish_state(&snapshot.addr, &snapshot.state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Doesn't wait for the client, the messages are dropped while its queue is full,
+    /// e.g. while the broker is down. All states are published again on reconnect.
+    fn publish_state(
+        &mut self,
+        addr: &BluetoothAdrr,
+        state: &SoundcoreDeviceState,
+    ) -> MqttBridgeResult<()> {
+        if self.announced.insert(addr.to_owned()) {
+            for entity in entities(state) {
+                let payload = discovery_payload(&self.topics, addr, state, entity);
+                if !self.try_publish(self.topics.discovery(addr, entity), payload.to_string())? {
+                    // Announce the device again with its next state
+                    self.announced.remove(addr);
+                    return Ok(());
+                }
+            }
+        }
+        for (entity, payload) in state_payloads(state) {
+            if !self.try_publish(self.topics.state(addr, entity), payload)? {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes a retained message, returns whether it was queued
+    fn try_publish(&self, topic: String, payload: String) -> MqttBridgeResult<bool> {
+        match self
+            .client
+            .try_publish(topic, QoS::AtLeastOnce, true, payload)
+        {
+            Ok(()) => Ok(true),
+            Err(ClientError::TryRequest(_)) => {
+                debug!("MQTT client queue is full, dropping state messages");
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Invalid commands and device errors are logged, they shouldn't stop the bridge
+    async fn handle_command(&self, topic: &str, payload: &str) {
+        let Some((addr, entity)) = self.topics.parse_command(topic) else {
+            debug!("Ignoring message on {}", topic);
+            return;
+        };
+        let Some(command) = Command::parse(entity, payload) else {
+            warn!("Invalid payload {} on {}", payload, topic);
+            return;
+        };
+        let Some(device) = self.manager.get_device(addr.to_owned()).await else {
+            warn!("Received a command for {}, which isn't connected", addr);
+            return;
+        };
+
+        let result = match (
+            command.sound_mode(&device.latest_state().await),
+            command.eq(),
+        ) {
+            (Some(sound_mode), _) => device.set_sound_mode(sound_mode).await,
+            (_, Some(eq)) => device.set_eq(eq).await,
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to apply {:?} to {}: {}", command, addr, e);
+        }
+    }
+}
+
+/// Forwards the incoming packets until the bridge stops listening
+async fn poll_eventloop(mut eventloop: EventLoop, incoming: mpsc::UnboundedSender<Packet>) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(packet)) => {
+                if incoming.send(packet).is_err() {
+                    return;
+                }
+            }
+            Ok(Event::Outgoing(_)) => {}
+            Err(e) => {
+                warn!("MQTT connection error: {}", e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::models::{CurrentSoundMode, EQConfiguration, EQProfile, SoundMode};
+
+use crate::Entity;
+
+/// A command received on a command topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    SoundMode(CurrentSoundMode),
+    EqProfile(EQProfile),
+}
+
+impl Command {
+    pub fn parse(entity: Entity, payload: &str) -> Option<Self> {
+        match entity {
+            Entity::SoundMode => from_payload(payload).map(Command::SoundMode),
+            Entity::EqProfile => from_payload(payload).map(Command::EqProfile),
+            _ => None,
+        }
+    }
+
+    /// The sound mode or EQ to set, based on the current state
+    pub fn sound_mode(&self, state: &SoundcoreDeviceState) -> Option<SoundMode> {
+        match self {
+            Command::SoundMode(current) => Some(SoundMode {
+                current: *current,
+                ..state.sound_mode
+            }),
+            Command::EqProfile(_) => None,
+        }
+    }
+
+    pub fn eq(&self) -> Option<EQConfiguration> {
+        match self {
+            Command::EqProfile(profile) => Some(EQConfiguration::stereo_with_profile(*profile)),
+            Command::SoundMode(_) => None,
+        }
+    }
+}
+
+fn from_payload<T: DeserializeOwned>(payload: &str) -> Option<T> {
+    serde_json::from_value(Value::String(payload.trim().to_string())).ok()
+}
+
+#[cfg(test)]
+mod command_tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_commands() {
+        assert_eq!(
+            Command::parse(Entity::SoundMode, "transparency"),
+            Some(Command::SoundMode(CurrentSoundMode::Transparency))
+        );
+        assert_eq!(
+            Command::parse(Entity::EqProfile, "Podcast"),
+            Some(Command::EqProfile(EQProfile::Podcast))
+        );
+        assert_eq!(Command::parse(Entity::SoundMode, "loud"), None);
+        assert_eq!(Command::parse(Entity::Battery, "100"), None);
+    }
+}
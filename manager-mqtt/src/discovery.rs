@@ -0,0 +1,210 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::models::{ANCMode, Battery, CurrentSoundMode, EQProfile, SingleBattery};
+
+use crate::{Entity, Topics};
+
+const SOUND_MODES: [CurrentSoundMode; 3] = [
+    CurrentSoundMode::ANC,
+    CurrentSoundMode::Transparency,
+    CurrentSoundMode::Normal,
+];
+
+/// The entities the device's state and features allow for
+pub fn entities(state: &SoundcoreDeviceState) -> Vec<Entity> {
+    let mut entities = match state.battery {
+        Battery::Single(_) => vec![Entity::Battery],
+        Battery::Dual(_) => vec![Entity::BatteryLeft, Entity::BatteryRight],
+    };
+    if state.feature_set.sound_mode_features.is_some() {
+        entities.extend([Entity::SoundMode, Entity::AncMode]);
+    }
+    if state.feature_set.equalizer_features.is_some() {
+        entities.push(Entity::EqProfile);
+    }
+    entities
+}
+
+/// The Home Assistant discovery config of an entity
+pub fn discovery_payload(
+    topics: &Topics,
+    addr: &BluetoothAdrr,
+    state: &SoundcoreDeviceState,
+    entity: Entity,
+) -> Value {
+    let node_id = Topics::node_id(addr);
+    let model = state
+        .serial
+        .as_ref()
+        .and_then(|sn| sn.to_model())
+        .map(|model| format!("{:?}", model));
+    let mut payload = json!({
+        "name": entity.name(),
+        "unique_id": format!("soundcore_{}_{}", node_id, entity.id()),
+        "state_topic": topics.state(addr, entity),
+        "availability_topic": topics.availability(),
+        "device": {
+            "identifiers": [format!("soundcore_{}", node_id)],
+            "connections": [["bluetooth", addr.to_string()]],
+            "manufacturer": "Soundcore",
+            "model": model,
+            "name": format!("Soundcore {}", addr),
+        },
+    });
+    let extra = match entity {
+        Entity::Battery | Entity::BatteryLeft | Entity::BatteryRight => json!({
+            "device_class": "battery",
+            "unit_of_measurement": "%",
+            "state_class": "measurement",
+        }),
+        Entity::SoundMode => json!({
+            "command_topic": topics.command(addr, entity),
+            "options": SOUND_MODES.iter().map(to_payload).collect::<Vec<_>>(),
+        }),
+        Entity::EqProfile => json!({
+            "command_topic": topics.command(addr, entity),
+            "options": eq_profile_options(state),
+        }),
+        Entity::AncMode => json!({}),
+    };
+    if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    payload
+}
+
+/// The state payloads of the entities, battery levels as percentages.
+/// Unknown battery levels aren't published.
+pub fn state_payloads(state: &SoundcoreDeviceState) -> Vec<(Entity, String)> {
+    entities(state)
+        .into_iter()
+        .filter_map(|entity| {
+            let payload = match entity {
+                Entity::Battery | Entity::BatteryLeft | Entity::BatteryRight => {
+                    battery_percent(&state.battery, entity)?.to_string()
+                }
+                Entity::SoundMode => to_payload(&state.sound_mode.current),
+                Entity::AncMode => anc_mode_name(&state.sound_mode.anc_mode),
+                Entity::EqProfile => to_payload(&state.eq_configuration.get_profile()),
+            };
+            Some((entity, payload))
+        })
+        .collect()
+}
+
+/// Only the presets with a known curve
+fn eq_profile_options(state: &SoundcoreDeviceState) -> Vec<String> {
+    match state.feature_set.equalizer_features {
+        Some(_) => EQProfile::regular_profiles()
+            .iter()
+            .map(to_payload)
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn battery_percent(battery: &Battery, entity: Entity) -> Option<u8> {
+    let battery: SingleBattery = match (battery, entity) {
+        (Battery::Dual(battery), Entity::BatteryRight) => battery.right,
+        (Battery::Dual(battery), _) => battery.left,
+        (Battery::Single(battery), _) => *battery,
+    };
+    battery.percent()
+}
+
+fn anc_mode_name(mode: &ANCMode) -> String {
+    match mode {
+        ANCMode::SceneBased(mode) => mode.to_string(),
+        ANCMode::Adaptive(mode) => mode.to_string(),
+    }
+}
+
+/// Uses the serde names, e.g. `transparency` or `SoundcoreSignature`
+fn to_payload<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod discovery_tests {
+    use std::str::FromStr;
+
+    use soundcore_lib::api::{DeviceFeatureSet, EqualizerFeatures, SoundModeFeatures};
+    use soundcore_lib::models::DualBattery;
+
+    use super::*;
+
+    fn state() -> SoundcoreDeviceState {
+        SoundcoreDeviceState {
+            feature_set: DeviceFeatureSet {
+                sound_mode_features: Some(
+                    SoundModeFeatures::scene_based_non_customizable_anc_non_customizable_transparency(),
+                ),
+                equalizer_features: Some(EqualizerFeatures {
+                    bands: 8,
+                    channels: 2,
+                    has_bass_up: false,
+                }),
+                ..Default::default()
+            },
+            battery: Battery::Dual(DualBattery {
+                left: SingleBattery {
+                    level: 4,
+                    charging: false,
+                },
+                right: SingleBattery {
+                    level: 1,
+                    charging: true,
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_build_state_payloads() {
+        assert_eq!(
+            state_payloads(&state()),
+            vec![
+                (Entity::BatteryLeft, "80".to_string()),
+                (Entity::BatteryRight, "20".to_string()),
+                (Entity::SoundMode, "normal".to_string()),
+                (Entity::AncMode, "Transport".to_string()),
+                (Entity::EqProfile, "SoundcoreSignature".to_string()),
+            ]
+        );
+        assert_eq!(
+            entities(&SoundcoreDeviceState::default()),
+            vec![Entity::Battery]
+        );
+        assert!(state_payloads(&SoundcoreDeviceState::default()).is_empty());
+    }
+
+    #[test]
+    fn should_build_discovery_payloads() {
+        let topics = Topics::new("soundcore", "homeassistant");
+        let addr = BluetoothAdrr::from_str("AC:12:2F:0A:0B:0C").unwrap();
+        let payload = discovery_payload(&topics, &addr, &state(), Entity::SoundMode);
+        assert_eq!(payload["unique_id"], "soundcore_ac122f0a0b0c_sound_mode");
+        assert_eq!(
+            payload["command_topic"],
+            "soundcore/ac122f0a0b0c/sound_mode/set"
+        );
+        assert_eq!(payload["options"], json!(["anc", "transparency", "normal"]));
+
+        let payload = discovery_payload(&topics, &addr, &state(), Entity::EqProfile);
+        assert_eq!(
+            payload["options"].as_array().map(Vec::len),
+            Some(EQProfile::regular_profiles().len())
+        );
+
+        let payload = discovery_payload(&topics, &addr, &state(), Entity::BatteryLeft);
+        assert_eq!(payload["device_class"], "battery");
+        assert!(payload.get("command_topic").is_none());
+    }
+}
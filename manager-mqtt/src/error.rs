@@ -0,0 +1,12 @@
+use soundcore_lib::error::SoundcoreLibError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MqttBridgeError {
+    #[error("MQTT client error: {0}")]
+    Client(#[from] rumqttc::ClientError),
+    #[error("Device error: {0}")]
+    Device(#[from] SoundcoreLibError),
+}
+
+pub type MqttBridgeResult<T> = Result<T, MqttBridgeError>;
@@ -0,0 +1,14 @@
+//! Publishes the state of connected devices to MQTT, with Home Assistant discovery,
+//! and forwards the commands received on the command topics to the devices.
+
+mod bridge;
+mod command;
+mod discovery;
+mod error;
+mod topics;
+
+pub use bridge::*;
+pub use command::*;
+pub use discovery::*;
+pub use error::*;
+pub use topics::*;
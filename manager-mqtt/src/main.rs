@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use log::{info, warn};
+
+use manager_mqtt::{MqttBridge, MqttBridgeConfig, MqttBridgeResult};
+use soundcore_lib::device_manager::create_device_manager;
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[arg(long, env = "MQTT_HOST", default_value = "localhost")]
+    host: String,
+    #[arg(long, env = "MQTT_PORT", default_value_t = 1883)]
+    port: u16,
+    #[arg(long, env = "MQTT_USERNAME", requires = "password")]
+    username: Option<String>,
+    #[arg(long, env = "MQTT_PASSWORD", requires = "username")]
+    password: Option<String>,
+    #[arg(long, default_value = "soundcore-manager")]
+    client_id: String,
+    #[arg(long, default_value = "soundcore")]
+    base_topic: String,
+    #[arg(long, default_value = "homeassistant")]
+    discovery_prefix: String,
+    /// How long to scan for devices before connecting, in seconds
+    #[arg(long, default_value_t = 5)]
+    scan_secs: u64,
+}
+
+#[tokio::main]
+async fn main() -> MqttBridgeResult<()> {
+    env_logger::init();
+    let args = Args::parse();
+    let config = MqttBridgeConfig {
+        host: args.host,
+        port: args.port,
+        client_id: args.client_id,
+        credentials: args.username.zip(args.password),
+        base_topic: args.base_topic,
+        discovery_prefix: args.discovery_prefix,
+    };
+
+    let manager = Arc::new(create_device_manager().await);
+    let discovered = manager
+        .ble_scan(Some(Duration::from_secs(args.scan_secs)))
+        .await?;
+    for device in discovered.into_iter().filter(|d| d.model.is_some()) {
+        let addr = device.descriptor.addr.to_owned();
+        match manager.connect(device).await {
+            Ok(_) => info!("Connected to {}", addr),
+            Err(e) => warn!("Failed to connect to {}: {}", addr, e),
+        }
+    }
+
+    let (bridge, eventloop) = MqttBridge::new(manager, &config);
+    bridge.run(eventloop).await
+}
@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use soundcore_lib::btaddr::BluetoothAdrr;
+
+/// An entity exposed for each device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Entity {
+    /// The battery of single battery devices, e.g. headphones
+    Battery,
+    BatteryLeft,
+    BatteryRight,
+    SoundMode,
+    AncMode,
+    EqProfile,
+}
+
+impl Entity {
+    pub const ALL: [Entity; 6] = [
+        Entity::Battery,
+        Entity::BatteryLeft,
+        Entity::BatteryRight,
+        Entity::SoundMode,
+        Entity::AncMode,
+        Entity::EqProfile,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Entity::Battery => "battery",
+            Entity::BatteryLeft => "battery_left",
+            Entity::BatteryRight => "battery_right",
+            Entity::SoundMode => "sound_mode",
+            Entity::AncMode => "anc_mode",
+            Entity::EqProfile => "eq_profile",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Entity::Battery => "Battery",
+            Entity::BatteryLeft => "Battery left",
+            Entity::BatteryRight => "Battery right",
+            Entity::SoundMode => "Sound mode",
+            Entity::AncMode => "ANC mode",
+            Entity::EqProfile => "EQ profile",
+        }
+    }
+
+    /// Writable entities are exposed as selects, the others as sensors
+    pub fn is_writable(&self) -> bool {
+        matches!(self, Entity::SoundMode | Entity::EqProfile)
+    }
+
+    pub fn component(&self) -> &'static str {
+        match self.is_writable() {
+            true => "select",
+            false => "sensor",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|entity| entity.id() == id)
+    }
+}
+
+/// The MQTT topic layout, `<base>/<node id>/<entity>` for states and `<base>/<node id>/<entity>/set` for commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    base: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    pub fn new(base: impl Into<String>, discovery_prefix: impl Into<String>) -> Self {
+        Self {
+            base: base.into(),
+            discovery_prefix: discovery_prefix.into(),
+        }
+    }
+
+    /// The address without separators, since colons aren't allowed in discovery topics
+    pub fn node_id(addr: &BluetoothAdrr) -> String {
+        addr.address.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn addr_from_node_id(node_id: &str) -> Option<BluetoothAdrr> {
+        if node_id.len() != 12 || !node_id.is_ascii() {
+            return None;
+        }
+        let addr = (0..12)
+            .step_by(2)
+            .map(|i| &node_id[i..i + 2])
+            .collect::<Vec<_>>()
+            .join(":");
+        BluetoothAdrr::from_str(&addr).ok()
+    }
+
+    pub fn state(&self, addr: &BluetoothAdrr, entity: Entity) -> String {
+        format!("{}/{}/{}", self.base, Self::node_id(addr), entity.id())
+    }
+
+    pub fn command(&self, addr: &BluetoothAdrr, entity: Entity) -> String {
+        format!("{}/set", self.state(addr, entity))
+    }
+
+    /// Matches the command topics of all devices
+    pub fn command_filter(&self) -> String {
+        format!("{}/+/+/set", self.base)
+    }
+
+    /// Set to offline by the broker when the bridge disconnects
+    pub fn availability(&self) -> String {
+        format!("{}/bridge/availability", self.base)
+    }
+
+    pub fn discovery(&self, addr: &BluetoothAdrr, entity: Entity) -> String {
+        format!(
+            "{}/{}/soundcore_{}/{}/config",
+            self.discovery_prefix,
+            entity.component(),
+            Self::node_id(addr),
+            entity.id()
+        )
+    }
+
+    pub fn parse_command(&self, topic: &str) -> Option<(BluetoothAdrr, Entity)> {
+        let rest = topic.strip_prefix(&self.base)?.strip_prefix('/')?;
+        let mut parts = rest.split('/');
+        let (Some(node_id), Some(entity), Some("set"), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        let entity = Entity::from_id(entity).filter(Entity::is_writable)?;
+        Some((Self::addr_from_node_id(node_id)?, entity))
+    }
+}
+
+#[cfg(test)]
+mod topics_tests {
+    use super::*;
+
+    fn addr() -> BluetoothAdrr {
+        BluetoothAdrr::from_str("AC:12:2F:0A:0B:0C").unwrap()
+    }
+
+    #[test]
+    fn should_build_topics() {
+        let topics = Topics::new("soundcore", "homeassistant");
+        assert_eq!(
+            topics.state(&addr(), Entity::BatteryLeft),
+            "soundcore/ac122f0a0b0c/battery_left"
+        );
+        assert_eq!(
+            topics.command(&addr(), Entity::SoundMode),
+            "soundcore/ac122f0a0b0c/sound_mode/set"
+        );
+        assert_eq!(
+            topics.discovery(&addr(), Entity::EqProfile),
+            "homeassistant/select/soundcore_ac122f0a0b0c/eq_profile/config"
+        );
+    }
+
+    #[test]
+    fn should_parse_command_topics() {
+        let topics = Topics::new("soundcore", "homeassistant");
+        let topic = topics.command(&addr(), Entity::EqProfile);
+        assert_eq!(
+            topics.parse_command(&topic),
+            Some((addr(), Entity::EqProfile))
+        );
+        assert_eq!(
+            topics.parse_command("soundcore/ac122f0a0b0c/battery/set"),
+            None
+        );
+        assert_eq!(
+            topics.parse_command("soundcore/ac122f/sound_mode/set"),
+            None
+        );
+        assert_eq!(
+            topics.parse_command("other/ac122f0a0b0c/sound_mode/set"),
+            None
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Runs the bridge against a local broker, e.g. `mosquitto -p 1883`.
+//! Ignored by default, run with `cargo test --no-default-features --features mock -- --ignored`.
+#![cfg(feature = "mock")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+
+use manager_mqtt::{Entity, MqttBridge, MqttBridgeConfig, Topics};
+use soundcore_lib::device_manager::create_device_manager;
+use soundcore_lib::models::CurrentSoundMode;
+
+fn broker() -> (String, u16) {
+    let broker = std::env::var("MQTT_BROKER").unwrap_or("localhost:1883".to_string());
+    let (host, port) = broker.split_once(':').expect("MQTT_BROKER is host:port");
+    (host.to_string(), port.parse().expect("valid port"))
+}
+
+#[tokio::test]
+#[ignore = "needs a local MQTT broker"]
+async fn should_publish_states_and_apply_commands() {
+    let (host, port) = broker();
+    let manager = Arc::new(create_device_manager().await);
+    let discovered = manager.ble_scan(None).await.unwrap()[0].to_owned();
+    let addr = discovered.descriptor.addr.to_owned();
+    let device = manager.connect(discovered).await.unwrap();
+
+    let config = MqttBridgeConfig {
+        host: host.to_owned(),
+        port,
+        client_id: "soundcore-manager-test".to_string(),
+        base_topic: "soundcore-test".to_string(),
+        ..Default::default()
+    };
+    let topics = Topics::new(&config.base_topic, &config.discovery_prefix);
+    let (bridge, eventloop) = MqttBridge::new(manager.to_owned(), &config);
+    tokio::spawn(bridge.run(eventloop));
+
+    let (client, mut eventloop) =
+        AsyncClient::new(MqttOptions::new("soundcore-test-client", host, port), 16);
+    let state_topic = topics.state(&addr, Entity::Battery);
+    client
+        .subscribe(&state_topic, QoS::AtLeastOnce)
+        .await
+        .unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Event::Incoming(Packet::Publish(publish)) = eventloop.poll().await.unwrap() {
+                if publish.topic == state_topic {
+                    return publish.payload;
+                }
+            }
+        }
+    })
+    .await
+    .expect("the battery state is published");
+    assert!(String::from_utf8_lossy(&received).parse::<u8>().is_ok());
+
+    client
+        .publish(
+            topics.command(&addr, Entity::SoundMode),
+            QoS::AtLeastOnce,
+            false,
+            "transparency",
+        )
+        .await
+        .unwrap();
+    tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while device.latest_state().await.sound_mode.current != CurrentSoundMode::Transparency {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("the sound mode command is applied");
+}
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use manager_fut::ManagerFuture;
+use soundcore_lib::ble::BLEConnection;
+use soundcore_lib::device::SoundcoreBLEDevice;
+use soundcore_lib::error::SoundcoreLibResult;
+use soundcore_lib::models::{CurrentSoundMode, EQConfiguration, EQProfile, SoundMode};
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Switches the sound mode, keeping the ANC and transparency settings
+    SoundMode(CurrentSoundMode),
+    EqProfile(EQProfile),
+}
+
+impl Action {
+    pub async fn apply<C, F>(&self, device: &SoundcoreBLEDevice<C, F>) -> SoundcoreLibResult<()>
+    where
+        C: BLEConnection,
+        F: ManagerFuture,
+    {
+        match self {
+            Action::SoundMode(current) => {
+                let sound_mode = SoundMode {
+                    current: *current,
+                    ..device.latest_state().await.sound_mode
+                };
+                device.set_sound_mode(sound_mode).await
+            }
+            Action::EqProfile(profile) => {
+                device
+                    .set_eq(EQConfiguration::stereo_with_profile(*profile))
+                    .await
+            }
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::SoundMode(mode) => write!(f, "set the sound mode to {}", mode),
+            Action::EqProfile(profile) => write!(f, "set the EQ profile to {}", profile),
+        }
+    }
+}
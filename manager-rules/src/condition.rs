@@ -0,0 +1,215 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::models::{Battery, CurrentSoundMode, SingleBattery};
+
+use crate::{RulesError, RulesResult};
+
+/// A time of day in the `HH:MM` format
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "String")]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8) -> RulesResult<Self> {
+        if hour > 23 || minute > 59 {
+            return Err(RulesError::InvalidConfig(format!(
+                "Invalid time {:02}:{:02}",
+                hour, minute
+            )));
+        }
+        Ok(Self { hour, minute })
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RulesError::InvalidConfig(format!("Invalid time {}, expected HH:MM", s));
+        let (hour, minute) = s.split_once(':').ok_or_else(invalid)?;
+        Self::new(
+            hour.parse().map_err(|_| invalid())?,
+            minute.parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = RulesError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// Any battery with a known level is below the percentage, levels are reported in steps of 20%
+    BatteryBelow(u8),
+    /// Whether any earbud is charging, e.g. when the earbuds are in the case
+    Charging(bool),
+    /// Whether the charging case itself is charging.
+    /// None of the decoded models report the case's battery yet, so this never matches `true`.
+    CaseCharging(bool),
+    SoundMode(CurrentSoundMode),
+    /// The end is exclusive, a window ending before its start spans midnight
+    TimeBetween {
+        start: TimeOfDay,
+        end: TimeOfDay,
+    },
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn matches(&self, state: &SoundcoreDeviceState, now: TimeOfDay) -> bool {
+        match self {
+            Condition::BatteryBelow(percent) => batteries(&state.battery)
+                .iter()
+                .any(|battery| battery.percent().is_some_and(|level| level < *percent)),
+            Condition::Charging(charging) => {
+                batteries(&state.battery)
+                    .iter()
+                    .any(|battery| battery.charging)
+                    == *charging
+            }
+            Condition::CaseCharging(charging) => {
+                state.case_battery.is_some_and(|battery| battery.charging) == *charging
+            }
+            Condition::SoundMode(mode) => state.sound_mode.current == *mode,
+            Condition::TimeBetween { start, end } => match start <= end {
+                true => *start <= now && now < *end,
+                false => *start <= now || now < *end,
+            },
+            Condition::All(conditions) => conditions.iter().all(|c| c.matches(state, now)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.matches(state, now)),
+        }
+    }
+
+    pub fn validate(&self) -> RulesResult<()> {
+        match self {
+            Condition::BatteryBelow(percent) if *percent > 100 => Err(RulesError::InvalidConfig(
+                format!("Battery percentage {} is above 100", percent),
+            )),
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                if conditions.is_empty() {
+                    return Err(RulesError::InvalidConfig(
+                        "all/any need at least one condition".to_string(),
+                    ));
+                }
+                conditions.iter().try_for_each(Condition::validate)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn batteries(battery: &Battery) -> Vec<SingleBattery> {
+    match battery {
+        Battery::Single(battery) => vec![*battery],
+        Battery::Dual(battery) => vec![battery.left, battery.right],
+    }
+}
+
+#[cfg(test)]
+mod condition_tests {
+    use soundcore_lib::models::DualBattery;
+
+    use super::*;
+
+    fn state(left: u8, right: u8, charging: bool) -> SoundcoreDeviceState {
+        SoundcoreDeviceState {
+            battery: Battery::Dual(DualBattery {
+                left: SingleBattery {
+                    level: left,
+                    charging,
+                },
+                right: SingleBattery {
+                    level: right,
+                    charging: false,
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn time(s: &str) -> TimeOfDay {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn should_match_battery_conditions() {
+        let noon = time("12:00");
+        assert!(Condition::BatteryBelow(10).matches(&state(0, 5, false), noon));
+        assert!(!Condition::BatteryBelow(10).matches(&state(1, 5, false), noon));
+        // Unknown levels never match
+        assert!(!Condition::BatteryBelow(100).matches(&SoundcoreDeviceState::default(), noon));
+        assert!(Condition::Charging(true).matches(&state(3, 3, true), noon));
+        assert!(Condition::Charging(false).matches(&state(3, 3, false), noon));
+
+        let case_charging = SoundcoreDeviceState {
+            case_battery: Some(SingleBattery {
+                level: 2,
+                charging: true,
+            }),
+            ..state(3, 3, false)
+        };
+        assert!(Condition::CaseCharging(true).matches(&case_charging, noon));
+        assert!(!Condition::Charging(true).matches(&case_charging, noon));
+        assert!(Condition::CaseCharging(false).matches(&state(3, 3, true), noon));
+    }
+
+    #[test]
+    fn should_match_time_windows() {
+        let work = Condition::TimeBetween {
+            start: time("09:00"),
+            end: time("17:00"),
+        };
+        let night = Condition::TimeBetween {
+            start: time("22:00"),
+            end: time("06:30"),
+        };
+        let state = SoundcoreDeviceState::default();
+        assert!(work.matches(&state, time("09:00")));
+        assert!(!work.matches(&state, time("17:00")));
+        assert!(night.matches(&state, time("23:15")));
+        assert!(night.matches(&state, time("06:00")));
+        assert!(!night.matches(&state, time("12:00")));
+    }
+
+    #[test]
+    fn should_parse_times() {
+        assert_eq!(time("7:05").to_string(), "07:05");
+        assert!("24:00".parse::<TimeOfDay>().is_err());
+        assert!("noon".parse::<TimeOfDay>().is_err());
+    }
+
+    #[test]
+    fn should_combine_conditions() {
+        let noon = time("12:00");
+        let condition = Condition::All(vec![
+            Condition::Charging(true),
+            Condition::Any(vec![
+                Condition::BatteryBelow(50),
+                Condition::SoundMode(CurrentSoundMode::ANC),
+            ]),
+        ]);
+        assert!(condition.matches(&state(1, 5, true), noon));
+        assert!(!condition.matches(&state(5, 5, true), noon));
+        assert!(Condition::Any(vec![]).validate().is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use soundcore_lib::btaddr::BluetoothAdrr;
+
+use crate::{Action, Condition, RulesError, RulesResult};
+
+/// The rules file, e.g.
+/// ```toml
+/// dry_run = true
+///
+/// [[rule]]
+/// name = "Podcasts at work"
+/// when = { time_between = { start = "09:00", end = "17:00" } }
+/// then = { eq_profile = "Podcast" }
+/// ```
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RulesConfig {
+    /// Only log the actions instead of sending them to the devices
+    #[serde(default)]
+    pub dry_run: bool,
+    /// How often time based conditions are checked
+    #[serde(default = "default_tick_secs")]
+    pub tick_secs: u64,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    /// Limits the rule to a single device, otherwise it applies to all of them
+    #[serde(default)]
+    pub device: Option<String>,
+    pub when: Condition,
+    pub then: Action,
+}
+
+impl Rule {
+    pub fn applies_to(&self, addr: &BluetoothAdrr) -> bool {
+        match &self.device {
+            Some(device) => BluetoothAdrr::from_str(device).is_ok_and(|device| device == *addr),
+            None => true,
+        }
+    }
+}
+
+fn default_tick_secs() -> u64 {
+    60
+}
+
+impl RulesConfig {
+    pub fn load(path: &Path) -> RulesResult<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml(s: &str) -> RulesResult<Self> {
+        let config: Self =
+            toml::from_str(s).map_err(|e| RulesError::InvalidConfig(e.message().to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn tick(&self) -> Duration {
+        Duration::from_secs(self.tick_secs)
+    }
+
+    fn validate(&self) -> RulesResult<()> {
+        if self.tick_secs == 0 {
+            return Err(RulesError::InvalidConfig(
+                "tick_secs must be positive".to_string(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if !names.insert(rule.name.as_str()) {
+                return Err(RulesError::InvalidConfig(format!(
+                    "Duplicate rule name {}",
+                    rule.name
+                )));
+            }
+            if let Some(device) = &rule.device {
+                BluetoothAdrr::from_str(device).map_err(|_| {
+                    RulesError::InvalidConfig(format!("Invalid device address {}", device))
+                })?;
+            }
+            rule.when.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use soundcore_lib::models::{CurrentSoundMode, EQProfile};
+
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        dry_run = true
+
+        [[rule]]
+        name = "Transparency on low battery"
+        when = { battery_below = 10 }
+        then = { sound_mode = "transparency" }
+
+        [[rule]]
+        name = "Podcasts at work"
+        device = "AC:12:2F:00:00:01"
+        when = { time_between = { start = "09:00", end = "17:00" } }
+        then = { eq_profile = "Podcast" }
+
+        [[rule]]
+        name = "No ANC while charging"
+        when = { all = [{ charging = true }, { sound_mode = "anc" }] }
+        then = { sound_mode = "normal" }
+    "#;
+
+    #[test]
+    fn should_parse_example() {
+        let config = RulesConfig::from_toml(EXAMPLE).unwrap();
+        assert!(config.dry_run);
+        assert_eq!(config.tick(), Duration::from_secs(60));
+        assert_eq!(config.rules.len(), 3);
+        assert_eq!(config.rules[0].when, Condition::BatteryBelow(10));
+        assert_eq!(
+            config.rules[0].then,
+            Action::SoundMode(CurrentSoundMode::Transparency)
+        );
+        assert_eq!(config.rules[1].then, Action::EqProfile(EQProfile::Podcast));
+
+        let addr = BluetoothAdrr::from_str("AC:12:2F:00:00:01").unwrap();
+        let other = BluetoothAdrr::from_str("AC:12:2F:00:00:02").unwrap();
+        assert!(config.rules[1].applies_to(&addr));
+        assert!(!config.rules[1].applies_to(&other));
+        assert!(config.rules[0].applies_to(&other));
+    }
+
+    #[test]
+    fn should_reject_invalid_configs() {
+        let duplicate = r#"
+            [[rule]]
+            name = "a"
+            when = { charging = true }
+            then = { sound_mode = "normal" }
+
+            [[rule]]
+            name = "a"
+            when = { charging = false }
+            then = { sound_mode = "anc" }
+        "#;
+        assert!(RulesConfig::from_toml(duplicate).is_err());
+
+        let bad_time = r#"
+            [[rule]]
+            name = "a"
+            when = { time_between = { start = "25:00", end = "17:00" } }
+            then = { sound_mode = "normal" }
+        "#;
+        assert!(RulesConfig::from_toml(bad_time).is_err());
+        assert!(RulesConfig::from_toml("tick_secs = 0").is_err());
+    }
+}
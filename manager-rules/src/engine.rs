@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use chrono::{Local, Timelike};
+use log::{debug, info, warn};
+use tokio::sync::broadcast::error::RecvError;
+
+use manager_fut::ManagerFuture;
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::ble::BLEConnectionManager;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device_manager::DeviceManager;
+
+use crate::{Action, RulesConfig, RulesResult, TimeOfDay};
+
+/// An action whose rule started matching
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedAction {
+    pub addr: BluetoothAdrr,
+    pub rule: String,
+    pub action: Action,
+}
+
+/// Evaluates the rules of a config against device states.
+/// A rule fires once when its condition starts matching and again only after it stopped matching,
+/// so that it doesn't fight manual changes.
+pub struct RulesEngine {
+    config: RulesConfig,
+    /// Indices of the rules currently matching each device
+    matching: HashSet<(usize, BluetoothAdrr)>,
+}
+
+impl RulesEngine {
+    pub fn new(config: RulesConfig) -> Self {
+        Self {
+            config,
+            matching: HashSet::new(),
+        }
+    }
+
+    pub fn evaluate(
+        &mut self,
+        addr: &BluetoothAdrr,
+        state: &SoundcoreDeviceState,
+        now: TimeOfDay,
+    ) -> Vec<PlannedAction> {
+        let mut planned = Vec::new();
+        for (idx, rule) in self.config.rules.iter().enumerate() {
+            if !rule.applies_to(addr) {
+                continue;
+            }
+            let key = (idx, addr.to_owned());
+            if !rule.when.matches(state, now) {
+                self.matching.remove(&key);
+            } else if self.matching.insert(key) {
+                planned.push(PlannedAction {
+                    addr: addr.to_owned(),
+                    rule: rule.name.to_owned(),
+                    action: rule.then.to_owned(),
+                });
+            }
+        }
+        planned
+    }
+
+    /// Evaluates the rules on every state update and periodically for time based conditions,
+    /// until the manager's state stream closes
+    pub async fn run<B, F>(&mut self, manager: &DeviceManager<B, F>) -> RulesResult<()>
+    where
+        B: BLEConnectionManager,
+        F: ManagerFuture,
+    {
+        let mut events = manager.state_events();
+        let tick = F::sleep(self.config.tick());
+        tokio::pin!(tick);
+        let mut planned = self.evaluate_connected(manager).await;
+        loop {
+            for action in planned {
+                self.execute(manager, action).await;
+            }
+            planned = tokio::select! {
+                event = events.recv() => match event {
+                    Ok((addr, state)) => self.evaluate(&addr, &state, now()),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Rules engine skipped {} state updates", skipped);
+                        Vec::new()
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                _ = &mut tick => {
+                    tick.set(F::sleep(self.config.tick()));
+                    self.evaluate_connected(manager).await
+                }
+            };
+        }
+    }
+
+    async fn evaluate_connected<B, F>(
+        &mut self,
+        manager: &DeviceManager<B, F>,
+    ) -> Vec<PlannedAction>
+    where
+        B: BLEConnectionManager,
+        F: ManagerFuture,
+    {
+        let mut planned = Vec::new();
+        for addr in manager.list_open_connections().await {
+            if let Some(device) = manager.get_device(addr.to_owned()).await {
+                let state = device.latest_state().await;
+                planned.extend(self.evaluate(&addr, &state, now()));
+            }
+        }
+        planned
+    }
+
+    /// Applies the action, or only logs it in dry-run mode.
+    /// Failures are logged since one device shouldn't stop the rules of the others.
+    pub async fn execute<B, F>(&self, manager: &DeviceManager<B, F>, planned: PlannedAction)
+    where
+        B: BLEConnectionManager,
+        F: ManagerFuture,
+    {
+        if self.config.dry_run {
+            info!(
+                "[dry run] {}: would {} on {}",
+                planned.rule, planned.action, planned.addr
+            );
+            return;
+        }
+        let Some(device) = manager.get_device(planned.addr.to_owned()).await else {
+            debug!(
+                "{} is no longer connected, skipping {}",
+                planned.addr, planned.rule
+            );
+            return;
+        };
+        info!("{}: {} on {}", planned.rule, planned.action, planned.addr);
+        if let Err(e) = planned.action.apply(&device).await {
+            warn!("Rule {} failed on {}: {}", planned.rule, planned.addr, e);
+        }
+    }
+}
+
+fn now() -> TimeOfDay {
+    let now = Local::now();
+    TimeOfDay::new(now.hour() as u8, now.minute() as u8).expect("chrono returns a valid time")
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use std::time::Duration;
+
+    use soundcore_lib::device_manager::create_device_manager;
+    use soundcore_lib::models::{Battery, CurrentSoundMode, SingleBattery};
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [[rule]]
+        name = "Transparency on low battery"
+        when = { battery_below = 10 }
+        then = { sound_mode = "transparency" }
+    "#;
+
+    fn state(level: u8) -> SoundcoreDeviceState {
+        SoundcoreDeviceState {
+            battery: Battery::Single(SingleBattery {
+                level,
+                charging: false,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn noon() -> TimeOfDay {
+        TimeOfDay::new(12, 0).unwrap()
+    }
+
+    #[test]
+    fn should_fire_once_per_match() {
+        let mut engine = RulesEngine::new(RulesConfig::from_toml(CONFIG).unwrap());
+        let addr = BluetoothAdrr::from(1);
+
+        assert!(engine.evaluate(&addr, &state(3), noon()).is_empty());
+        let planned = engine.evaluate(&addr, &state(0), noon());
+        assert_eq!(
+            planned,
+            vec![PlannedAction {
+                addr: addr.to_owned(),
+                rule: "Transparency on low battery".to_string(),
+                action: Action::SoundMode(CurrentSoundMode::Transparency),
+            }]
+        );
+        assert!(engine.evaluate(&addr, &state(0), noon()).is_empty());
+        // Other devices are tracked separately
+        assert_eq!(
+            engine
+                .evaluate(&BluetoothAdrr::from(2), &state(0), noon())
+                .len(),
+            1
+        );
+
+        assert!(engine.evaluate(&addr, &state(5), noon()).is_empty());
+        assert_eq!(engine.evaluate(&addr, &state(0), noon()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_run_rules_on_connected_devices() {
+        let manager = create_device_manager().await;
+        let discovered = manager.ble_scan(None).await.unwrap()[0].to_owned();
+        let device = manager.connect(discovered).await.unwrap();
+        let config = RulesConfig::from_toml(
+            r#"
+            [[rule]]
+            name = "Never normal"
+            when = { sound_mode = "normal" }
+            then = { sound_mode = "transparency" }
+            "#,
+        )
+        .unwrap();
+
+        let mut engine = RulesEngine::new(config);
+        // The manager's state stream stays open while it's alive
+        let run = tokio::time::timeout(Duration::from_millis(200), engine.run(&manager)).await;
+        assert!(run.is_err());
+        assert_eq!(
+            device.latest_state().await.sound_mode.current,
+            CurrentSoundMode::Transparency
+        );
+    }
+
+    #[tokio::test]
+    async fn should_only_log_in_dry_run() {
+        let manager = create_device_manager().await;
+        let discovered = manager.ble_scan(None).await.unwrap()[0].to_owned();
+        let addr = discovered.descriptor.addr.to_owned();
+        let device = manager.connect(discovered).await.unwrap();
+        let planned = PlannedAction {
+            addr,
+            rule: "Transparency".to_string(),
+            action: Action::SoundMode(CurrentSoundMode::Transparency),
+        };
+
+        let dry_run = RulesConfig::from_toml(&format!("dry_run = true\n{}", CONFIG)).unwrap();
+        RulesEngine::new(dry_run)
+            .execute(&manager, planned.to_owned())
+            .await;
+        assert_eq!(
+            device.latest_state().await.sound_mode.current,
+            CurrentSoundMode::Normal
+        );
+
+        let config = RulesConfig::from_toml(CONFIG).unwrap();
+        RulesEngine::new(config).execute(&manager, planned).await;
+        assert_eq!(
+            device.latest_state().await.sound_mode.current,
+            CurrentSoundMode::Transparency
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use soundcore_lib::error::SoundcoreLibError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RulesError {
+    #[error("Failed to read the rules file")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid rules file: {0}")]
+    InvalidConfig(String),
+    #[error("Device error: {0}")]
+    Device(#[from] SoundcoreLibError),
+}
+
+pub type RulesResult<T> = Result<T, RulesError>;
@@ -0,0 +1,15 @@
+//! Declarative automations for connected devices.
+//! Rules are evaluated against device state updates and the time of day,
+//! and drive the device setters through a `DeviceManager`.
+
+mod action;
+mod condition;
+mod config;
+mod engine;
+mod error;
+
+pub use action::*;
+pub use condition::*;
+pub use config::*;
+pub use engine::*;
+pub use error::*;
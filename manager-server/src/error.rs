@@ -0,0 +1,39 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use thiserror::Error;
+
+use manager_bridge::BridgeResponse;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::error::SoundcoreLibError;
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("Device {0} not found")]
+    DeviceNotFound(BluetoothAdrr),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Device error: {0}")]
+    Device(#[from] SoundcoreLibError),
+}
+
+pub type ServerResult<T> = Result<T, ServerError>;
+
+/// Errors are sent as `deviceNotFound` or `genericError` events, like the app's bridge does
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ServerError::DeviceNotFound(_) => StatusCode::NOT_FOUND,
+            ServerError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            ServerError::Device(SoundcoreLibError::FeatureNotSupported(_)) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ServerError::Device(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let event = match self {
+            ServerError::DeviceNotFound(addr) => BridgeResponse::DeviceNotFound(addr),
+            e => BridgeResponse::GenericError(e.to_string()),
+        };
+        (status, Json(event)).into_response()
+    }
+}
@@ -0,0 +1,12 @@
+//! An HTTP and WebSocket API for a `DeviceManager`, for consumers which can't link Rust.
+//! Payloads reuse the serde types of soundcore-lib, and responses and WebSocket events are
+//! manager-bridge's `BridgeResponse`s like the app's, so the UI can run in a plain browser.
+
+mod error;
+mod metrics;
+mod routes;
+mod ws;
+
+pub use error::*;
+pub use metrics::render as render_metrics;
+pub use routes::*;
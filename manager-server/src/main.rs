@@ -0,0 +1,54 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use log::{info, warn};
+use tower_http::cors::CorsLayer;
+
+use manager_server::router;
+use soundcore_lib::device_manager::create_device_manager;
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[arg(long, env = "SOUNDCORE_SERVER_ADDR", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// Allows requests from any origin, e.g. the UI's dev server
+    #[arg(long)]
+    cors: bool,
+    /// How long to scan for devices before connecting, in seconds
+    #[arg(long, default_value_t = 5)]
+    scan_secs: u64,
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+
+    let manager = Arc::new(create_device_manager().await);
+    match manager
+        .ble_scan(Some(Duration::from_secs(args.scan_secs)))
+        .await
+    {
+        Ok(discovered) => {
+            for device in discovered.into_iter().filter(|d| d.model.is_some()) {
+                let addr = device.descriptor.addr.to_owned();
+                match manager.connect(device).await {
+                    Ok(_) => info!("Connected to {}", addr),
+                    Err(e) => warn!("Failed to connect to {}: {}", addr, e),
+                }
+            }
+        }
+        Err(e) => warn!("Scan failed: {}", e),
+    }
+
+    let mut app = router(manager);
+    if args.cors {
+        app = app.layer(CorsLayer::permissive());
+    }
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    info!("Listening on {}", args.listen);
+    axum::serve(listener, app).await
+}
@@ -0,0 +1,231 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use log::warn;
+use prometheus::{Encoder, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder};
+
+use soundcore_lib::api::{ConnectionStatsSnapshot, SoundcoreDeviceState};
+use soundcore_lib::device_manager::{DeviceConnectionStatus, DeviceSnapshot};
+use soundcore_lib::models::{Battery, CurrentSoundMode, SingleBattery};
+
+use crate::SharedManager;
+
+const SOUND_MODES: [CurrentSoundMode; 3] = [
+    CurrentSoundMode::ANC,
+    CurrentSoundMode::Transparency,
+    CurrentSoundMode::Normal,
+];
+
+/// `GET /metrics` in the Prometheus text format
+pub(crate) async fn metrics(State(manager): State<SharedManager>) -> impl IntoResponse {
+    let snapshots = manager.snapshot().await;
+    let stats = manager.connection_stats().await;
+    let devices = snapshots.iter().map(|snapshot| {
+        let stats = stats.get(&snapshot.addr).copied().unwrap_or_default();
+        (snapshot, stats)
+    });
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        render(devices),
+    )
+}
+
+/// Built from the current snapshots on every scrape, disconnected devices keep their last state
+pub fn render<'a>(
+    devices: impl IntoIterator<Item = (&'a DeviceSnapshot, ConnectionStatsSnapshot)>,
+) -> String {
+    let metrics = Metrics::new();
+    for (snapshot, stats) in devices {
+        metrics.record(snapshot, &stats);
+    }
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer) {
+        warn!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+struct Metrics {
+    registry: Registry,
+    connected: IntGaugeVec,
+    battery_level: IntGaugeVec,
+    battery_charging: IntGaugeVec,
+    firmware: IntGaugeVec,
+    sound_mode: IntGaugeVec,
+    packets_received: IntCounterVec,
+    parse_failures: IntCounterVec,
+    checksum_mismatches: IntCounterVec,
+    reconnects: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry =
+            Registry::new_custom(Some("soundcore".to_string()), None).expect("the prefix is valid");
+        let gauge = |name: &str, help: &str, labels: &[&str]| {
+            let gauge = IntGaugeVec::new(Opts::new(name, help), labels).expect("valid gauge");
+            registry
+                .register(Box::new(gauge.clone()))
+                .expect("unique metric names");
+            gauge
+        };
+        let counter = |name: &str, help: &str| {
+            let counter =
+                IntCounterVec::new(Opts::new(name, help), &["addr"]).expect("valid counter");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("unique metric names");
+            counter
+        };
+
+        Self {
+            connected: gauge(
+                "connected",
+                "Whether the device is connected",
+                &["addr", "model"],
+            ),
+            battery_level: gauge(
+                "battery_level",
+                "Battery level in the 0-5 range reported by the device",
+                &["addr", "side"],
+            ),
+            battery_charging: gauge(
+                "battery_charging",
+                "Whether the battery is charging",
+                &["addr", "side"],
+            ),
+            firmware: gauge(
+                "firmware_info",
+                "Firmware version of each side, always 1",
+                &["addr", "side", "version"],
+            ),
+            sound_mode: gauge(
+                "sound_mode",
+                "1 for the current sound mode, 0 for the others",
+                &["addr", "mode"],
+            ),
+            packets_received: counter("packets_received_total", "Packets received from the device"),
+            parse_failures: counter("parse_failures_total", "Packets which couldn't be parsed"),
+            checksum_mismatches: counter(
+                "checksum_mismatches_total",
+                "Packets with an invalid checksum",
+            ),
+            reconnects: counter("reconnects_total", "Connections after the first one"),
+            registry,
+        }
+    }
+
+    fn record(&self, snapshot: &DeviceSnapshot, stats: &ConnectionStatsSnapshot) {
+        let addr = snapshot.addr.to_string();
+        let state = &snapshot.state;
+        let model = state
+            .serial
+            .as_ref()
+            .and_then(|sn| sn.to_model())
+            .map(|model| format!("{:?}", model))
+            .unwrap_or_default();
+        self.connected
+            .with_label_values(&[&addr, &model])
+            .set((snapshot.status == DeviceConnectionStatus::Connected) as i64);
+
+        for (side, battery) in batteries(state) {
+            self.battery_level
+                .with_label_values(&[&addr, side])
+                .set(battery.level as i64);
+            self.battery_charging
+                .with_label_values(&[&addr, side])
+                .set(battery.charging as i64);
+        }
+        if let Some(fw) = &state.fw {
+            let sides = [
+                ("primary", Some(fw.primary())),
+                ("secondary", fw.secondary()),
+            ];
+            for (side, version) in sides {
+                if let Some(version) = version {
+                    self.firmware
+                        .with_label_values(&[&addr, side, &version.to_string()])
+                        .set(1);
+                }
+            }
+        }
+        for mode in SOUND_MODES {
+            self.sound_mode
+                .with_label_values(&[&addr, &mode.to_string().to_lowercase()])
+                .set((state.sound_mode.current == mode) as i64);
+        }
+
+        let counters = [
+            (&self.packets_received, stats.packets_received),
+            (&self.parse_failures, stats.parse_failures),
+            (&self.checksum_mismatches, stats.checksum_mismatches),
+            (&self.reconnects, stats.reconnects),
+        ];
+        for (counter, value) in counters {
+            counter.with_label_values(&[&addr]).inc_by(value);
+        }
+    }
+}
+
+fn batteries(state: &SoundcoreDeviceState) -> Vec<(&'static str, SingleBattery)> {
+    match state.battery {
+        Battery::Single(battery) => vec![("single", battery)],
+        Battery::Dual(battery) => vec![("left", battery.left), ("right", battery.right)],
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use soundcore_lib::btaddr::BluetoothAdrr;
+    use soundcore_lib::models::{DeviceFirmware, DualBattery, FirmwareVer};
+
+    use super::*;
+
+    #[test]
+    fn should_render_device_metrics() {
+        let snapshot = DeviceSnapshot {
+            addr: BluetoothAdrr::from(1),
+            status: DeviceConnectionStatus::Connected,
+            state: SoundcoreDeviceState {
+                battery: Battery::Dual(DualBattery {
+                    left: SingleBattery {
+                        level: 4,
+                        charging: true,
+                    },
+                    right: SingleBattery {
+                        level: 2,
+                        charging: false,
+                    },
+                }),
+                fw: Some(DeviceFirmware::new(
+                    FirmwareVer::new(2, 61),
+                    Some(FirmwareVer::new(2, 60)),
+                )),
+                ..Default::default()
+            },
+        };
+        let stats = ConnectionStatsSnapshot {
+            packets_received: 12,
+            checksum_mismatches: 2,
+            ..Default::default()
+        };
+        let addr = snapshot.addr.to_string();
+
+        let text = render([(&snapshot, stats)]);
+        let expected = [
+            format!("soundcore_battery_level{{addr=\"{addr}\",side=\"left\"}} 4"),
+            format!("soundcore_battery_charging{{addr=\"{addr}\",side=\"right\"}} 0"),
+            format!(
+                "soundcore_firmware_info{{addr=\"{addr}\",side=\"secondary\",version=\"02.60\"}} 1"
+            ),
+            format!("soundcore_sound_mode{{addr=\"{addr}\",mode=\"normal\"}} 1"),
+            format!("soundcore_sound_mode{{addr=\"{addr}\",mode=\"anc\"}} 0"),
+            format!("soundcore_packets_received_total{{addr=\"{addr}\"}} 12"),
+            format!("soundcore_checksum_mismatches_total{{addr=\"{addr}\"}} 2"),
+            format!("soundcore_reconnects_total{{addr=\"{addr}\"}} 0"),
+        ];
+        for line in expected {
+            assert!(text.contains(&line), "missing {line} in\n{text}");
+        }
+    }
+}
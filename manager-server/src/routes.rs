@@ -0,0 +1,167 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use manager_bridge::{BridgeResponse, SetEqualizerPayload};
+use manager_fut::TokioFuture;
+use soundcore_lib::api::SoundcoreDeviceState;
+use soundcore_lib::ble::BLEConnectionManager;
+use soundcore_lib::btaddr::BluetoothAdrr;
+use soundcore_lib::device::SoundcoreBLEDevice;
+use soundcore_lib::device_manager::{DefaultDeviceManager, DeviceManager, DeviceSnapshot};
+use soundcore_lib::models::SoundMode;
+
+use crate::metrics::metrics;
+use crate::ws::events;
+use crate::{ServerError, ServerResult};
+
+pub type SharedManager = Arc<DefaultDeviceManager>;
+
+/// `GET /devices`, `GET /devices/:addr/state`, `POST /devices/:addr/sound-mode`,
+/// `POST /devices/:addr/eq`, the `GET /events` WebSocket and `GET /metrics`
+pub fn router(manager: SharedManager) -> Router {
+    Router::new()
+        .route("/devices", get(list_devices))
+        .route("/devices/:addr/state", get(device_state))
+        .route("/devices/:addr/sound-mode", post(set_sound_mode))
+        .route("/devices/:addr/eq", post(set_eq))
+        .route("/events", get(events))
+        .route("/metrics", get(metrics))
+        .with_state(manager)
+}
+
+/// The last known state of every device that has been connected
+async fn list_devices(State(manager): State<SharedManager>) -> Json<Vec<DeviceSnapshot>> {
+    Json(manager.snapshot().await)
+}
+
+async fn device_state(
+    State(manager): State<SharedManager>,
+    Path(addr): Path<String>,
+) -> ServerResult<Json<SoundcoreDeviceState>> {
+    let (_, device) = connected_device(&manager, &addr).await?;
+    Ok(Json(device.latest_state().await))
+}
+
+async fn set_sound_mode(
+    State(manager): State<SharedManager>,
+    Path(addr): Path<String>,
+    Json(sound_mode): Json<SoundMode>,
+) -> ServerResult<Json<BridgeResponse>> {
+    let (addr, device) = connected_device(&manager, &addr).await?;
+    device.set_sound_mode(sound_mode).await?;
+    Ok(Json(BridgeResponse::SoundModeUpdated(addr)))
+}
+
+async fn set_eq(
+    State(manager): State<SharedManager>,
+    Path(addr): Path<String>,
+    Json(payload): Json<SetEqualizerPayload>,
+) -> ServerResult<Json<BridgeResponse>> {
+    let (addr, device) = connected_device(&manager, &addr).await?;
+    device.set_eq(payload.into()).await?;
+    Ok(Json(BridgeResponse::EqualizerUpdated(addr)))
+}
+
+async fn connected_device<B: BLEConnectionManager>(
+    manager: &DeviceManager<B, TokioFuture>,
+    addr: &str,
+) -> ServerResult<(
+    BluetoothAdrr,
+    Arc<SoundcoreBLEDevice<B::Connection, TokioFuture>>,
+)> {
+    let addr =
+        BluetoothAdrr::from_str(addr).map_err(|_| ServerError::InvalidAddress(addr.to_string()))?;
+    let device = manager
+        .get_device(addr.to_owned())
+        .await
+        .ok_or(ServerError::DeviceNotFound(addr.to_owned()))?;
+    Ok((addr, device))
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod routes_tests {
+    use axum::body::Body;
+    use axum::http::{header, Method, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use soundcore_lib::device_manager::create_device_manager;
+    use soundcore_lib::models::CurrentSoundMode;
+
+    use super::*;
+
+    async fn request(
+        app: Router,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.map_or(Body::empty(), |b| Body::from(b.to_string())))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_serve_devices_and_apply_sound_modes() {
+        let manager = Arc::new(create_device_manager().await);
+        let discovered = manager.ble_scan(None).await.unwrap()[0].to_owned();
+        let addr = discovered.descriptor.addr.to_owned();
+        let device = manager.connect(discovered).await.unwrap();
+        let app = router(manager);
+
+        let (status, devices) = request(app.to_owned(), Method::GET, "/devices", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(devices.as_array().map(Vec::len), Some(1));
+
+        let state_uri = format!("/devices/{}/state", addr);
+        let (status, state) = request(app.to_owned(), Method::GET, &state_uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(state["soundMode"]["current"], "normal");
+
+        let mut sound_mode = state["soundMode"].to_owned();
+        sound_mode["current"] = json!("transparency");
+        let uri = format!("/devices/{}/sound-mode", addr);
+        let (status, event) = request(app, Method::POST, &uri, Some(sound_mode)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(event["kind"], "soundModeUpdated");
+        assert_eq!(
+            device.latest_state().await.sound_mode.current,
+            CurrentSoundMode::Transparency
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_unknown_devices() {
+        let app = router(Arc::new(create_device_manager().await));
+
+        let (status, event) = request(
+            app.to_owned(),
+            Method::GET,
+            "/devices/AC:12:2F:00:00:01/state",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(event["kind"], "deviceNotFound");
+
+        let body = json!({ "command": "setEqualizerPreset", "payload": "Podcast" });
+        let (status, event) = request(app, Method::POST, "/devices/nope/eq", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(event["kind"], "genericError");
+    }
+}
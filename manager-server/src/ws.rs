@@ -0,0 +1,58 @@
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{State, WebSocketUpgrade};
+use axum::response::Response;
+use log::{debug, warn};
+use tokio::sync::broadcast::error::RecvError;
+
+use manager_bridge::{BridgeResponse, TaggedStateChange, TaggedStateResponse};
+
+use crate::SharedManager;
+
+pub(crate) async fn events(
+    State(manager): State<SharedManager>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade.on_upgrade(move |socket| stream_events(manager, socket))
+}
+
+/// Streams `newState` and `stateChanged` events until the client goes away.
+/// Messages from the client are ignored, commands go through the REST routes.
+async fn stream_events(manager: SharedManager, mut socket: WebSocket) {
+    let mut states = manager.state_events();
+    let mut changes = manager.state_change_events();
+    loop {
+        let event = tokio::select! {
+            event = states.recv() => match event {
+                Ok((addr, state)) => BridgeResponse::NewState(TaggedStateResponse { addr, state }),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("WebSocket client skipped {} state updates", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            event = changes.recv() => match event {
+                Ok((addr, change)) => BridgeResponse::StateChanged(TaggedStateChange { addr, change }),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("WebSocket client skipped {} state changes", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+        let json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize {:?}: {}", event, e);
+                continue;
+            }
+        };
+        if socket.send(Message::Text(json)).await.is_err() {
+            break;
+        }
+    }
+    debug!("WebSocket client disconnected");
+}
@@ -1,5 +1,13 @@
 import { WebBLEDevice } from '@wasm/manager_wasm';
-import { EQProfile, SoundcoreDeviceState, SoundMode } from '@generated-types/soundcore-lib';
+import {
+  EQFrequencyResponse,
+  EQPreset,
+  EQPresetLibrary,
+  EQProfile,
+  SoundcoreDeviceState,
+  SoundMode,
+  SoundModeCycle
+} from '@generated-types/soundcore-lib';
 import { useWebManagerStore } from '@stores/web/useWebManagerStore';
 
 export class BLEDevice {
@@ -23,14 +31,58 @@ export class BLEDevice {
     return this.webBLEDevice.setSoundMode(JSON.stringify(soundMode));
   }
 
+  public async setSoundModeCycle(cycle: SoundModeCycle): Promise<void> {
+    return this.webBLEDevice.setSoundModeCycle(JSON.stringify(cycle));
+  }
+
   public async setEqualizerCustom(values: number[]): Promise<void> {
     return this.webBLEDevice.setEqualizerCustom(new Int8Array(values));
   }
 
+  public async setEqualizerCustomStereo(left: number[], right: number[]): Promise<void> {
+    return this.webBLEDevice.setEqualizerCustomStereo(new Int8Array(left), new Int8Array(right));
+  }
+
   public async setEqualizerPreset(profile: EQProfile): Promise<void> {
     return this.webBLEDevice.setEqualizerPreset(JSON.stringify(profile));
   }
 
+  public loadEqPresets(library: EQPresetLibrary) {
+    this.webBLEDevice.loadEqPresets(JSON.stringify(library));
+  }
+
+  public eqPresets(): EQPresetLibrary {
+    return JSON.parse(this.webBLEDevice.eqPresets());
+  }
+
+  public async saveEqPreset(preset: EQPreset): Promise<void> {
+    return this.webBLEDevice.saveEqPreset(JSON.stringify(preset));
+  }
+
+  public async importEqPresets(presets: EQPreset[]): Promise<void> {
+    return this.webBLEDevice.importEqPresets(JSON.stringify(presets));
+  }
+
+  public exportEqPresets(): EQPreset[] {
+    return JSON.parse(this.webBLEDevice.exportEqPresets());
+  }
+
+  public renameEqPreset(name: string, newName: string) {
+    this.webBLEDevice.renameEqPreset(name, newName);
+  }
+
+  public deleteEqPreset(name: string) {
+    this.webBLEDevice.deleteEqPreset(name);
+  }
+
+  public async applyEqPreset(name: string): Promise<void> {
+    return this.webBLEDevice.applyEqPreset(name);
+  }
+
+  public async eqFrequencyResponse(points: number): Promise<EQFrequencyResponse> {
+    return this.webBLEDevice.eqFrequencyResponse(points);
+  }
+
   public free() {
     this.webBLEDevice.free();
   }
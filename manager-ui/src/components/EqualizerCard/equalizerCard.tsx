@@ -73,8 +73,22 @@ export const EqualizerCard: React.FC<EqualizerCardProps> = ({ state }) => {
     return mapRangeArray(valueArr, 0, 240, -6, 6).map((v) => v * 2);
   };
 
+  // The curves of the artist presets are unknown, so the library rejects them
+  const professionalProfiles: string[] = [
+    EQProfile.Foxes,
+    EQProfile.Halestorm,
+    EQProfile.Lecrae,
+    EQProfile.Daya,
+    EQProfile.CedricGervais,
+    EQProfile.TheInfamousStringdusters,
+    EQProfile.JohnPaulWhite
+  ];
   const eqProfiles = Object.keys(EQProfile).filter((item) => {
-    return isNaN(Number(item)) && item !== 'Custom' && (!hasBassUp || item !== 'BassBooster');
+    return (
+      item !== 'Custom' &&
+      !professionalProfiles.includes(item) &&
+      (!hasBassUp || item !== 'BassBooster')
+    );
   });
 
   const onCardPress = (v: string) => {
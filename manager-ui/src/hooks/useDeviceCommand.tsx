@@ -1,4 +1,9 @@
-import { BluetoothAdrr, EQProfile, SoundMode } from '@generated-types/soundcore-lib';
+import {
+  BluetoothAdrr,
+  EQProfile,
+  SoundMode,
+  SoundModeCycle
+} from '@generated-types/soundcore-lib';
 import { useAsyncBridgeRequest } from './useAsyncBridge';
 import { BLEDevice } from '../ble/bleDevice';
 
@@ -18,6 +23,23 @@ export const useUpdateDeviceSoundMode = async (ref: BluetoothAdrr | BLEDevice, m
   }
 };
 
+export const useUpdateDeviceSoundModeCycle = async (
+  ref: BluetoothAdrr | BLEDevice,
+  cycle: SoundModeCycle
+) => {
+  if (window.isTauri && 'address' in ref) {
+    return useAsyncBridgeRequest({
+      command: 'setSoundModeCycle',
+      payload: {
+        addr: ref,
+        payload: cycle
+      }
+    });
+  } else if (ref instanceof BLEDevice) {
+    return ref.setSoundModeCycle(cycle);
+  }
+};
+
 export const useUpdatePresetEqualizer = async (
   ref: BluetoothAdrr | BLEDevice,
   preset: EQProfile
@@ -61,3 +83,29 @@ export const useUpdateCustomEqualizer = async (
     return ref.setEqualizerCustom(values);
   }
 };
+
+/**
+ * Set separate custom EQ values for each channel
+ * @param left The values should be in range -60..=60
+ * @param right The values should be in range -60..=60
+ */
+export const useUpdateCustomStereoEqualizer = async (
+  ref: BluetoothAdrr | BLEDevice,
+  left: number[],
+  right: number[]
+) => {
+  if (window.isTauri) {
+    return useAsyncBridgeRequest({
+      command: 'setEqualizer',
+      payload: {
+        addr: ref,
+        payload: {
+          command: 'setCustomStereoEqualizer',
+          payload: { left, right }
+        }
+      }
+    });
+  } else if (ref instanceof BLEDevice) {
+    return ref.setEqualizerCustomStereo(left, right);
+  }
+};
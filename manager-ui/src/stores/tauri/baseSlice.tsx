@@ -2,7 +2,11 @@ import { Event } from '@tauri-apps/api/event';
 import { StateCreator, StoreApi } from 'zustand';
 import { TauriManagerStoreSlices } from './useTauriManagerStore';
 import { BridgeResponse } from '@generated-types/tauri-backend';
-import { BluetoothAdrr, SoundcoreDeviceState } from '@generated-types/soundcore-lib';
+import {
+  BluetoothAdrr,
+  DeviceConnectionStatus,
+  SoundcoreDeviceState
+} from '@generated-types/soundcore-lib';
 
 export interface BaseSlice {
   currentViewedDevice: BluetoothAdrr | null;
@@ -51,6 +55,9 @@ const bridgeResponseHandlers: BridgeResponseHandlers = {
   newState: (payload, _set, get) => {
     get().setStateFromBridgeResponse(payload);
   },
+  stateChanged: (_payload, _set, _get) => {
+    // The full state is delivered by newState, nothing to do here for now
+  },
   scanResult: (payload, _set, get): void => {
     get().setLatestScan(payload);
   },
@@ -77,13 +84,36 @@ const bridgeResponseHandlers: BridgeResponseHandlers = {
   disconnectedAll: (_payload, _set, _get) => {
     // TODO: Wait for this beforing "starting" the app? No-op for now.
   },
+  snapshot: (payload, _set, get) => {
+    payload
+      .filter((snapshot) => snapshot.status === DeviceConnectionStatus.Connected)
+      .forEach((snapshot) => {
+        get().addConnectedDevice(snapshot.addr);
+        get().setStateFromBridgeResponse(snapshot);
+      });
+  },
   deviceNotFound: (_payload, _set, _get) => {
     throw new Error('Function not implemented.');
   },
   soundModeUpdated: (_payload, _set, _get) => {
     throw new Error('Function not implemented.');
   },
+  soundModeCycleUpdated: (_payload, _set, _get) => {
+    // The new cycle is delivered through the state change
+  },
   equalizerUpdated: (_e: BluetoothAdrr, _set, _get) => {
     throw new Error('Function not implemented.');
+  },
+  settings: (_payload, _set, _get) => {
+    // TODO: Add a settings view. No-op for now.
+  },
+  eqFrequencyResponse: (_payload, _set, _get) => {
+    // TODO: Plot the response in the equalizer card. No-op for now.
+  },
+  configExported: (_payload, _set, _get) => {
+    // TODO: Offer the snapshot as a download. No-op for now.
+  },
+  configApplied: (_payload, _set, _get) => {
+    // The applied fields are delivered through the state changes
   }
 };
@@ -70,6 +70,9 @@ export interface SoundModeFeatures {
   hasCustomizableTransparency: boolean;
   maxCustomAnc?: number;
   maxCustomTransparency?: number;
+  hasAutoAnc: boolean;
+  /** Manual is always supported */
+  ancStrengthModes: ANCStrengthMode[];
 }
 
 export interface DeviceFeatureSet {
@@ -102,12 +105,44 @@ export interface SoundMode {
   transMode: TransparencyMode;
   customAnc: CustomANCValue;
   customTrans?: CustomTransparencyValue;
+  /** Only part of the customizable transparency layout, like the fields below.
+   * See SoundModeFeatures for whether a model supports them. */
+  autoAnc: boolean;
+  ancStrengthMode: ANCStrengthMode;
+}
+
+export enum EarbudSide {
+  Left = 'left',
+  Right = 'right',
+  Both = 'both'
+}
+
+/** The sound modes the physical button cycles through */
+export interface SoundModeCycle {
+  anc: boolean;
+  transparency: boolean;
+  normal: boolean;
 }
 
 export type EQConfiguration =
   | { type: 'stereo'; value: StereoEQConfiguration }
   | { type: 'mono'; value: MonoEQConfiguration };
 
+export type EQPresetCurve =
+  | { type: 'mono'; value: MonoEQ }
+  | { type: 'stereo'; value: StereoEQ };
+
+/** A user-defined custom EQ curve */
+export interface EQPreset {
+  name: string;
+  curve: EQPresetCurve;
+}
+
+/** A collection of uniquely named presets, kept in insertion order */
+export interface EQPresetLibrary {
+  presets: EQPreset[];
+}
+
 export enum KnownProductCodes {
   A3027 = 'A3027',
   A3028 = 'A3028',
@@ -161,6 +196,11 @@ export interface HearingProtect {
 export interface SoundcoreDeviceState {
   featureSet: DeviceFeatureSet;
   battery: Battery;
+  /**
+   * Only reported by models whose state packets carry the case's level and charging state,
+   * none of the decoded ones do yet
+   */
+  caseBattery?: SingleBattery;
   soundMode: SoundMode;
   eqConfiguration: EQConfiguration;
   serial?: SerialNumber;
@@ -183,10 +223,60 @@ export interface SoundcoreDeviceState {
   ambientSoundNotice?: AmbientSoundNotice;
   powerOnBatteryNotice?: PowerOnBatteryNotice;
   threeDimensionalEffect?: ThreeDimensionalEffect;
+  gameMode?: GameMode;
   deviceColor?: DeviceColor;
   ldac?: LDAC;
   promptLanguage?: PromptLanguage;
   hearingProtect?: HearingProtect;
+  soundModeCycle?: SoundModeCycle;
+}
+
+/**
+ * The writable configuration of a device, used to restore it after a reset.
+ * Fields the device didn't report are left out.
+ */
+export interface DeviceConfigSnapshot {
+  version: number;
+  /** The model the snapshot was taken from */
+  model: KnownProductCodes;
+  soundMode?: SoundMode;
+  eqConfiguration?: EQConfiguration;
+  soundModeCycle?: SoundModeCycle;
+  buttonModel?: ButtonModel;
+  touchTone?: TouchTone;
+  wearDetection?: WearDetection;
+  supportTwoCnn?: SupportTwoCnn;
+  inEarBeep?: InEarBeep;
+  ambientSoundNotice?: AmbientSoundNotice;
+  powerOnBatteryNotice?: PowerOnBatteryNotice;
+  threeDimensionalEffect?: ThreeDimensionalEffect;
+    promptLanguage?: PromptLanguage;
+  autoPowerOff?: AutoPowerOff;
+  hearingProtect?: HearingProtect;
+}
+
+export interface SkippedConfigField {
+  field: StateField;
+  reason: ConfigSkipReason;
+}
+
+/** The outcome of applying a snapshot to a device */
+export interface ConfigApplyReport {
+  applied: StateField[];
+  skipped: SkippedConfigField[];
+}
+
+/** The approximate response of each channel's EQ curve */
+export interface EQFrequencyResponse {
+  left: FrequencyResponsePoint[];
+  right: FrequencyResponsePoint[];
+}
+
+/** A state update along with the fields that changed and its originator */
+export interface StateChange {
+  source: ChangeSource;
+  changed: StateField[];
+  state: SoundcoreDeviceState;
 }
 
 export interface BluetoothAdrr {
@@ -206,6 +296,85 @@ export interface DiscoveredDevice {
   model?: KnownProductCodes;
 }
 
+/** The last known state of a device, along with its connection status. */
+export interface DeviceSnapshot {
+  addr: BluetoothAdrr;
+  status: DeviceConnectionStatus;
+  state: SoundcoreDeviceState;
+}
+
+/** A top-level field of the SoundcoreDeviceState */
+export enum StateField {
+  FeatureSet = 'featureSet',
+  Battery = 'battery',
+  CaseBattery = 'caseBattery',
+  SoundMode = 'soundMode',
+  EqConfiguration = 'eqConfiguration',
+  Serial = 'serial',
+  Fw = 'fw',
+  ButtonModel = 'buttonModel',
+  HostDevice = 'hostDevice',
+  SideTone = 'sideTone',
+  AgeRange = 'ageRange',
+  HearidEqPreset = 'hearidEqPreset',
+  HearId = 'hearId',
+  HearIdHasData = 'hearIdHasData',
+  TouchTone = 'touchTone',
+  TwsStatus = 'twsStatus',
+  WearDetection = 'wearDetection',
+  BassUp = 'bassUp',
+  AutoPowerOff = 'autoPowerOff',
+  SupportTwoCnn = 'supportTwoCnn',
+  InEarBeep = 'inEarBeep',
+  AmbientSoundNotice = 'ambientSoundNotice',
+  PowerOnBatteryNotice = 'powerOnBatteryNotice',
+  ThreeDimensionalEffect = 'threeDimensionalEffect',
+  GameMode = 'gameMode',
+  DeviceColor = 'deviceColor',
+  LDAC = 'ldac',
+  PromptLanguage = 'promptLanguage',
+  HearingProtect = 'hearingProtect',
+  SoundModeCycle = 'soundModeCycle'
+}
+
+/** The originator of a state change */
+export enum ChangeSource {
+  /** The change was made by a command sent from this library */
+  Local = 'local',
+  /** The change was reported by the device, e.g. a button press or a battery update */
+  Device = 'device'
+}
+
+export enum EQFileFormat {
+  /** Equalizer APO/AutoEq parametric filters, e.g. `Filter 1: ON PK Fc 105 Hz Gain -2.3 dB Q 0.70` */
+  Parametric = 'parametric',
+  /** Equalizer APO/AutoEq graphic EQ, e.g. `GraphicEQ: 20 -1.5; 21 -1.5; ...` */
+  Graphic = 'graphic'
+}
+
+/** The connection status of a device known to the DeviceManager. */
+export enum DeviceConnectionStatus {
+  Connected = 'connected',
+  Disconnected = 'disconnected'
+}
+
+/** Known firmware behaviour that differs from what the model normally supports */
+export enum FirmwareQuirk {
+  /** The device sometimes advertises itself as "BES_BLE" */
+  AdvertisesAsBesBle = 'advertisesAsBesBle',
+  /** The earbuds report different firmware versions */
+  TwsFirmwareMismatch = 'twsFirmwareMismatch'
+}
+
+export enum ConfigSkipReason {
+  /** The device doesn't support the field */
+  Unsupported = 'unsupported',
+  /** The device supports the field, but there is no command to change it yet */
+  NotWritable = 'notWritable',
+  /** The device rejected the value, e.g. an EQ with a different number of bands */
+  Invalid = 'invalid'
+}
+
 export enum Action {
   VolumeUp = 'volumeUp',
   VolumeDown = 'volumeDown',
@@ -260,6 +429,12 @@ export interface DualBattery {
   right: SingleBattery;
 }
 
+/** A point of an EQ frequency response, the gain is in dB */
+export interface FrequencyResponsePoint {
+  frequency: number;
+  gain: number;
+}
+
 export interface MonoEQ {
   /** * The values that we store are what is
    * received/sent and clamped within the range of 0..=240 */
@@ -343,13 +518,27 @@ export enum FeatureFlags {
   AMBIENT_SOUND_NOTICE = 'AMBIENT_SOUND_NOTICE',
   POWER_ON_BATTERY_NOTICE = 'POWER_ON_BATTERY_NOTICE',
   SUPPORT_TWO_CONNECTIONS = 'SUPPORT_TWO_CONNECTIONS',
-  MULTIPLE_DEVICE_LIST = 'MULTIPLE_DEVICE_LIST'
+  MULTIPLE_DEVICE_LIST = 'MULTIPLE_DEVICE_LIST',
+  FIND_DEVICE = 'FIND_DEVICE',
+  POWER_OFF = 'POWER_OFF',
+  FACTORY_RESET = 'FACTORY_RESET',
+  RENAME = 'RENAME'
 }
 
 export type BLEAdapterEvent =
   | { kind: 'deviceConnected'; value: BluetoothAdrr }
   | { kind: 'deviceDisconnected'; value: BluetoothAdrr };
 
+/** How the ANC strength is adjusted on newer devices */
+export enum ANCStrengthMode {
+  /** The strength is set using the custom ANC value */
+  Manual = 'Manual',
+  /** The strength follows the environment noise */
+  Environment = 'Environment',
+  /** The strength follows the ear canal fit */
+  EarCanal = 'EarCanal'
+}
+
 export enum AdaptiveANCMode {
   Custom = 'Custom',
   Adaptive = 'Adaptive'
@@ -394,3 +583,16 @@ export enum SceneBasedANCMode {
   Indoor = 'Indoor',
   Custom = 'Custom'
 }
+
+/** An action which can't be undone from the library */
+export enum DestructiveAction {
+  PowerOff = 'powerOff',
+  FactoryReset = 'factoryReset'
+}
+
+/** Issued by SoundcoreBLEDevice::confirmation_token and consumed by the action it was issued for.
+ * This guards against accidental calls, it isn't a security measure. */
+export interface ConfirmationToken {
+  action: DestructiveAction;
+  id: number;
+}
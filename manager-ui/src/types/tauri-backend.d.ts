@@ -12,32 +12,118 @@ export interface TaggedStateResponse {
   state: SoundcoreDeviceState;
 }
 
+export interface TaggedStateChange {
+  addr: BluetoothAdrr;
+  change: StateChange;
+}
+
+export interface TaggedEqFrequencyResponse {
+  addr: BluetoothAdrr;
+  response: EQFrequencyResponse;
+}
+
+export interface TaggedConfigSnapshot {
+  addr: BluetoothAdrr;
+  snapshot: DeviceConfigSnapshot;
+}
+
+export interface TaggedConfigApplyReport {
+  addr: BluetoothAdrr;
+  report: ConfigApplyReport;
+}
+
 export interface ConnectionFailedResponse {
   addr: BluetoothAdrr;
   reason: string;
 }
 
+export interface DevicePreferences {
+  addr: BluetoothAdrr;
+  nickname?: string;
+  autoConnect: boolean;
+  favoriteEqProfiles: EQProfile[];
+}
+
+/** Per-event toggles for desktop notifications */
+export interface NotificationSettings {
+  lowBattery: boolean;
+  /** A low battery notification is shown once a bud drops to this level (0-5) */
+  lowBatteryThreshold: number;
+  lowCaseBattery: boolean;
+  /** Same as `low_battery_threshold`, for the charging case */
+  lowCaseBatteryThreshold: number;
+  chargingStarted: boolean;
+  chargingFinished: boolean;
+  disconnected: boolean;
+  /** Only changes made using the headset buttons are reported */
+  ancChanged: boolean;
+}
+
+export interface AppSettings {
+  /** Stored as a list since BluetoothAdrr can't be used as a JSON object key */
+  devices: DevicePreferences[];
+  notifications: NotificationSettings;
+  scanDurationSecs: number;
+  eqPresets: EQPresetLibrary;
+}
+
 export type BridgeCommand =
   | { command: 'scan'; payload?: undefined }
   | { command: 'connect'; payload: DiscoveredDevice }
   | { command: 'disconnect'; payload: BluetoothAdrr }
   | { command: 'disconnectAll'; payload?: undefined }
+  | { command: 'getSnapshot'; payload?: undefined }
   | { command: 'setSoundMode'; payload: AddrWrappedPayload<SoundMode> }
-  | { command: 'setEqualizer'; payload: AddrWrappedPayload<SetEqualizerPayload> };
+  /** Sets the sound modes the device's button cycles through */
+  | { command: 'setSoundModeCycle'; payload: AddrWrappedPayload<SoundModeCycle> }
+  | { command: 'setEqualizer'; payload: AddrWrappedPayload<SetEqualizerPayload> }
+  | { command: 'getSettings'; payload?: undefined }
+  | { command: 'updateSettings'; payload: AppSettings }
+  /** Saves the preset, validated against the equalizer of the given device */
+  | { command: 'saveEqPreset'; payload: AddrWrappedPayload<EQPreset> }
+  | { command: 'importEqPresets'; payload: AddrWrappedPayload<EQPreset[]> }
+  | { command: 'renameEqPreset'; payload: RenameEqPresetPayload }
+  | { command: 'deleteEqPreset'; payload: string }
+  /** Applies the preset with the given name */
+  | { command: 'applyEqPreset'; payload: AddrWrappedPayload<string> }
+  /** Computes the frequency response of the device's current EQ for plotting */
+  | { command: 'getEqFrequencyResponse'; payload: BluetoothAdrr }
+  | { command: 'exportConfig'; payload: BluetoothAdrr }
+  /** Applies a config exported from the same or a compatible model */
+  | { command: 'applyConfig'; payload: AddrWrappedPayload<DeviceConfigSnapshot> };
+
+export interface RenameEqPresetPayload {
+  name: string;
+  newName: string;
+}
+
+/** Separate curves for the left and right channels, in the same range as SetCustomEqualizer */
+export interface StereoEqualizerValues {
+  left: number[];
+  right: number[];
+}
 
 export type SetEqualizerPayload =
   | { command: 'setCustomEqualizer'; payload: number[] }
+  | { command: 'setCustomStereoEqualizer'; payload: StereoEqualizerValues }
   | { command: 'setEqualizerPreset'; payload: EQProfile };
 
 export type BridgeResponse =
   | { kind: 'scanResult'; payload: DiscoveredDevice[] }
   | { kind: 'connectionEstablished'; payload: TaggedStateResponse }
   | { kind: 'newState'; payload: TaggedStateResponse }
+  | { kind: 'stateChanged'; payload: TaggedStateChange }
   | { kind: 'disconnected'; payload: BluetoothAdrr }
   | { kind: 'disconnectedAll'; payload?: undefined }
+  | { kind: 'snapshot'; payload: DeviceSnapshot[] }
   | { kind: 'adapterEvent'; payload: BLEAdapterEvent }
   | { kind: 'connectionFailed'; payload: ConnectionFailedResponse }
   | { kind: 'genericError'; payload: string }
   | { kind: 'deviceNotFound'; payload: BluetoothAdrr }
   | { kind: 'soundModeUpdated'; payload: BluetoothAdrr }
-  | { kind: 'equalizerUpdated'; payload: BluetoothAdrr };
+  | { kind: 'soundModeCycleUpdated'; payload: BluetoothAdrr }
+  | { kind: 'equalizerUpdated'; payload: BluetoothAdrr }
+  | { kind: 'settings'; payload: AppSettings }
+  | { kind: 'eqFrequencyResponse'; payload: TaggedEqFrequencyResponse }
+  | { kind: 'configExported'; payload: TaggedConfigSnapshot }
+  | { kind: 'configApplied'; payload: TaggedConfigApplyReport };
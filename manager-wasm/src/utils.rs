@@ -49,8 +49,8 @@ pub fn get_soundcore_mac_prefixes() -> Result<BLECompanyIdentifiers, JsValue> {
 
 #[wasm_bindgen(js_name = "getPresetEqValue")]
 pub fn get_preset_eq_value(profile: String, bands: usize) -> Result<Vec<u8>, JsValue> {
-    Ok(EQProfile::from_str(&profile)
+    let eq = EQProfile::from_str(&profile)
         .map_err(|err| format!("{err:?}"))?
-        .eq()
-        .to_bytes(bands))
+        .eq();
+    Ok(eq.values.into_iter().take(bands).collect())
 }
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::sync::Arc;
 
 use js_sys::Function;
@@ -6,8 +7,9 @@ use wasm_bindgen::JsValue;
 use web_sys::BluetoothDevice;
 
 use manager_fut::{ManagerFuture, WasmFuture};
+use soundcore_lib::api::{EQPreset, EQPresetLibrary, EqualizerFeatures};
 use soundcore_lib::device::SoundcoreBLEDevice;
-use soundcore_lib::models::{EQConfiguration, MonoEQ, SoundMode};
+use soundcore_lib::models::{EQConfiguration, MonoEQ, SoundMode, SoundModeCycle};
 
 use crate::connection::WebBLEConnection;
 
@@ -16,6 +18,8 @@ use crate::connection::WebBLEConnection;
 #[wasm_bindgen]
 pub struct WebBLEDevice {
     device: SoundcoreBLEDevice<WebBLEConnection, WasmFuture>,
+    /// Persisted by JS-land, see loadEqPresets/eqPresets
+    eq_presets: RefCell<EQPresetLibrary>,
 }
 
 #[wasm_bindgen]
@@ -26,7 +30,10 @@ impl WebBLEDevice {
         let device = SoundcoreBLEDevice::new(Arc::new(conn))
             .await
             .map_err(|e| format!("{e:?}"))?;
-        Ok(Self { device })
+        Ok(Self {
+            device,
+            eq_presets: RefCell::new(EQPresetLibrary::default()),
+        })
     }
 
     #[wasm_bindgen(js_name = "setOnStateChange")]
@@ -62,6 +69,17 @@ impl WebBLEDevice {
         Ok(())
     }
 
+    #[wasm_bindgen(js_name = "setSoundModeCycle")]
+    pub async fn set_sound_mode_cycle(&self, cycle: String) -> Result<(), JsValue> {
+        let cycle: SoundModeCycle =
+            serde_json::from_str(&cycle).map_err(|err| format!("{err:?}"))?;
+        self.device
+            .set_sound_mode_cycle(cycle)
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = "setEqualizerCustom")]
     pub async fn set_custom_eq(&self, bytes: &[i8]) -> Result<(), JsValue> {
         let eq = EQConfiguration::mono_custom(MonoEQ::from_signed_bytes(bytes.to_vec()));
@@ -72,6 +90,19 @@ impl WebBLEDevice {
         Ok(())
     }
 
+    #[wasm_bindgen(js_name = "setEqualizerCustomStereo")]
+    pub async fn set_custom_stereo_eq(&self, left: &[i8], right: &[i8]) -> Result<(), JsValue> {
+        let eq = EQConfiguration::stereo_custom(
+            MonoEQ::from_signed_bytes(left.to_vec()),
+            MonoEQ::from_signed_bytes(right.to_vec()),
+        );
+        self.device
+            .set_eq(eq)
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = "setEqualizerPreset")]
     pub async fn set_preset_eq(&self, preset: String) -> Result<(), JsValue> {
         let eq = EQConfiguration::stereo_with_profile(
@@ -83,10 +114,123 @@ impl WebBLEDevice {
             .map_err(|err| format!("{err:?}"))?;
         Ok(())
     }
+
+    /// Replaces the preset library with a previously stored one
+    #[wasm_bindgen(js_name = "loadEqPresets")]
+    pub fn load_eq_presets(&self, library: String) -> Result<(), JsValue> {
+        let library: EQPresetLibrary =
+            serde_json::from_str(&library).map_err(|err| format!("{err:?}"))?;
+        library
+            .check_unique_names()
+            .map_err(|err| format!("{err:?}"))?;
+        *self.eq_presets.borrow_mut() = library;
+        Ok(())
+    }
+
+    /// The preset library as JSON, to be stored by the caller
+    #[wasm_bindgen(js_name = "eqPresets")]
+    pub fn eq_presets(&self) -> Result<String, JsValue> {
+        Ok(serde_json::to_string(&*self.eq_presets.borrow()).map_err(|err| format!("{err:?}"))?)
+    }
+
+    #[wasm_bindgen(js_name = "saveEqPreset")]
+    pub async fn save_eq_preset(&self, preset: String) -> Result<(), JsValue> {
+        let preset: EQPreset = serde_json::from_str(&preset).map_err(|err| format!("{err:?}"))?;
+        let features = self.equalizer_features().await?;
+        self.eq_presets
+            .borrow_mut()
+            .save(preset, &features)
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "importEqPresets")]
+    pub async fn import_eq_presets(&self, presets: String) -> Result<(), JsValue> {
+        let presets: Vec<EQPreset> =
+            serde_json::from_str(&presets).map_err(|err| format!("{err:?}"))?;
+        let features = self.equalizer_features().await?;
+        self.eq_presets
+            .borrow_mut()
+            .import(presets, &features)
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "exportEqPresets")]
+    pub fn export_eq_presets(&self) -> Result<String, JsValue> {
+        Ok(serde_json::to_string(&self.eq_presets.borrow().export())
+            .map_err(|err| format!("{err:?}"))?)
+    }
+
+    #[wasm_bindgen(js_name = "renameEqPreset")]
+    pub fn rename_eq_preset(&self, name: String, new_name: String) -> Result<(), JsValue> {
+        self.eq_presets
+            .borrow_mut()
+            .rename(&name, &new_name)
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "deleteEqPreset")]
+    pub fn delete_eq_preset(&self, name: String) -> Result<(), JsValue> {
+        self.eq_presets
+            .borrow_mut()
+            .delete(&name)
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "applyEqPreset")]
+    pub async fn apply_eq_preset(&self, name: String) -> Result<(), JsValue> {
+        let preset = self
+            .eq_presets
+            .borrow()
+            .get(&name)
+            .cloned()
+            .ok_or(format!("EQ preset not found: {name}"))?;
+        preset
+            .validate(&self.equalizer_features().await?)
+            .map_err(|err| format!("{err:?}"))?;
+        self.device
+            .set_eq(preset.eq_configuration())
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        Ok(())
+    }
+
+    /// The approximate frequency response of the current EQ at `points` frequencies
+    #[wasm_bindgen(js_name = "eqFrequencyResponse")]
+    pub async fn eq_frequency_response(
+        &self,
+        points: usize,
+    ) -> Result<EQFrequencyResponse, JsValue> {
+        let response = self
+            .device
+            .latest_state()
+            .await
+            .eq_frequency_response(points)
+            .ok_or("The device does not have an equalizer")?;
+        Ok(serde_wasm_bindgen::to_value(&response)?.into())
+    }
+}
+
+impl WebBLEDevice {
+    async fn equalizer_features(&self) -> Result<EqualizerFeatures, JsValue> {
+        Ok(self
+            .device
+            .latest_state()
+            .await
+            .feature_set
+            .equalizer_features
+            .ok_or("The device does not have an equalizer")?)
+    }
 }
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "SoundcoreDeviceState")]
     pub type SoundcoreDeviceState;
+
+    #[wasm_bindgen(typescript_type = "EQFrequencyResponse")]
+    pub type EQFrequencyResponse;
 }
@@ -1,5 +1,13 @@
+mod config_snapshot;
+mod confirmation;
+mod connection_stats;
+mod eq_presets;
 mod feature_set;
 mod state;
 
+pub use config_snapshot::*;
+pub use confirmation::*;
+pub use connection_stats::*;
+pub use eq_presets::*;
 pub use feature_set::*;
 pub use state::*;
@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::api::{SoundcoreDeviceState, StateField};
+use crate::devices::model_features;
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::models::{
+    AmbientSoundNotice, AutoPowerOff, ButtonModel, EQConfiguration, HearingProtect, InEarBeep,
+    PowerOnBatteryNotice, PromptLanguage, SoundMode, SoundModeCycle, SupportTwoCnn,
+    ThreeDimensionalEffect, TouchTone, WearDetection,
+};
+use crate::types::KnownProductCodes;
+
+/// The writable configuration of a device, used to restore it after a reset.
+/// Fields the device didn't report are left out.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct DeviceConfigSnapshot {
+    pub version: u32,
+    /// The model the snapshot was taken from
+    pub model: KnownProductCodes,
+    pub sound_mode: Option<SoundMode>,
+    pub eq_configuration: Option<EQConfiguration>,
+    pub sound_mode_cycle: Option<SoundModeCycle>,
+    pub button_model: Option<ButtonModel>,
+    // Toggles
+    pub touch_tone: Option<TouchTone>,
+    pub wear_detection: Option<WearDetection>,
+    pub support_two_cnn: Option<SupportTwoCnn>,
+    pub in_ear_beep: Option<InEarBeep>,
+    pub ambient_sound_notice: Option<AmbientSoundNotice>,
+    pub power_on_battery_notice: Option<PowerOnBatteryNotice>,
+    pub three_dimensional_effect: Option<ThreeDimensionalEffect>,
+    // Other
+    pub prompt_language: Option<PromptLanguage>,
+    pub auto_power_off: Option<AutoPowerOff>,
+    pub hearing_protect: Option<HearingProtect>,
+}
+
+impl DeviceConfigSnapshot {
+    pub const VERSION: u32 = 1;
+
+    pub fn from_state(model: KnownProductCodes, state: &SoundcoreDeviceState) -> Self {
+        Self {
+            version: Self::VERSION,
+            model,
+            sound_mode: state
+                .feature_set
+                .sound_mode_features
+                .as_ref()
+                .map(|_| state.sound_mode),
+            eq_configuration: state
+                .feature_set
+                .equalizer_features
+                .as_ref()
+                .map(|_| state.eq_configuration.to_owned()),
+            sound_mode_cycle: state.sound_mode_cycle,
+            button_model: state.button_model.to_owned(),
+            touch_tone: state.touch_tone,
+            wear_detection: state.wear_detection,
+            support_two_cnn: state.support_two_cnn,
+            in_ear_beep: state.in_ear_beep,
+            ambient_sound_notice: state.ambient_sound_notice,
+            power_on_battery_notice: state.power_on_battery_notice,
+            three_dimensional_effect: state.three_dimensional_effect,
+            prompt_language: state.prompt_language,
+            auto_power_off: state.auto_power_off,
+            hearing_protect: state.hearing_protect,
+        }
+    }
+
+    pub fn to_json(&self) -> SoundcoreLibResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| invalid_snapshot(e.to_string()))
+    }
+
+    /// Parses a snapshot, rejecting ones written by a newer version of the library
+    pub fn from_json(json: &str) -> SoundcoreLibResult<Self> {
+        let snapshot: Self =
+            serde_json::from_str(json).map_err(|e| invalid_snapshot(e.to_string()))?;
+        if snapshot.version > Self::VERSION {
+            return Err(invalid_snapshot(format!(
+                "Unsupported version {}, expected at most {}",
+                snapshot.version,
+                Self::VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Snapshots apply to their own model and to models declaring the same features
+    pub fn check_compatible(&self, model: KnownProductCodes) -> SoundcoreLibResult<()> {
+        let compatible = self.model == model
+            || model_features(self.model)
+                .zip(model_features(model))
+                .is_some_and(|(snapshot_features, features)| snapshot_features == features);
+        if !compatible {
+            return Err(invalid_snapshot(format!(
+                "Taken from {:?}, which is incompatible with {:?}",
+                self.model, model
+            )));
+        }
+        Ok(())
+    }
+
+    /// The fields that are part of the snapshot but can't be written to a device yet
+    pub(crate) fn read_only_fields(&self, state: &SoundcoreDeviceState) -> Vec<(StateField, bool)> {
+        [
+            (
+                StateField::ButtonModel,
+                self.button_model.is_some(),
+                state.button_model.is_some(),
+            ),
+            (
+                StateField::SupportTwoCnn,
+                self.support_two_cnn.is_some(),
+                state.support_two_cnn.is_some(),
+            ),
+            (
+                StateField::TouchTone,
+                self.touch_tone.is_some(),
+                state.touch_tone.is_some(),
+            ),
+            (
+                StateField::WearDetection,
+                self.wear_detection.is_some(),
+                state.wear_detection.is_some(),
+            ),
+            (
+                StateField::InEarBeep,
+                self.in_ear_beep.is_some(),
+                state.in_ear_beep.is_some(),
+            ),
+            (
+                StateField::AmbientSoundNotice,
+                self.ambient_sound_notice.is_some(),
+                state.ambient_sound_notice.is_some(),
+            ),
+            (
+                StateField::PowerOnBatteryNotice,
+                self.power_on_battery_notice.is_some(),
+                state.power_on_battery_notice.is_some(),
+            ),
+            (
+                StateField::AutoPowerOff,
+                self.auto_power_off.is_some(),
+                state.auto_power_off.is_some(),
+            ),
+            (
+                StateField::HearingProtect,
+                self.hearing_protect.is_some(),
+                state.hearing_protect.is_some(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, in_snapshot, _)| *in_snapshot)
+        .map(|(field, _, supported)| (field, supported))
+        .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum ConfigSkipReason {
+    /// The device doesn't support the field
+    Unsupported,
+    /// The device supports the field, but there is no command to change it yet
+    NotWritable,
+    /// The device rejected the value, e.g. an EQ with a different number of bands
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SkippedConfigField {
+    pub field: StateField,
+    pub reason: ConfigSkipReason,
+}
+
+/// The outcome of applying a snapshot to a device
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct ConfigApplyReport {
+    pub applied: Vec<StateField>,
+    pub skipped: Vec<SkippedConfigField>,
+}
+
+impl ConfigApplyReport {
+    pub(crate) fn skip(&mut self, field: StateField, reason: ConfigSkipReason) {
+        self.skipped.push(SkippedConfigField { field, reason });
+    }
+
+    /// Unsupported features and invalid values are skipped,
+    /// any other error, e.g. a failed write, is returned
+    pub(crate) fn record(
+        &mut self,
+        field: StateField,
+        result: SoundcoreLibResult<()>,
+    ) -> SoundcoreLibResult<()> {
+        match result {
+            Ok(()) => self.applied.push(field),
+            Err(SoundcoreLibError::FeatureNotSupported(_)) => {
+                self.skip(field, ConfigSkipReason::Unsupported)
+            }
+            Err(
+                SoundcoreLibError::InvalidEQ(_)
+                | SoundcoreLibError::InvalidArguments
+                | SoundcoreLibError::InvalidDeviceName(_),
+            ) => self.skip(field, ConfigSkipReason::Invalid),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+}
+
+fn invalid_snapshot(reason: String) -> SoundcoreLibError {
+    SoundcoreLibError::InvalidConfigSnapshot(reason)
+}
+
+#[cfg(test)]
+mod config_snapshot_tests {
+    use crate::devices::a3951_features;
+
+    use super::*;
+
+    fn state() -> SoundcoreDeviceState {
+        SoundcoreDeviceState {
+            feature_set: a3951_features(),
+            touch_tone: Some(TouchTone(true)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_round_trip_json() {
+        let snapshot = DeviceConfigSnapshot::from_state(KnownProductCodes::A3951, &state());
+        assert!(snapshot.sound_mode.is_some());
+        assert!(snapshot.eq_configuration.is_some());
+        assert_eq!(snapshot.prompt_language, None);
+
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(DeviceConfigSnapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn should_reject_newer_versions() {
+        let snapshot = DeviceConfigSnapshot {
+            version: DeviceConfigSnapshot::VERSION + 1,
+            ..DeviceConfigSnapshot::from_state(KnownProductCodes::A3951, &state())
+        };
+        assert!(matches!(
+            DeviceConfigSnapshot::from_json(&snapshot.to_json().unwrap()),
+            Err(SoundcoreLibError::InvalidConfigSnapshot(_))
+        ));
+        assert!(DeviceConfigSnapshot::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn should_list_read_only_fields() {
+        let snapshot = DeviceConfigSnapshot::from_state(KnownProductCodes::A3951, &state());
+        assert_eq!(
+            snapshot.read_only_fields(&state()),
+            vec![(StateField::TouchTone, true)]
+        );
+        assert_eq!(
+            snapshot.read_only_fields(&SoundcoreDeviceState::default()),
+            vec![(StateField::TouchTone, false)]
+        );
+    }
+
+    #[test]
+    fn should_only_apply_to_compatible_models() {
+        let snapshot = DeviceConfigSnapshot::from_state(KnownProductCodes::A3027, &state());
+        assert!(snapshot.check_compatible(KnownProductCodes::A3027).is_ok());
+        // Both models declare the same features
+        assert!(snapshot.check_compatible(KnownProductCodes::A3028).is_ok());
+        assert!(matches!(
+            snapshot.check_compatible(KnownProductCodes::A3951),
+            Err(SoundcoreLibError::InvalidConfigSnapshot(_))
+        ));
+        assert!(snapshot.check_compatible(KnownProductCodes::A3947).is_err());
+    }
+
+    #[test]
+    fn should_skip_invalid_values() {
+        let mut report = ConfigApplyReport::default();
+        report
+            .record(
+                StateField::EqConfiguration,
+                Err(SoundcoreLibError::InvalidEQ("10 bands".to_string())),
+            )
+            .unwrap();
+        assert_eq!(
+            report.skipped,
+            vec![SkippedConfigField {
+                field: StateField::EqConfiguration,
+                reason: ConfigSkipReason::Invalid,
+            }]
+        );
+        assert!(report
+            .record(StateField::SoundMode, Err(SoundcoreLibError::SendError))
+            .is_err());
+    }
+}
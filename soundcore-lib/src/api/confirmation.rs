@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+
+/// An action which can't be undone from the library
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, Display)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum DestructiveAction {
+    PowerOff,
+    FactoryReset,
+}
+
+/// Issued by SoundcoreBLEDevice::confirmation_token and consumed by the action it was issued for.
+/// This guards against accidental calls, it isn't a security measure.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct ConfirmationToken {
+    pub action: DestructiveAction,
+    pub id: u32,
+}
+
+/// The token a device issued last, only that one is accepted
+#[derive(Debug, Default)]
+pub(crate) struct PendingConfirmation {
+    token: Option<ConfirmationToken>,
+    next_id: u32,
+}
+
+impl PendingConfirmation {
+    /// Replaces any previously issued token
+    pub(crate) fn issue(&mut self, action: DestructiveAction) -> ConfirmationToken {
+        let token = ConfirmationToken {
+            action,
+            id: self.next_id,
+        };
+        self.next_id = self.next_id.wrapping_add(1);
+        self.token = Some(token);
+        token
+    }
+
+    pub(crate) fn consume(
+        &mut self,
+        action: DestructiveAction,
+        token: ConfirmationToken,
+    ) -> SoundcoreLibResult<()> {
+        if token.action != action || self.token != Some(token) {
+            return Err(SoundcoreLibError::InvalidConfirmationToken);
+        }
+        self.token = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod confirmation_tests {
+    use super::*;
+
+    #[test]
+    fn should_consume_a_token_once() {
+        let mut pending = PendingConfirmation::default();
+        let token = pending.issue(DestructiveAction::PowerOff);
+        assert!(pending.consume(DestructiveAction::PowerOff, token).is_ok());
+        assert!(pending.consume(DestructiveAction::PowerOff, token).is_err());
+    }
+
+    #[test]
+    fn should_reject_a_token_for_another_action() {
+        let mut pending = PendingConfirmation::default();
+        let token = pending.issue(DestructiveAction::PowerOff);
+        assert!(pending
+            .consume(DestructiveAction::FactoryReset, token)
+            .is_err());
+        // A mismatched action doesn't consume the token
+        assert!(pending.consume(DestructiveAction::PowerOff, token).is_ok());
+    }
+
+    #[test]
+    fn should_reject_a_replaced_token() {
+        let mut pending = PendingConfirmation::default();
+        let replaced = pending.issue(DestructiveAction::FactoryReset);
+        let token = pending.issue(DestructiveAction::FactoryReset);
+        assert!(pending
+            .consume(DestructiveAction::FactoryReset, replaced)
+            .is_err());
+        assert!(pending
+            .consume(DestructiveAction::FactoryReset, token)
+            .is_ok());
+    }
+}
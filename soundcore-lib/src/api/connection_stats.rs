@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Counters of a device's connection, kept across reconnects by the `DeviceManager`.
+/// Packets are counted once the initial state has been received.
+#[derive(Debug, Default)]
+pub struct ConnectionStats {
+    packets_received: AtomicU64,
+    parse_failures: AtomicU64,
+    checksum_mismatches: AtomicU64,
+    reconnects: AtomicU64,
+}
+
+impl ConnectionStats {
+    pub(crate) fn record_packet(&self) {
+        self.packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_parse_failure(&self) {
+        self.parse_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_checksum_mismatch(&self) {
+        self.checksum_mismatches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ConnectionStatsSnapshot {
+        ConnectionStatsSnapshot {
+            packets_received: self.packets_received.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            checksum_mismatches: self.checksum_mismatches.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatsSnapshot {
+    pub packets_received: u64,
+    /// Packets with a valid checksum which couldn't be parsed
+    pub parse_failures: u64,
+    pub checksum_mismatches: u64,
+    pub reconnects: u64,
+}
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::api::EqualizerFeatures;
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::models::{EQConfiguration, MonoEQ, StereoEQ};
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash)]
+#[typeshare]
+#[serde(rename_all = "camelCase", tag = "type", content = "value")]
+pub enum EQPresetCurve {
+    Mono(MonoEQ),
+    Stereo(StereoEQ),
+}
+
+/// A user-defined custom EQ curve
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash)]
+#[typeshare]
+#[serde(rename_all = "camelCase")]
+pub struct EQPreset {
+    pub name: String,
+    pub curve: EQPresetCurve,
+}
+
+impl EQPreset {
+    /// Checks that the curve can be applied to a device with the given features
+    pub fn validate(&self, features: &EqualizerFeatures) -> SoundcoreLibResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(invalid_preset("The name can't be empty".to_string()));
+        }
+
+        let channels = match &self.curve {
+            EQPresetCurve::Mono(eq) => vec![eq],
+            EQPresetCurve::Stereo(eq) => {
+                if eq.left != eq.right && features.channels < 2 {
+                    return Err(invalid_preset(format!(
+                        "{} has separate channel curves but the device has a single channel",
+                        self.name
+                    )));
+                }
+                vec![&eq.left, &eq.right]
+            }
+        };
+
+        // Values outside of this range can't be converted to DRC values
+        let min = (MonoEQ::MIN_FLOAT * 10.0) as u8;
+        let max = (MonoEQ::MAX_FLOAT * 10.0) as u8;
+        for eq in channels {
+            if eq.values.len() != features.bands as usize {
+                return Err(invalid_preset(format!(
+                    "{} has {} bands, expected {}",
+                    self.name,
+                    eq.values.len(),
+                    features.bands
+                )));
+            }
+            if eq.values.iter().any(|value| !(min..=max).contains(value)) {
+                return Err(invalid_preset(format!(
+                    "{} has values outside of the {}..={} range",
+                    self.name, min, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn eq_configuration(&self) -> EQConfiguration {
+        match &self.curve {
+            EQPresetCurve::Mono(eq) => EQConfiguration::mono_custom(eq.to_owned()),
+            EQPresetCurve::Stereo(eq) => {
+                EQConfiguration::stereo_custom(eq.left.to_owned(), eq.right.to_owned())
+            }
+        }
+    }
+}
+
+/// A collection of uniquely named presets, kept in insertion order
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash, Default)]
+#[typeshare]
+#[serde(rename_all = "camelCase")]
+pub struct EQPresetLibrary {
+    presets: Vec<EQPreset>,
+}
+
+impl EQPresetLibrary {
+    pub fn presets(&self) -> &[EQPreset] {
+        &self.presets
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EQPreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Adds the preset, replacing any preset with the same name
+    pub fn save(
+        &mut self,
+        preset: EQPreset,
+        features: &EqualizerFeatures,
+    ) -> SoundcoreLibResult<()> {
+        preset.validate(features)?;
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        Ok(())
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> SoundcoreLibResult<()> {
+        if new_name.trim().is_empty() {
+            return Err(invalid_preset("The name can't be empty".to_string()));
+        }
+        if name != new_name && self.get(new_name).is_some() {
+            return Err(invalid_preset(format!("{} already exists", new_name)));
+        }
+        let preset = self
+            .presets
+            .iter_mut()
+            .find(|preset| preset.name == name)
+            .ok_or(SoundcoreLibError::EQPresetNotFound(name.to_string()))?;
+        preset.name = new_name.to_string();
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> SoundcoreLibResult<EQPreset> {
+        let idx = self
+            .presets
+            .iter()
+            .position(|preset| preset.name == name)
+            .ok_or(SoundcoreLibError::EQPresetNotFound(name.to_string()))?;
+        Ok(self.presets.remove(idx))
+    }
+
+    /// Saves all the presets, nothing is imported if any of them is invalid
+    pub fn import(
+        &mut self,
+        presets: Vec<EQPreset>,
+        features: &EqualizerFeatures,
+    ) -> SoundcoreLibResult<()> {
+        presets
+            .iter()
+            .try_for_each(|preset| preset.validate(features))?;
+        presets
+            .into_iter()
+            .try_for_each(|preset| self.save(preset, features))
+    }
+
+    pub fn export(&self) -> Vec<EQPreset> {
+        self.presets.to_owned()
+    }
+
+    /// Deserialized libraries don't go through `save`, so their names have to be checked
+    pub fn check_unique_names(&self) -> SoundcoreLibResult<()> {
+        let mut names = HashSet::new();
+        match self
+            .presets
+            .iter()
+            .find(|preset| !names.insert(preset.name.as_str()))
+        {
+            Some(preset) => Err(invalid_preset(format!("{} already exists", preset.name))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn invalid_preset(reason: String) -> SoundcoreLibError {
+    SoundcoreLibError::InvalidEQPreset(reason)
+}
+
+#[cfg(test)]
+mod eq_presets_tests {
+    use crate::models::EQProfile;
+
+    use super::*;
+
+    fn features(channels: u8) -> EqualizerFeatures {
+        EqualizerFeatures {
+            bands: 8,
+            channels,
+            has_bass_up: false,
+        }
+    }
+
+    fn mono_preset(name: &str) -> EQPreset {
+        EQPreset {
+            name: name.to_string(),
+            curve: EQPresetCurve::Mono(EQProfile::Acoustic.eq()),
+        }
+    }
+
+    fn stereo_preset(name: &str) -> EQPreset {
+        EQPreset {
+            name: name.to_string(),
+            curve: EQPresetCurve::Stereo(StereoEQ {
+                left: EQProfile::Acoustic.eq(),
+                right: EQProfile::Classical.eq(),
+            }),
+        }
+    }
+
+    #[test]
+    fn should_save_and_replace_presets() {
+        let mut library = EQPresetLibrary::default();
+        library.save(mono_preset("Commute"), &features(1)).unwrap();
+        library.save(mono_preset("Desk"), &features(1)).unwrap();
+
+        let replacement = EQPreset {
+            name: "Commute".to_string(),
+            curve: EQPresetCurve::Mono(EQProfile::Podcast.eq()),
+        };
+        library.save(replacement.clone(), &features(1)).unwrap();
+
+        assert_eq!(library.presets().len(), 2);
+        assert_eq!(library.get("Commute"), Some(&replacement));
+        assert_eq!(library.presets()[1].name, "Desk");
+    }
+
+    #[test]
+    fn should_validate_against_features() {
+        let mut library = EQPresetLibrary::default();
+        assert!(library.save(stereo_preset("Left"), &features(1)).is_err());
+        assert!(library.save(stereo_preset("Left"), &features(2)).is_ok());
+
+        let short = EQPreset {
+            name: "Short".to_string(),
+            curve: EQPresetCurve::Mono(MonoEQ::from_vec(vec![120; 6])),
+        };
+        assert!(short.validate(&features(2)).is_err());
+
+        let out_of_range = EQPreset {
+            name: "Loud".to_string(),
+            curve: EQPresetCurve::Mono(MonoEQ::from_vec(vec![20; 8])),
+        };
+        assert!(out_of_range.validate(&features(2)).is_err());
+        assert!(mono_preset(" ").validate(&features(2)).is_err());
+    }
+
+    #[test]
+    fn should_rename_and_delete_presets() {
+        let mut library = EQPresetLibrary::default();
+        library.save(mono_preset("Commute"), &features(1)).unwrap();
+        library.save(mono_preset("Desk"), &features(1)).unwrap();
+
+        assert!(library.rename("Commute", "Desk").is_err());
+        assert!(library.rename("Missing", "Gym").is_err());
+        library.rename("Commute", "Train").unwrap();
+        assert!(library.get("Train").is_some());
+        assert!(library.get("Commute").is_none());
+
+        assert_eq!(library.delete("Desk").unwrap().name, "Desk");
+        assert!(library.delete("Desk").is_err());
+        assert_eq!(library.presets().len(), 1);
+    }
+
+    #[test]
+    fn should_import_all_or_nothing() {
+        let mut library = EQPresetLibrary::default();
+        assert!(library
+            .import(
+                vec![mono_preset("Commute"), stereo_preset("Left")],
+                &features(1)
+            )
+            .is_err());
+        assert!(library.presets().is_empty());
+
+        library
+            .import(
+                vec![mono_preset("Commute"), stereo_preset("Left")],
+                &features(2),
+            )
+            .unwrap();
+        assert_eq!(
+            library.export(),
+            vec![mono_preset("Commute"), stereo_preset("Left")]
+        );
+    }
+
+    #[test]
+    fn should_build_eq_configuration() {
+        assert_eq!(
+            mono_preset("Commute").eq_configuration(),
+            EQConfiguration::mono_custom(EQProfile::Acoustic.eq())
+        );
+        assert!(stereo_preset("Left").eq_configuration().is_per_channel());
+    }
+
+    #[test]
+    fn should_reject_deserialized_duplicate_names() {
+        let mut library = EQPresetLibrary::default();
+        library.save(mono_preset("Commute"), &features(1)).unwrap();
+        library.save(mono_preset("Desk"), &features(1)).unwrap();
+        assert!(library.check_unique_names().is_ok());
+
+        let json = serde_json::to_string(&library)
+            .unwrap()
+            .replace("\"Desk\"", "\"Commute\"");
+        let library: EQPresetLibrary = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            library.check_unique_names(),
+            Err(SoundcoreLibError::InvalidEQPreset(_))
+        ));
+    }
+}
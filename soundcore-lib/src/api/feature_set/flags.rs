@@ -22,4 +22,8 @@ pub enum FeatureFlags {
     POWER_ON_BATTERY_NOTICE,
     SUPPORT_TWO_CONNECTIONS,
     MULTIPLE_DEVICE_LIST,
+    FIND_DEVICE,
+    POWER_OFF,
+    FACTORY_RESET,
+    RENAME,
 }
@@ -3,9 +3,10 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
 use crate::models::{
-    ANCMode, AdaptiveANCMode, CustomizableTransparencyMode, NonCustomizableTransparencyMode,
-    SceneBasedANCMode, TransparencyMode,
+    ANCMode, ANCStrengthMode, AdaptiveANCMode, CustomizableTransparencyMode,
+    NonCustomizableTransparencyMode, SceneBasedANCMode, SoundMode, TransparencyMode,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -19,6 +20,9 @@ pub struct SoundModeFeatures {
     has_customizable_transparency: bool,
     max_custom_anc: Option<u8>,
     max_custom_transparency: Option<u8>,
+    has_auto_anc: bool,
+    /// Manual is always supported
+    anc_strength_modes: Arc<[ANCStrengthMode]>,
 }
 
 impl SoundModeFeatures {
@@ -48,9 +52,21 @@ impl SoundModeFeatures {
             max_custom_anc: has_customizable_anc.and_then(Self::determine_max_custom_anc),
             max_custom_transparency: has_customizable_transparency
                 .and_then(Self::determine_max_custom_transparency),
+            has_auto_anc: false,
+            anc_strength_modes: Arc::new([]),
         }
     }
 
+    pub fn with_auto_anc(mut self) -> Self {
+        self.has_auto_anc = true;
+        self
+    }
+
+    pub fn with_anc_strength_modes(mut self, modes: &[ANCStrengthMode]) -> Self {
+        self.anc_strength_modes = modes.into();
+        self
+    }
+
     pub fn adaptive_customizable_anc_customizable_transparency() -> SoundModeFeatures {
         SoundModeFeatures::new(
             &[
@@ -104,6 +120,32 @@ impl SoundModeFeatures {
         self.has_normal
     }
 
+    pub fn has_auto_anc(&self) -> bool {
+        self.has_auto_anc
+    }
+
+    pub fn supports_anc_strength_mode(&self, mode: ANCStrengthMode) -> bool {
+        mode == ANCStrengthMode::Manual || self.anc_strength_modes.contains(&mode)
+    }
+
+    /// Checks the ANC options of the sound mode which the model may not support
+    pub fn validate(&self, sound_mode: &SoundMode) -> SoundcoreLibResult<()> {
+        let unsupported = if sound_mode.auto_anc && !self.has_auto_anc {
+            Some("Auto ANC".to_string())
+        } else if !self.supports_anc_strength_mode(sound_mode.anc_strength_mode) {
+            Some(format!(
+                "ANC strength mode {}",
+                sound_mode.anc_strength_mode
+            ))
+        } else {
+            None
+        };
+        match unsupported {
+            Some(feature) => Err(SoundcoreLibError::FeatureNotSupported(feature)),
+            None => Ok(()),
+        }
+    }
+
     pub fn allowed_anc_modes(&self) -> &[ANCMode] {
         &self.allowed_anc_modes
     }
@@ -259,4 +301,31 @@ mod test {
             None
         );
     }
+
+    #[test]
+    fn should_validate_anc_options() {
+        let features = SoundModeFeatures::adaptive_customizable_anc_customizable_transparency();
+        let sound_mode = SoundMode {
+            auto_anc: true,
+            ..Default::default()
+        };
+        assert!(features.validate(&SoundMode::default()).is_ok());
+        assert!(features.validate(&sound_mode).is_err());
+        assert!(features
+            .clone()
+            .with_auto_anc()
+            .validate(&sound_mode)
+            .is_ok());
+
+        let sound_mode = SoundMode {
+            anc_strength_mode: ANCStrengthMode::EarCanal,
+            ..Default::default()
+        };
+        let features = features.with_anc_strength_modes(&[ANCStrengthMode::Environment]);
+        assert!(features.validate(&sound_mode).is_err());
+        assert!(features
+            .with_anc_strength_modes(&[ANCStrengthMode::EarCanal])
+            .validate(&sound_mode)
+            .is_ok());
+    }
 }
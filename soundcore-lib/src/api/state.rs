@@ -1,3 +1,5 @@
 mod soundcore_device_state;
+mod state_change;
 
 pub use soundcore_device_state::*;
+pub use state_change::*;
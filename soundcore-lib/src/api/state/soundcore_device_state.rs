@@ -2,15 +2,16 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::models::{
-    AmbientSoundNotice, AutoPowerOff, BassUp, DeviceColor, DeviceFirmware, HearingProtect,
-    InEarBeep, PowerOnBatteryNotice, PromptLanguage, SupportTwoCnn, ThreeDimensionalEffect,
-    TouchTone, LDAC,
+    AmbientSoundNotice, AutoPowerOff, BassUp, DeviceColor, DeviceFirmware, GameMode,
+    HearingProtect, InEarBeep, PowerOnBatteryNotice, PromptLanguage, SoundModeCycle, SupportTwoCnn,
+    ThreeDimensionalEffect, TouchTone, LDAC,
 };
 use crate::{
-    api::DeviceFeatureSet,
+    api::{DeviceFeatureSet, FeatureFlags},
     models::{
-        AgeRange, Battery, ButtonModel, EQConfiguration, HearID, SerialNumber, SideTone, SoundMode,
-        TwsStatus, WearDetection,
+        AgeRange, Battery, ButtonModel, EQConfiguration, FrequencyResponsePoint, HearID,
+        SerialNumber, SideTone, SingleBattery, SoundMode, StereoEQConfiguration, TwsStatus,
+        WearDetection, EQ_BAND_FREQUENCIES,
     },
 };
 
@@ -21,6 +22,9 @@ use crate::{
 pub struct SoundcoreDeviceState {
     pub feature_set: DeviceFeatureSet,
     pub battery: Battery,
+    /// Only reported by models whose state packets carry the case's level and charging state,
+    /// none of the decoded ones do yet
+    pub case_battery: Option<SingleBattery>,
     pub sound_mode: SoundMode,
     pub eq_configuration: EQConfiguration,
     pub serial: Option<SerialNumber>,
@@ -45,8 +49,75 @@ pub struct SoundcoreDeviceState {
     pub power_on_battery_notice: Option<PowerOnBatteryNotice>,
     // Other
     pub three_dimensional_effect: Option<ThreeDimensionalEffect>,
+    pub game_mode: Option<GameMode>,
     pub device_color: Option<DeviceColor>,
     pub ldac: Option<LDAC>,
     pub prompt_language: Option<PromptLanguage>,
     pub hearing_protect: Option<HearingProtect>,
+    pub sound_mode_cycle: Option<SoundModeCycle>,
+}
+
+/// The approximate response of each channel's EQ curve
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct EQFrequencyResponse {
+    pub left: Vec<FrequencyResponsePoint>,
+    pub right: Vec<FrequencyResponsePoint>,
+}
+
+impl SoundcoreDeviceState {
+    /// The response of the current EQ at `points` frequencies, see MonoEQ::frequency_response.
+    /// Returns None if the device has no equalizer.
+    pub fn eq_frequency_response(&self, points: usize) -> Option<EQFrequencyResponse> {
+        self.feature_set.equalizer_features?;
+        let drc = self.feature_set.flags.contains(&FeatureFlags::DRC);
+        let config: StereoEQConfiguration = self.eq_configuration.to_owned().into();
+        Some(EQFrequencyResponse {
+            left: config
+                .eq
+                .left
+                .frequency_response(&EQ_BAND_FREQUENCIES, drc, points),
+            right: config
+                .eq
+                .right
+                .frequency_response(&EQ_BAND_FREQUENCIES, drc, points),
+        })
+    }
+}
+
+#[cfg(test)]
+mod soundcore_device_state_tests {
+    use crate::api::EqualizerFeatures;
+    use crate::models::{EQProfile, MonoEQ};
+
+    use super::*;
+
+    #[test]
+    fn should_compute_eq_frequency_response() {
+        assert_eq!(
+            SoundcoreDeviceState::default().eq_frequency_response(16),
+            None
+        );
+
+        let state = SoundcoreDeviceState {
+            feature_set: DeviceFeatureSet {
+                equalizer_features: Some(EqualizerFeatures {
+                    bands: 8,
+                    channels: 2,
+                    has_bass_up: false,
+                }),
+                ..Default::default()
+            },
+            eq_configuration: EQConfiguration::stereo_custom(
+                EQProfile::BassBooster.eq(),
+                MonoEQ::from_gains_db(&[0.0; 8]),
+            ),
+            ..Default::default()
+        };
+        let response = state.eq_frequency_response(16).unwrap();
+        assert_eq!(response.left.len(), 16);
+        assert!(response.left.iter().any(|point| point.gain > 3.0));
+        assert!(response.right.iter().all(|point| point.gain.abs() < 0.01));
+    }
 }
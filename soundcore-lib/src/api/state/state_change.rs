@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use typeshare::typeshare;
+
+use crate::api::SoundcoreDeviceState;
+
+/// A top-level field of the SoundcoreDeviceState
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Hash,
+    Display,
+    EnumIter,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+#[typeshare]
+pub enum StateField {
+    FeatureSet,
+    Battery,
+    CaseBattery,
+    SoundMode,
+    EqConfiguration,
+    Serial,
+    Fw,
+    ButtonModel,
+    HostDevice,
+    SideTone,
+    AgeRange,
+    HearidEqPreset,
+    HearId,
+    HearIdHasData,
+    TouchTone,
+    TwsStatus,
+    WearDetection,
+    BassUp,
+    AutoPowerOff,
+    SupportTwoCnn,
+    InEarBeep,
+    AmbientSoundNotice,
+    PowerOnBatteryNotice,
+    ThreeDimensionalEffect,
+    GameMode,
+    DeviceColor,
+    #[serde(rename = "ldac")]
+    LDAC,
+    PromptLanguage,
+    HearingProtect,
+    SoundModeCycle,
+}
+
+/// The originator of a state change
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, Display)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum ChangeSource {
+    /// The change was made by a command sent from this library
+    Local,
+    /// The change was reported by the device, e.g. a button press or a battery update
+    Device,
+}
+
+/// A state update along with the fields that changed and its originator
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct StateChange {
+    pub source: ChangeSource,
+    pub changed: Vec<StateField>,
+    pub state: SoundcoreDeviceState,
+}
+
+impl StateChange {
+    /// Diffs the two states, returns None if nothing changed
+    pub fn new(
+        source: ChangeSource,
+        old_state: &SoundcoreDeviceState,
+        new_state: &SoundcoreDeviceState,
+    ) -> Option<Self> {
+        let changed = old_state.changed_fields(new_state);
+        if changed.is_empty() {
+            return None;
+        }
+        Some(Self {
+            source,
+            changed,
+            state: new_state.to_owned(),
+        })
+    }
+
+    pub fn contains(&self, field: StateField) -> bool {
+        self.changed.contains(&field)
+    }
+}
+
+impl SoundcoreDeviceState {
+    /// Returns the fields that differ between this state and `other`, in declaration order
+    pub fn changed_fields(&self, other: &SoundcoreDeviceState) -> Vec<StateField> {
+        [
+            (
+                StateField::FeatureSet,
+                self.feature_set != other.feature_set,
+            ),
+            (StateField::Battery, self.battery != other.battery),
+            (
+                StateField::CaseBattery,
+                self.case_battery != other.case_battery,
+            ),
+            (StateField::SoundMode, self.sound_mode != other.sound_mode),
+            (
+                StateField::EqConfiguration,
+                self.eq_configuration != other.eq_configuration,
+            ),
+            (StateField::Serial, self.serial != other.serial),
+            (StateField::Fw, self.fw != other.fw),
+            (
+                StateField::ButtonModel,
+                self.button_model != other.button_model,
+            ),
+            (
+                StateField::HostDevice,
+                self.host_device != other.host_device,
+            ),
+            (StateField::SideTone, self.side_tone != other.side_tone),
+            (StateField::AgeRange, self.age_range != other.age_range),
+            (
+                StateField::HearidEqPreset,
+                self.hearid_eq_preset != other.hearid_eq_preset,
+            ),
+            (StateField::HearId, self.hear_id != other.hear_id),
+            (
+                StateField::HearIdHasData,
+                self.hear_id_has_data != other.hear_id_has_data,
+            ),
+            (StateField::TouchTone, self.touch_tone != other.touch_tone),
+            (StateField::TwsStatus, self.tws_status != other.tws_status),
+            (
+                StateField::WearDetection,
+                self.wear_detection != other.wear_detection,
+            ),
+            (StateField::BassUp, self.bass_up != other.bass_up),
+            (
+                StateField::AutoPowerOff,
+                self.auto_power_off != other.auto_power_off,
+            ),
+            (
+                StateField::SupportTwoCnn,
+                self.support_two_cnn != other.support_two_cnn,
+            ),
+            (StateField::InEarBeep, self.in_ear_beep != other.in_ear_beep),
+            (
+                StateField::AmbientSoundNotice,
+                self.ambient_sound_notice != other.ambient_sound_notice,
+            ),
+            (
+                StateField::PowerOnBatteryNotice,
+                self.power_on_battery_notice != other.power_on_battery_notice,
+            ),
+            (
+                StateField::ThreeDimensionalEffect,
+                self.three_dimensional_effect != other.three_dimensional_effect,
+            ),
+            (StateField::GameMode, self.game_mode != other.game_mode),
+            (
+                StateField::DeviceColor,
+                self.device_color != other.device_color,
+            ),
+            (StateField::LDAC, self.ldac != other.ldac),
+            (
+                StateField::PromptLanguage,
+                self.prompt_language != other.prompt_language,
+            ),
+            (
+                StateField::HearingProtect,
+                self.hearing_protect != other.hearing_protect,
+            ),
+            (
+                StateField::SoundModeCycle,
+                self.sound_mode_cycle != other.sound_mode_cycle,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod state_change_tests {
+    use crate::models::{Battery, CurrentSoundMode, SingleBattery, SoundMode, TouchTone};
+
+    use super::*;
+
+    #[test]
+    fn should_have_no_changes_for_equal_states() {
+        let state = SoundcoreDeviceState::default();
+        assert!(state.changed_fields(&state.clone()).is_empty());
+        assert_eq!(
+            StateChange::new(ChangeSource::Device, &state, &state.clone()),
+            None
+        );
+    }
+
+    #[test]
+    fn should_report_changed_fields_in_order() {
+        let old_state = SoundcoreDeviceState::default();
+        let new_state = SoundcoreDeviceState {
+            touch_tone: Some(TouchTone(true)),
+            sound_mode: SoundMode {
+                current: CurrentSoundMode::ANC,
+                ..Default::default()
+            },
+            battery: Battery::Single(SingleBattery {
+                charging: true,
+                level: 3,
+            }),
+            ..old_state.clone()
+        };
+
+        assert_eq!(
+            old_state.changed_fields(&new_state),
+            vec![
+                StateField::Battery,
+                StateField::SoundMode,
+                StateField::TouchTone
+            ]
+        );
+    }
+
+    #[test]
+    fn should_create_state_change_with_source() {
+        let old_state = SoundcoreDeviceState::default();
+        let new_state = SoundcoreDeviceState {
+            host_device: Some(1),
+            ..old_state.clone()
+        };
+
+        let change = StateChange::new(ChangeSource::Local, &old_state, &new_state).unwrap();
+        assert_eq!(change.source, ChangeSource::Local);
+        assert!(change.contains(StateField::HostDevice));
+        assert!(!change.contains(StateField::Battery));
+        assert_eq!(change.state, new_state);
+    }
+}
@@ -1,20 +1,31 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use log::{debug, error, trace};
-use tokio::sync::{mpsc, watch, Mutex};
+use log::{debug, error, trace, warn};
+use nom::error::VerboseError;
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 
 use manager_fut::ManagerFuture;
 
-use crate::api::SoundcoreDeviceState;
+use crate::api::{
+    ChangeSource, ConfigApplyReport, ConfigSkipReason, ConfirmationToken, ConnectionStats,
+    ConnectionStatsSnapshot, DestructiveAction, DeviceConfigSnapshot, FeatureFlags,
+    PendingConfirmation, SoundcoreDeviceState, StateChange, StateField,
+};
 use crate::ble::{BLEConnection, WriteType};
+use crate::devices::{firmware_quirks, FirmwareQuirk};
 use crate::error::{SoundcoreLibError, SoundcoreLibResult};
-use crate::models::{BassUp, EQConfiguration, EQProfile, SoundMode};
+use crate::models::{
+    BassUp, EQConfiguration, EQProfile, EarbudSide, PromptLanguage, SoundMode, SoundModeCycle,
+    ThreeDimensionalEffect,
+};
 use crate::packets::{
-    BassUpCommandBuilder, EqCommandBuilder, RequestPacketBuilder, RequestPacketKind,
-    ResponsePacket, SoundModeCommandBuilder, StateTransformationPacket,
+    BassUpCommandBuilder, EqCommandBuilder, FactoryResetCommandBuilder, PowerOffCommandBuilder,
+    PromptLanguageCommandBuilder, RenameCommandBuilder, RequestPacketBuilder, RequestPacketKind,
+    ResponsePacket, RingCommandBuilder, SoundModeCommandBuilder, SoundModeCycleCommandBuilder,
+    StateTransformationPacket, ThreeDimensionalEffectCommandBuilder,
 };
-use crate::parsers::TaggedData;
+use crate::parsers::{parse_and_check_checksum, TaggedData};
 use crate::types::KnownProductCodes;
 
 pub struct SoundcoreBLEDevice<C, F>
@@ -24,8 +35,12 @@ where
 {
     connection: Arc<C>,
     state_channel: Arc<Mutex<watch::Sender<SoundcoreDeviceState>>>,
+    state_changes: broadcast::Sender<StateChange>,
     _state_channel_handle: F::JoinHandle,
     model: KnownProductCodes,
+    firmware_quirks: Arc<[FirmwareQuirk]>,
+    stats: Arc<ConnectionStats>,
+    pending_confirmation: Mutex<PendingConfirmation>,
 }
 
 impl<C, F> SoundcoreBLEDevice<C, F>
@@ -33,7 +48,17 @@ where
     C: BLEConnection,
     F: ManagerFuture,
 {
+    const STATE_CHANGES_CAPACITY: usize = 255;
+
     pub async fn new(connection: Arc<C>) -> SoundcoreLibResult<Self> {
+        Self::with_stats(connection, Arc::default()).await
+    }
+
+    /// Records the packets of this connection in `stats`, e.g. the counters of a previous connection
+    pub async fn with_stats(
+        connection: Arc<C>,
+        stats: Arc<ConnectionStats>,
+    ) -> SoundcoreLibResult<Self> {
         let mut byte_channel = connection.byte_channel().await?;
         let initial_state = Self::init_state(&connection, &mut byte_channel).await?;
         debug!(
@@ -41,20 +66,43 @@ where
             initial_state,
             connection.descriptor()
         );
-        let state_sender = Arc::new(Mutex::new(watch::channel(initial_state.data.clone()).0));
-        let packet_handler = Self::spawn_packet_handler(state_sender.to_owned(), byte_channel);
 
-        let model = if let Some(sn) = initial_state.data.serial {
+        let model = if let Some(sn) = &initial_state.data.serial {
             sn.to_model().unwrap_or(initial_state.tag)
         } else {
             initial_state.tag
         };
 
+        let quirks: Arc<[FirmwareQuirk]> = match &initial_state.data.fw {
+            Some(fw) => firmware_quirks(model, fw).into(),
+            None => Arc::new([]),
+        };
+        if quirks.contains(&FirmwareQuirk::TwsFirmwareMismatch) {
+            warn!(
+                "Firmware mismatch between the earbuds of {:?}: {:?}",
+                connection.descriptor(),
+                initial_state.data.fw
+            );
+        }
+
+        let state_sender = Arc::new(Mutex::new(watch::channel(initial_state.data.clone()).0));
+        let state_changes = broadcast::channel(Self::STATE_CHANGES_CAPACITY).0;
+        let packet_handler = Self::spawn_packet_handler(
+            state_sender.to_owned(),
+            state_changes.to_owned(),
+            byte_channel,
+            stats.to_owned(),
+        );
+
         Ok(Self {
             connection,
             state_channel: state_sender,
+            state_changes,
             _state_channel_handle: packet_handler,
             model,
+            firmware_quirks: quirks,
+            stats,
+            pending_confirmation: Mutex::default(),
         })
     }
 
@@ -178,7 +226,9 @@ where
 
     fn spawn_packet_handler(
         state_sender: Arc<Mutex<watch::Sender<SoundcoreDeviceState>>>,
+        state_changes: broadcast::Sender<StateChange>,
         mut byte_channel: mpsc::Receiver<Vec<u8>>,
+        stats: Arc<ConnectionStats>,
     ) -> F::JoinHandle {
         F::spawn(async move {
             while let Some(bytes) = byte_channel.recv().await {
@@ -186,18 +236,29 @@ where
                 if bytes.is_empty() {
                     continue;
                 }
+                stats.record_packet();
+                let payload = match parse_and_check_checksum::<VerboseError<&[u8]>>(&bytes) {
+                    Ok((payload, _)) => payload,
+                    Err(e) => {
+                        stats.record_checksum_mismatch();
+                        warn!("Dropping packet {:?} with a bad checksum: {:?}", bytes, e);
+                        continue;
+                    }
+                };
 
-                match ResponsePacket::from_bytes(&bytes) {
+                match ResponsePacket::from_verified_bytes(payload) {
                     Ok(packet) => {
                         let state_sender = state_sender.lock().await;
-                        let old_state = state_sender.borrow();
-                        let new_state = packet.transform_state(&old_state);
-                        if new_state != *old_state {
-                            std::mem::drop(old_state);
-                            state_sender.send_replace(new_state);
-                        }
+                        let new_state = packet.transform_state(&state_sender.borrow());
+                        Self::publish_state(
+                            &state_sender,
+                            &state_changes,
+                            new_state,
+                            ChangeSource::Device,
+                        );
                     }
                     Err(e) => {
+                        stats.record_parse_failure();
                         error!("Failed to parse packet: {:?}", e);
                     }
                 }
@@ -214,12 +275,46 @@ where
         self.state_channel.lock().await.borrow().clone()
     }
 
+    /// The quirks of the firmware the device reported when connecting
+    pub fn firmware_quirks(&self) -> &[FirmwareQuirk] {
+        &self.firmware_quirks
+    }
+
+    pub fn connection_stats(&self) -> ConnectionStatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    /// Subscribes to field-level state changes, tagged with their originator.
+    /// Unlike the state channel, no event is emitted for the initial state.
+    pub fn state_changes(&self) -> broadcast::Receiver<StateChange> {
+        self.state_changes.subscribe()
+    }
+
+    /// Replaces the current state if it differs from `new_state` and emits the change
+    fn publish_state(
+        state_sender: &watch::Sender<SoundcoreDeviceState>,
+        state_changes: &broadcast::Sender<StateChange>,
+        new_state: SoundcoreDeviceState,
+        source: ChangeSource,
+    ) {
+        let change = StateChange::new(source, &state_sender.borrow(), &new_state);
+        if let Some(change) = change {
+            trace!("State change from {}: {:?}", source, change.changed);
+            state_sender.send_replace(new_state);
+            // An error only means that there are no subscribers
+            let _ = state_changes.send(change);
+        }
+    }
+
     pub async fn set_sound_mode(&self, sound_mode: SoundMode) -> SoundcoreLibResult<()> {
         // TODO: perform some validation on the sound mode/features
         // TODO: Check if https://github.com/Oppzippy/OpenSCQ30/blob/dec0ad3f2659205ff6efdb8d12ec333ba9f3a0b4/lib/src/soundcore_device/device/device_command_dispatcher.rs#L28
         // is valid for all models or device-specific
         let command = SoundModeCommandBuilder::new(sound_mode, self.model).build();
         let latest_state = self.latest_state().await;
+        if let Some(features) = &latest_state.feature_set.sound_mode_features {
+            features.validate(&sound_mode)?;
+        }
 
         if latest_state.sound_mode == sound_mode {
             return Ok(());
@@ -232,13 +327,190 @@ where
         let state_sender = self.state_channel.lock().await;
         let mut new_state = state_sender.borrow().clone();
         new_state.sound_mode = sound_mode;
-        state_sender.send_replace(new_state);
+        Self::publish_state(
+            &state_sender,
+            &self.state_changes,
+            new_state,
+            ChangeSource::Local,
+        );
+
+        Ok(())
+    }
+
+    /// Only supported by devices which report their cycle in the state
+    pub async fn set_sound_mode_cycle(&self, cycle: SoundModeCycle) -> SoundcoreLibResult<()> {
+        let latest_state = self.latest_state().await;
+        let Some(current) = latest_state.sound_mode_cycle else {
+            return Err(SoundcoreLibError::FeatureNotSupported(
+                "Sound mode cycle".to_string(),
+            ));
+        };
+        cycle.validate()?;
+        if current == cycle {
+            return Ok(());
+        }
+
+        let command = SoundModeCycleCommandBuilder::new(cycle, self.model).build();
+        self.connection
+            .write(&command, WriteType::WithoutResponse)
+            .await?;
+
+        let state_sender = self.state_channel.lock().await;
+        let mut new_state = state_sender.borrow().clone();
+        new_state.sound_mode_cycle = Some(cycle);
+        Self::publish_state(
+            &state_sender,
+            &self.state_changes,
+            new_state,
+            ChangeSource::Local,
+        );
 
         Ok(())
     }
 
+    /// Starts or stops playing a locating tone
+    pub async fn ring(&self, side: EarbudSide, enable: bool) -> SoundcoreLibResult<()> {
+        Self::check_flag(&self.latest_state().await, FeatureFlags::FIND_DEVICE)?;
+        self.connection
+            .write(
+                &RingCommandBuilder::new(self.model, side, enable).build(),
+                WriteType::WithoutResponse,
+            )
+            .await
+    }
+
+    pub async fn set_prompt_language(&self, language: PromptLanguage) -> SoundcoreLibResult<()> {
+        let latest_state = self.latest_state().await;
+        Self::check_flag(&latest_state, FeatureFlags::LANG_PROMPT)?;
+        if latest_state.prompt_language == Some(language) {
+            return Ok(());
+        }
+
+        self.connection
+            .write(
+                &PromptLanguageCommandBuilder::new(self.model, language).build(),
+                WriteType::WithoutResponse,
+            )
+            .await?;
+
+        let state_sender = self.state_channel.lock().await;
+        let mut new_state = state_sender.borrow().clone();
+        new_state.prompt_language = Some(language);
+        Self::publish_state(
+            &state_sender,
+            &self.state_changes,
+            new_state,
+            ChangeSource::Local,
+        );
+
+        Ok(())
+    }
+
+    /// Only supported by devices which report the effect in the state
+    pub async fn set_three_dimensional_effect(&self, enable: bool) -> SoundcoreLibResult<()> {
+        let latest_state = self.latest_state().await;
+        let Some(current) = latest_state.three_dimensional_effect else {
+            return Err(SoundcoreLibError::FeatureNotSupported(
+                "3D effect".to_string(),
+            ));
+        };
+        if current == ThreeDimensionalEffect(enable) {
+            return Ok(());
+        }
+
+        self.connection
+            .write(
+                &ThreeDimensionalEffectCommandBuilder::new(
+                    self.model,
+                    ThreeDimensionalEffect(enable),
+                )
+                .build(),
+                WriteType::WithoutResponse,
+            )
+            .await?;
+
+        let state_sender = self.state_channel.lock().await;
+        let mut new_state = state_sender.borrow().clone();
+        new_state.three_dimensional_effect = Some(ThreeDimensionalEffect(enable));
+        Self::publish_state(
+            &state_sender,
+            &self.state_changes,
+            new_state,
+            ChangeSource::Local,
+        );
+
+        Ok(())
+    }
+
+    /// Issues the token required by power_off and factory_reset,
+    /// replacing any previously issued token
+    pub async fn confirmation_token(&self, action: DestructiveAction) -> ConfirmationToken {
+        self.pending_confirmation.lock().await.issue(action)
+    }
+
+    pub async fn power_off(&self, token: ConfirmationToken) -> SoundcoreLibResult<()> {
+        Self::check_flag(&self.latest_state().await, FeatureFlags::POWER_OFF)?;
+        self.pending_confirmation
+            .lock()
+            .await
+            .consume(DestructiveAction::PowerOff, token)?;
+        self.connection
+            .write(
+                &PowerOffCommandBuilder::new(self.model).build(),
+                WriteType::WithoutResponse,
+            )
+            .await
+    }
+
+    pub async fn factory_reset(&self, token: ConfirmationToken) -> SoundcoreLibResult<()> {
+        Self::check_flag(&self.latest_state().await, FeatureFlags::FACTORY_RESET)?;
+        self.pending_confirmation
+            .lock()
+            .await
+            .consume(DestructiveAction::FactoryReset, token)?;
+        self.connection
+            .write(
+                &FactoryResetCommandBuilder::new(self.model).build(),
+                WriteType::WithoutResponse,
+            )
+            .await
+    }
+
+    /// Changes the advertised Bluetooth name, see RenameCommandBuilder::MAX_NAME_LENGTH
+    pub async fn rename(&self, name: String) -> SoundcoreLibResult<()> {
+        Self::check_flag(&self.latest_state().await, FeatureFlags::RENAME)?;
+        let command = RenameCommandBuilder::new(self.model, name).build()?;
+        self.connection
+            .write(&command, WriteType::WithoutResponse)
+            .await
+    }
+
+    fn check_flag(state: &SoundcoreDeviceState, flag: FeatureFlags) -> SoundcoreLibResult<()> {
+        if !state.feature_set.flags.contains(&flag) {
+            return Err(SoundcoreLibError::FeatureNotSupported(format!(
+                "{:?}",
+                flag
+            )));
+        }
+        Ok(())
+    }
+
     pub async fn set_eq(&self, eq: EQConfiguration) -> SoundcoreLibResult<()> {
         let latest_state = self.latest_state().await;
+        if let Some(features) = &latest_state.feature_set.equalizer_features {
+            if eq.is_per_channel() && features.channels < 2 {
+                return Err(SoundcoreLibError::FeatureNotSupported(
+                    "Per-channel equalizer".to_string(),
+                ));
+            }
+        }
+        // The curves of the artist presets are unknown, so they can't be sent
+        if eq.get_profile().is_professional() {
+            return Err(SoundcoreLibError::FeatureNotSupported(format!(
+                "EQ profile {}",
+                eq.get_profile()
+            )));
+        }
         let state_sender = self.state_channel.lock().await;
         let mut new_state = state_sender.borrow().clone();
         // If the device supports bass up, the transition from
@@ -246,7 +518,7 @@ where
         // a BassUp command. Additionally, if the transition is
         // from BassBooster->SoundcoreSignature send the eq command
         // after the BassUp.
-        if let Some(features) = latest_state.feature_set.equalizer_features {
+        if let Some(features) = &latest_state.feature_set.equalizer_features {
             let latest_eq_profile = latest_state.eq_configuration.get_profile();
             let new_eq_profile = eq.get_profile();
             if features.has_bass_up {
@@ -267,7 +539,12 @@ where
                     let mut new_eq = new_state.eq_configuration.clone();
                     new_eq.set_profile(EQProfile::BassBooster);
                     new_state.eq_configuration = new_eq;
-                    state_sender.send_replace(new_state);
+                    Self::publish_state(
+                        &state_sender,
+                        &self.state_changes,
+                        new_state,
+                        ChangeSource::Local,
+                    );
                     return Ok(());
                 } else if latest_eq_profile == EQProfile::BassBooster
                     && new_eq_profile == EQProfile::SoundcoreSignature
@@ -283,15 +560,76 @@ where
             }
         }
 
-        let command = EqCommandBuilder::new(eq.clone(), self.model).build();
+        let command =
+            EqCommandBuilder::new(eq.clone(), self.model, &latest_state.feature_set).build()?;
 
         self.connection
             .write(&command, WriteType::WithoutResponse)
             .await?;
 
         new_state.eq_configuration = eq;
-        state_sender.send_replace(new_state);
+        Self::publish_state(
+            &state_sender,
+            &self.state_changes,
+            new_state,
+            ChangeSource::Local,
+        );
 
         Ok(())
     }
+
+    /// Exports the writable configuration, e.g. to restore it after a factory reset
+    pub async fn export_config(&self) -> DeviceConfigSnapshot {
+        DeviceConfigSnapshot::from_state(self.model, &self.latest_state().await)
+    }
+
+    /// Applies a snapshot taken from this or a compatible model.
+    /// Fields this device doesn't support or whose values it rejects are skipped and listed in the report.
+    pub async fn apply_config(
+        &self,
+        snapshot: &DeviceConfigSnapshot,
+    ) -> SoundcoreLibResult<ConfigApplyReport> {
+        snapshot.check_compatible(self.model)?;
+        let state = self.latest_state().await;
+        let mut report = ConfigApplyReport::default();
+        let unsupported =
+            |field: StateField| Err(SoundcoreLibError::FeatureNotSupported(field.to_string()));
+
+        if let Some(sound_mode) = snapshot.sound_mode {
+            let result = match state.feature_set.sound_mode_features {
+                Some(_) => self.set_sound_mode(sound_mode).await,
+                None => unsupported(StateField::SoundMode),
+            };
+            report.record(StateField::SoundMode, result)?;
+        }
+        if let Some(eq) = &snapshot.eq_configuration {
+            let result = match state.feature_set.equalizer_features {
+                Some(_) => self.set_eq(eq.to_owned()).await,
+                None => unsupported(StateField::EqConfiguration),
+            };
+            report.record(StateField::EqConfiguration, result)?;
+        }
+        if let Some(cycle) = snapshot.sound_mode_cycle {
+            let result = self.set_sound_mode_cycle(cycle).await;
+            report.record(StateField::SoundModeCycle, result)?;
+        }
+        if let Some(ThreeDimensionalEffect(enable)) = snapshot.three_dimensional_effect {
+            let result = self.set_three_dimensional_effect(enable).await;
+            report.record(StateField::ThreeDimensionalEffect, result)?;
+        }
+        if let Some(language) = snapshot.prompt_language {
+            let result = self.set_prompt_language(language).await;
+            report.record(StateField::PromptLanguage, result)?;
+        }
+
+        for (field, supported) in snapshot.read_only_fields(&state) {
+            let reason = match supported {
+                true => ConfigSkipReason::NotWritable,
+                false => ConfigSkipReason::Unsupported,
+            };
+            report.skip(field, reason);
+        }
+
+        Ok(report)
+    }
 }
@@ -2,11 +2,12 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::{sync::Arc, time::Duration};
 
+use log::trace;
+
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use typeshare::typeshare;
 
-use manager_fut::ManagerFuture;
 /// default-features shall be set to false
 #[cfg(any(
     feature = "mock",
@@ -14,6 +15,7 @@ use manager_fut::ManagerFuture;
     feature = "winrt-backend"
 ))]
 use manager_fut::TokioFuture;
+use manager_fut::{ManagerFuture, ManagerJoinHandle};
 
 // TODO: Specify clippy & fmt features
 #[allow(unused_imports)]
@@ -23,9 +25,11 @@ use crate::ble::BLEAdapterEvent;
 #[cfg(any(test, feature = "mock"))]
 use crate::mocks::*;
 use crate::{
+    api::{ConnectionStats, ConnectionStatsSnapshot, SoundcoreDeviceState, StateChange},
     ble::{BLEConnectionManager, BLEDeviceDescriptor},
     btaddr::BluetoothAdrr,
     device::SoundcoreBLEDevice,
+    devices::model_from_advertised_name,
     error::SoundcoreLibResult,
     types::{KnownProductCodes, SOUNDCORE_NAME_PRODUCT_CODE_MAP},
 };
@@ -33,6 +37,13 @@ use crate::{
 type DeviceMap<B, F> = RwLock<
     HashMap<BluetoothAdrr, Arc<SoundcoreBLEDevice<<B as BLEConnectionManager>::Connection, F>>>,
 >;
+type SnapshotMap = Arc<RwLock<HashMap<BluetoothAdrr, DeviceSnapshot>>>;
+
+/// An event emitted by the manager-level state stream, tagged with the device's address.
+pub type DeviceStateEvent = (BluetoothAdrr, SoundcoreDeviceState);
+
+/// A field-level state change of a device, tagged with the device's address.
+pub type DeviceStateChangeEvent = (BluetoothAdrr, StateChange);
 
 pub struct DeviceManager<B, F>
 where
@@ -41,6 +52,16 @@ where
 {
     ble_manager: B,
     ble_devices: DeviceMap<B, F>,
+    /// Last known state and connection status of every device that has been connected
+    snapshots: SnapshotMap,
+    /// Merged state stream of all connected devices
+    state_events: broadcast::Sender<DeviceStateEvent>,
+    /// Merged field-level state changes of all connected devices
+    state_change_events: broadcast::Sender<DeviceStateChangeEvent>,
+    /// Handles of the tasks forwarding each device's channels to the merged streams
+    state_forwarders: RwLock<HashMap<BluetoothAdrr, Vec<F::JoinHandle>>>,
+    /// Connection counters of every device that has been connected, kept across reconnects
+    connection_stats: RwLock<HashMap<BluetoothAdrr, Arc<ConnectionStats>>>,
 }
 
 impl<B, F> DeviceManager<B, F>
@@ -48,10 +69,17 @@ where
     B: BLEConnectionManager,
     F: ManagerFuture,
 {
+    const STATE_EVENTS_CAPACITY: usize = 255;
+
     pub async fn new(ble_manager: B) -> Self {
         Self {
             ble_manager,
             ble_devices: RwLock::new(HashMap::new()),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            state_events: broadcast::channel(Self::STATE_EVENTS_CAPACITY).0,
+            state_change_events: broadcast::channel(Self::STATE_EVENTS_CAPACITY).0,
+            state_forwarders: RwLock::new(HashMap::new()),
+            connection_stats: RwLock::new(HashMap::new()),
         }
     }
 
@@ -69,7 +97,21 @@ where
             Entry::Vacant(ve) => {
                 // TODO: Check UUID sets based on resolved model
                 let connection = self.ble_manager.connect(device.descriptor, None).await?;
-                let device = Arc::new(SoundcoreBLEDevice::new(connection).await?);
+                let (stats, reconnect) = match self
+                    .connection_stats
+                    .write()
+                    .await
+                    .entry(ve.key().to_owned())
+                {
+                    Entry::Occupied(e) => (e.get().to_owned(), true),
+                    Entry::Vacant(e) => (e.insert(Arc::default()).to_owned(), false),
+                };
+                let device =
+                    Arc::new(SoundcoreBLEDevice::with_stats(connection, stats.to_owned()).await?);
+                if reconnect {
+                    stats.record_reconnect();
+                }
+                self.track_device(ve.key().to_owned(), &device).await;
                 ve.insert(device.clone());
                 Ok(device)
             }
@@ -85,9 +127,20 @@ where
 
     pub async fn disconnect(&self, addr: BluetoothAdrr) -> SoundcoreLibResult<()> {
         self.ble_devices.write().await.remove(&addr);
+        self.untrack_device(&addr).await;
         Ok(())
     }
 
+    /// The connection counters of every device that has been connected
+    pub async fn connection_stats(&self) -> HashMap<BluetoothAdrr, ConnectionStatsSnapshot> {
+        self.connection_stats
+            .read()
+            .await
+            .iter()
+            .map(|(addr, stats)| (addr.to_owned(), stats.snapshot()))
+            .collect()
+    }
+
     pub async fn list_open_connections(&self) -> Vec<BluetoothAdrr> {
         self.ble_devices.read().await.keys().cloned().collect()
     }
@@ -114,10 +167,107 @@ where
 
     pub async fn disconnect_all(&self) -> SoundcoreLibResult<()> {
         let mut devices = self.ble_devices.write().await;
+        for addr in devices.keys() {
+            self.untrack_device(addr).await;
+        }
         devices.clear();
         Ok(())
     }
 
+    /// Subscribes to the merged state stream of all connected devices.
+    /// The current state of a device is emitted once when it connects, followed by every change.
+    pub fn state_events(&self) -> broadcast::Receiver<DeviceStateEvent> {
+        self.state_events.subscribe()
+    }
+
+    /// Subscribes to the merged field-level state changes of all connected devices.
+    pub fn state_change_events(&self) -> broadcast::Receiver<DeviceStateChangeEvent> {
+        self.state_change_events.subscribe()
+    }
+
+    /// Returns the last known state and connection status of every device
+    /// that has been connected through this manager, ordered by address.
+    pub async fn snapshot(&self) -> Vec<DeviceSnapshot> {
+        let mut snapshots = self
+            .snapshots
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        snapshots.sort_by(|a, b| a.addr.cmp(&b.addr));
+        snapshots
+    }
+
+    async fn track_device(
+        &self,
+        addr: BluetoothAdrr,
+        device: &SoundcoreBLEDevice<B::Connection, F>,
+    ) {
+        let mut state_channel = device.state_channel().await;
+        self.snapshots.write().await.insert(
+            addr.clone(),
+            DeviceSnapshot {
+                addr: addr.clone(),
+                status: DeviceConnectionStatus::Connected,
+                state: state_channel.borrow().clone(),
+            },
+        );
+
+        let (snapshots, state_events) = (self.snapshots.clone(), self.state_events.clone());
+        let forwarder_addr = addr.clone();
+        let state_handle = F::spawn(async move {
+            loop {
+                let state = state_channel.borrow_and_update().clone();
+                if let Some(snapshot) = snapshots.write().await.get_mut(&forwarder_addr) {
+                    snapshot.state = state.clone();
+                }
+                // An error only means that there are no subscribers
+                let _ = state_events.send((forwarder_addr.clone(), state));
+                if state_channel.changed().await.is_err() {
+                    break;
+                }
+            }
+            trace!("State forwarder for {:?} finished", forwarder_addr);
+        });
+
+        let mut state_changes = device.state_changes();
+        let state_change_events = self.state_change_events.clone();
+        let forwarder_addr = addr.clone();
+        let state_change_handle = F::spawn(async move {
+            loop {
+                match state_changes.recv().await {
+                    Ok(change) => {
+                        let _ = state_change_events.send((forwarder_addr.clone(), change));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        trace!(
+                            "State change forwarder for {:?} lagged, skipped {} changes",
+                            forwarder_addr,
+                            skipped
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            trace!("State change forwarder for {:?} finished", forwarder_addr);
+        });
+
+        let handles = vec![state_handle, state_change_handle];
+        if let Some(old_handles) = self.state_forwarders.write().await.insert(addr, handles) {
+            old_handles.iter().for_each(ManagerJoinHandle::abort);
+        }
+    }
+
+    async fn untrack_device(&self, addr: &BluetoothAdrr) {
+        if let Some(handles) = self.state_forwarders.write().await.remove(addr) {
+            handles.iter().for_each(ManagerJoinHandle::abort);
+        }
+        if let Some(snapshot) = self.snapshots.write().await.get_mut(addr) {
+            snapshot.status = DeviceConnectionStatus::Disconnected;
+        }
+    }
+
     fn map_descriptor_to_discovered_device(descriptor: &BLEDeviceDescriptor) -> DiscoveredDevice {
         DiscoveredDevice {
             descriptor: descriptor.to_owned(),
@@ -126,12 +276,15 @@ where
     }
 
     fn resolve_model(discovered_device: DiscoveredDevice) -> DiscoveredDevice {
-        match SOUNDCORE_NAME_PRODUCT_CODE_MAP
+        let name = &discovered_device.descriptor.name;
+        let model = SOUNDCORE_NAME_PRODUCT_CODE_MAP
             .into_iter()
-            .find(|(k, _v)| discovered_device.descriptor.name.contains(**k))
-        {
-            Some((_k, v)) => DiscoveredDevice {
-                model: Some(v.to_owned()),
+            .find(|(k, _v)| name.contains(**k))
+            .map(|(_k, v)| v.to_owned())
+            .or_else(|| model_from_advertised_name(name));
+        match model {
+            Some(model) => DiscoveredDevice {
+                model: Some(model),
                 ..discovered_device
             },
             None => discovered_device,
@@ -150,17 +303,265 @@ pub struct DiscoveredDevice {
     pub model: Option<KnownProductCodes>,
 }
 
+/// The connection status of a device known to the DeviceManager.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum DeviceConnectionStatus {
+    Connected,
+    Disconnected,
+}
+
+/// The last known state of a device, along with its connection status.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct DeviceSnapshot {
+    pub addr: BluetoothAdrr,
+    pub status: DeviceConnectionStatus,
+    pub state: SoundcoreDeviceState,
+}
+
+/// The manager returned by `create_device_manager` for the enabled backend.
+#[cfg(all(
+    feature = "btleplug-backend",
+    not(feature = "winrt-backend"),
+    not(feature = "mock")
+))]
+pub type DefaultDeviceManager = DeviceManager<BtlePlugBLEManager, TokioFuture>;
+
+#[cfg(feature = "mock")]
+pub type DefaultDeviceManager = DeviceManager<MockBLEConnectionManager, TokioFuture>;
+
 #[cfg(all(
     feature = "btleplug-backend",
     not(feature = "winrt-backend"),
     not(feature = "mock")
 ))]
-pub async fn create_device_manager() -> DeviceManager<BtlePlugBLEManager, TokioFuture> {
+pub async fn create_device_manager() -> DefaultDeviceManager {
     let manager = BtlePlugBLEManager::new().await.unwrap();
     DeviceManager::new(manager).await
 }
 
 #[cfg(feature = "mock")]
-pub async fn create_device_manager() -> DeviceManager<MockBLEConnectionManager, TokioFuture> {
+pub async fn create_device_manager() -> DefaultDeviceManager {
     DeviceManager::new(MockBLEConnectionManager::new()).await
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{
+        ChangeSource, ConfigSkipReason, DestructiveAction, DeviceConfigSnapshot,
+        SkippedConfigField, StateField,
+    };
+    use crate::error::SoundcoreLibError;
+    use crate::models::{
+        CurrentSoundMode, EQConfiguration, MonoEQ, PromptLanguage, SoundMode, TouchTone,
+    };
+    use crate::parsers::generate_checksum;
+
+    use super::*;
+
+    async fn connect_mock_device(
+        manager: &DeviceManager<MockBLEConnectionManager, TokioFuture>,
+    ) -> (
+        BluetoothAdrr,
+        Arc<SoundcoreBLEDevice<MockBLEConnection, TokioFuture>>,
+    ) {
+        let discovered = manager.ble_scan(None).await.unwrap()[0].to_owned();
+        let addr = discovered.descriptor.addr.clone();
+        (addr, manager.connect(discovered).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_emit_initial_state_on_connect() {
+        let manager = create_device_manager().await;
+        let mut events = manager.state_events();
+        let (device_addr, device) = connect_mock_device(&manager).await;
+
+        let (addr, state) = events.recv().await.unwrap();
+        assert_eq!(addr, device_addr);
+        assert_eq!(state, device.latest_state().await);
+    }
+
+    #[tokio::test]
+    async fn should_emit_state_changes() {
+        let manager = create_device_manager().await;
+        let mut events = manager.state_events();
+        let (_, device) = connect_mock_device(&manager).await;
+        let _initial = events.recv().await.unwrap();
+
+        let sound_mode = SoundMode {
+            current: CurrentSoundMode::ANC,
+            ..Default::default()
+        };
+        device.set_sound_mode(sound_mode).await.unwrap();
+
+        let (_, state) = events.recv().await.unwrap();
+        assert_eq!(state.sound_mode, sound_mode);
+    }
+
+    #[tokio::test]
+    async fn should_emit_local_state_changes() {
+        let manager = create_device_manager().await;
+        let mut change_events = manager.state_change_events();
+        let (device_addr, device) = connect_mock_device(&manager).await;
+
+        let sound_mode = SoundMode {
+            current: CurrentSoundMode::ANC,
+            ..Default::default()
+        };
+        device.set_sound_mode(sound_mode).await.unwrap();
+
+        let (addr, change) = change_events.recv().await.unwrap();
+        assert_eq!(addr, device_addr);
+        assert_eq!(change.source, ChangeSource::Local);
+        assert_eq!(change.changed, vec![StateField::SoundMode]);
+        assert_eq!(change.state.sound_mode, sound_mode);
+    }
+
+    #[tokio::test]
+    async fn should_set_per_channel_eq() {
+        let manager = create_device_manager().await;
+        let (_, device) = connect_mock_device(&manager).await;
+
+        let eq = EQConfiguration::stereo_custom(
+            MonoEQ::from_signed_bytes(vec![0, 10, 20, 30, 40, 50, 60, 70]),
+            MonoEQ::from_signed_bytes(vec![70, 60, 50, 40, 30, 20, 10, 0]),
+        );
+        device.set_eq(eq.clone()).await.unwrap();
+
+        assert_eq!(device.latest_state().await.eq_configuration, eq);
+    }
+
+    #[tokio::test]
+    async fn should_gate_management_actions() {
+        let manager = create_device_manager().await;
+        let (_, device) = connect_mock_device(&manager).await;
+
+        // The mocked device doesn't report any feature flags
+        let token = device
+            .confirmation_token(DestructiveAction::FactoryReset)
+            .await;
+        assert!(matches!(
+            device.factory_reset(token).await,
+            Err(SoundcoreLibError::FeatureNotSupported(_))
+        ));
+        assert!(device.rename("Desk".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_skip_unsupported_config_fields() {
+        let manager = create_device_manager().await;
+        let (_, device) = connect_mock_device(&manager).await;
+
+        // The mocked device doesn't report any features
+        let snapshot = device.export_config().await;
+        assert_eq!(snapshot.sound_mode, None);
+        let snapshot = DeviceConfigSnapshot {
+            sound_mode: Some(SoundMode::default()),
+            prompt_language: Some(PromptLanguage::Chinese),
+            touch_tone: Some(TouchTone(true)),
+            ..snapshot
+        };
+
+        let report = device.apply_config(&snapshot).await.unwrap();
+        let unsupported = |field| SkippedConfigField {
+            field,
+            reason: ConfigSkipReason::Unsupported,
+        };
+        assert!(report.applied.is_empty());
+        assert_eq!(
+            report.skipped,
+            vec![
+                unsupported(StateField::SoundMode),
+                unsupported(StateField::PromptLanguage),
+                unsupported(StateField::TouchTone),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_config_from_incompatible_model() {
+        let manager = create_device_manager().await;
+        let (_, device) = connect_mock_device(&manager).await;
+
+        let snapshot = device.export_config().await;
+        let other_model = match snapshot.model {
+            KnownProductCodes::A3951 => KnownProductCodes::A3040,
+            _ => KnownProductCodes::A3951,
+        };
+        let snapshot = DeviceConfigSnapshot {
+            model: other_model,
+            sound_mode: Some(SoundMode::default()),
+            ..snapshot
+        };
+
+        assert!(matches!(
+            device.apply_config(&snapshot).await,
+            Err(SoundcoreLibError::InvalidConfigSnapshot(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_snapshot_connection_status() {
+        let manager = create_device_manager().await;
+        let (addr, _device) = connect_mock_device(&manager).await;
+
+        let snapshot = manager.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].addr, addr);
+        assert_eq!(snapshot[0].status, DeviceConnectionStatus::Connected);
+
+        manager.disconnect(addr.clone()).await.unwrap();
+
+        let snapshot = manager.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].status, DeviceConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn should_keep_connection_stats_across_reconnects() {
+        let manager = create_device_manager().await;
+        let (addr, _device) = connect_mock_device(&manager).await;
+        assert_eq!(manager.connection_stats().await[&addr].reconnects, 0);
+
+        manager.disconnect(addr.clone()).await.unwrap();
+        connect_mock_device(&manager).await;
+        assert_eq!(manager.connection_stats().await[&addr].reconnects, 1);
+    }
+
+    #[tokio::test]
+    async fn should_count_received_packets() {
+        let connection = MockBLEConnection::new();
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        connection.set_read_channel(rx).await;
+        let device = SoundcoreBLEDevice::<_, TokioFuture>::new(Arc::new(connection))
+            .await
+            .unwrap();
+
+        let valid = test_data::a3951::A3951_SOUND_MODE_UPDATE_BYTES.to_vec();
+        let mut bad_checksum = valid.clone();
+        *bad_checksum.last_mut().unwrap() ^= 0xFF;
+        // A sound mode update too short for its payload
+        let mut truncated = vec![0x09, 0xFF, 0x00, 0x00, 0x01, 0x06, 0x01, 0x0B, 0x00, 0x00];
+        truncated.push(generate_checksum(&truncated));
+        for bytes in [valid, bad_checksum, truncated] {
+            tx.send(bytes).await.unwrap();
+        }
+
+        let expected = ConnectionStatsSnapshot {
+            packets_received: 3,
+            parse_failures: 1,
+            checksum_mismatches: 1,
+            reconnects: 0,
+        };
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while device.connection_stats() != expected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("unexpected stats {:?}", device.connection_stats()));
+    }
+}
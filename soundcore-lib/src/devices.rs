@@ -5,6 +5,7 @@ pub use a3040::*;
 pub use a3930::*;
 pub use a3947::*;
 pub use a3951::*;
+pub use firmware_quirks::*;
 
 mod a3027;
 mod a3028;
@@ -13,3 +14,20 @@ mod a3040;
 mod a3930;
 mod a3947;
 mod a3951;
+mod firmware_quirks;
+
+use crate::api::DeviceFeatureSet;
+use crate::types::KnownProductCodes;
+
+/// The feature set a model declares, before any firmware quirks are applied
+pub fn model_features(model: KnownProductCodes) -> Option<DeviceFeatureSet> {
+    match model {
+        KnownProductCodes::A3027 => Some(a3027_features()),
+        KnownProductCodes::A3028 => Some(a3028_features()),
+        KnownProductCodes::A3029 => Some(a3029_features()),
+        KnownProductCodes::A3040 => Some(a3040_features()),
+        KnownProductCodes::A3930 => Some(a3930_features()),
+        KnownProductCodes::A3951 => Some(a3951_features()),
+        _ => None,
+    }
+}
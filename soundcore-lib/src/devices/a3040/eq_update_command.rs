@@ -1,19 +1,32 @@
+use crate::error::SoundcoreLibResult;
 use crate::models::{CustomHearID, EQConfiguration, EQProfile, StereoEQConfiguration};
-use crate::packets::Packet;
+use crate::packets::{EQEncoder, Packet};
 
 pub struct A3040EqUpdateCommand {
     eq_configuration: StereoEQConfiguration,
-    hear_id: Option<CustomHearID>,
+    eq_bytes: [Vec<u8>; 2],
+    drc_eq_bytes: [Vec<u8>; 2],
 }
 
 impl A3040EqUpdateCommand {
     const DEFAULT_HEAR_ID_EQ: [u8; 10] =
         [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
-    pub fn new(eq_configuration: EQConfiguration) -> Self {
-        Self {
-            eq_configuration: eq_configuration.into(),
-            hear_id: None,
-        }
+    const EQ_SLOTS: usize = 10;
+
+    pub fn new(eq_configuration: EQConfiguration, encoder: &EQEncoder) -> SoundcoreLibResult<Self> {
+        let eq_configuration: StereoEQConfiguration = eq_configuration.into();
+        let eq = &eq_configuration.eq;
+        Ok(Self {
+            eq_bytes: [
+                encoder.encode(&eq.left, Self::EQ_SLOTS)?,
+                encoder.encode(&eq.right, Self::EQ_SLOTS)?,
+            ],
+            drc_eq_bytes: [
+                encoder.encode_drc(&eq.left, Self::EQ_SLOTS)?,
+                encoder.encode_drc(&eq.right, Self::EQ_SLOTS)?,
+            ],
+            eq_configuration,
+        })
     }
 }
 
@@ -35,47 +48,31 @@ impl Packet for A3040EqUpdateCommand {
             self.eq_configuration.profile.id() as u8,
             (self.eq_configuration.profile.id() >> 8) as u8,
         ];
-        let hear_id = self.hear_id.clone().unwrap_or_default();
+        // HearID isn't configurable yet, so the defaults are sent
+        let hear_id = CustomHearID::default();
         let hear_id_eq_idx_bytes = hear_id.hear_id_eq_index.unwrap_or(0).to_be_bytes();
-        let no_drc_eq_bytes_left = self.eq_configuration.eq.left.to_10band_bytes();
-        let no_drc_eq_bytes_right = self.eq_configuration.eq.right.to_10band_bytes();
+        let [no_drc_eq_bytes_left, no_drc_eq_bytes_right] = &self.eq_bytes;
         // TODO: Refactor HearID and parsers to include these
         let hear_id_gender = 0xFF;
         let hear_id_age_range = 0xFF;
 
-        let (hear_id_eq_left, hear_id_eq_right) = match &self.hear_id {
-            Some(hear_id) => (
-                hear_id.clone().custom_values.left.to_bytes(10usize),
-                hear_id.clone().custom_values.right.to_bytes(10usize),
-            ),
-            None => (
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-            ),
-        };
+        let [hear_id_eq_left, hear_id_eq_right] = [
+            Self::DEFAULT_HEAR_ID_EQ.to_vec(),
+            Self::DEFAULT_HEAR_ID_EQ.to_vec(),
+        ];
 
         let hear_id_time: [u8; 4] = hear_id.base.time.to_be_bytes();
         let hear_id_type = hear_id.hearid_type.0;
 
-        let (hear_id_custom_left, hear_id_custom_right) = match &self.hear_id {
-            Some(hearid) => (
-                hearid.custom_values.left.to_bytes(10usize),
-                hearid.custom_values.right.to_bytes(10usize),
-            ),
-            None => (
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-            ),
-        };
+        let [hear_id_custom_left, hear_id_custom_right] = [&hear_id_eq_left, &hear_id_eq_right];
 
-        let drc_eq_bytes_left = self.eq_configuration.eq.left.to_drc_bytes();
-        let drc_eq_bytes_right = self.eq_configuration.eq.right.to_drc_bytes();
+        let [drc_eq_bytes_left, drc_eq_bytes_right] = &self.drc_eq_bytes;
 
         let mut bytes = Vec::with_capacity(96);
         bytes.extend_from_slice(&profile_bytes);
         bytes.extend_from_slice(&hear_id_eq_idx_bytes);
-        bytes.extend_from_slice(&no_drc_eq_bytes_left);
-        bytes.extend_from_slice(&no_drc_eq_bytes_right);
+        bytes.extend_from_slice(no_drc_eq_bytes_left);
+        bytes.extend_from_slice(no_drc_eq_bytes_right);
         bytes.push(hear_id_gender);
         bytes.push(hear_id_age_range);
         bytes.push(0x00);
@@ -83,10 +80,10 @@ impl Packet for A3040EqUpdateCommand {
         bytes.extend_from_slice(&hear_id_eq_right);
         bytes.extend_from_slice(&hear_id_time);
         bytes.push(hear_id_type);
-        bytes.extend_from_slice(&hear_id_custom_left);
-        bytes.extend_from_slice(&hear_id_custom_right);
-        bytes.extend_from_slice(&drc_eq_bytes_left);
-        bytes.extend_from_slice(&drc_eq_bytes_right);
+        bytes.extend_from_slice(hear_id_custom_left);
+        bytes.extend_from_slice(hear_id_custom_right);
+        bytes.extend_from_slice(drc_eq_bytes_left);
+        bytes.extend_from_slice(drc_eq_bytes_right);
         bytes.push(0x00); // Volume DB
         bytes
     }
@@ -95,6 +92,7 @@ impl Packet for A3040EqUpdateCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devices::a3040_features;
     use crate::models::{EQProfile, MonoEQConfiguration};
     use pretty_assertions::assert_eq;
 
@@ -104,7 +102,9 @@ mod tests {
             profile: EQProfile::Acoustic,
             eq: EQProfile::Acoustic.eq(),
         };
-        let command = A3040EqUpdateCommand::new(EQConfiguration::Mono(eq_configuration));
+        let encoder = EQEncoder::new(&a3040_features());
+        let command =
+            A3040EqUpdateCommand::new(EQConfiguration::Mono(eq_configuration), &encoder).unwrap();
         assert_eq!(
             test_data::a3040::SET_EQ_ACOUSTIC_NO_HEAR_ID.to_vec(),
             command.bytes()
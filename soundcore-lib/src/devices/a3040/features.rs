@@ -5,7 +5,8 @@ use crate::api::{DeviceFeatureSet, EqualizerFeatures, FeatureFlags, SoundModeFea
 pub fn a3040_features() -> DeviceFeatureSet {
     DeviceFeatureSet {
         sound_mode_features: Some(
-            SoundModeFeatures::adaptive_customizable_anc_customizable_transparency(),
+            SoundModeFeatures::adaptive_customizable_anc_customizable_transparency()
+                .with_auto_anc(),
         ),
         equalizer_features: Some(EqualizerFeatures {
             bands: 8,
@@ -15,9 +16,11 @@ pub fn a3040_features() -> DeviceFeatureSet {
         flags: Arc::new([
             FeatureFlags::CUSTOM_BUTTONS,
             FeatureFlags::AUTO_POWER_OFF_ON,
+            FeatureFlags::DRC,
             FeatureFlags::POWER_ON_BATTERY_NOTICE,
             FeatureFlags::MULTIPLE_DEVICE_LIST,
             FeatureFlags::HEARING_PROTECTION,
+            FeatureFlags::LANG_PROMPT,
             FeatureFlags::AMBIENT_SOUND_NOTICE,
         ]),
     }
@@ -39,6 +39,8 @@ mod tests {
                 custom_anc: CustomANCValue::from_u8(5),
                 trans_mode: TransparencyMode::Customizable(CustomizableTransparencyMode::Custom),
                 custom_trans: Some(CustomTransparencyValue::from_u8(3)),
+                auto_anc: true,
+                ..Default::default()
             },
         };
 
@@ -113,6 +113,7 @@ pub fn parse_a3947_state_update<'a, E: ParseError<'a>>(
             parse_auto_power_off_on,
             bool_parser::<CustomButtonWearEnable, E>,
         ))(bytes)?;
+        // TODO: The remaining bytes are unknown, the sound mode cycle is not at a known offset
 
         Ok((
             bytes,
@@ -155,6 +156,8 @@ impl From<A3947StateResponse> for DeviceStateResponse {
             // TODO: add feature set
             feature_set: Default::default(),
             battery: value.battery.into(),
+            // The packet carries the case's level, but where its charging bit is isn't known
+            case_battery: None,
             sound_mode: value.sound_mode,
             eq: value.eq.into(),
             sn: Some(value.sn),
@@ -176,10 +179,12 @@ impl From<A3947StateResponse> for DeviceStateResponse {
             ambient_sound_notice: None,
             power_on_battery_notice: None,
             three_dimensional_effect: None,
+            game_mode: None,
             device_color: Some(value.device_color),
             ldac: Some(value.ldac),
             prompt_language: None,
             hearing_protect: Some(value.hearing_protect),
+            sound_mode_cycle: None,
         }
     }
 }
@@ -1,20 +1,33 @@
 use crate::{
+    error::SoundcoreLibResult,
     models::{CustomHearID, EQConfiguration, StereoEQConfiguration},
-    packets::Packet,
+    packets::{EQEncoder, Packet},
 };
 
 pub struct A3951EqUpdateCommand {
     eq_configuration: StereoEQConfiguration,
-    hear_id: Option<CustomHearID>,
+    eq_bytes: [Vec<u8>; 2],
+    drc_eq_bytes: [Vec<u8>; 2],
 }
 
 impl A3951EqUpdateCommand {
     const DEFAULT_HEAR_ID_EQ: [u8; 8] = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
-    pub fn new(eq_configuration: EQConfiguration) -> Self {
-        Self {
-            eq_configuration: eq_configuration.into(),
-            hear_id: None,
-        }
+    const EQ_SLOTS: usize = 8;
+
+    pub fn new(eq_configuration: EQConfiguration, encoder: &EQEncoder) -> SoundcoreLibResult<Self> {
+        let eq_configuration: StereoEQConfiguration = eq_configuration.into();
+        let eq = &eq_configuration.eq;
+        Ok(Self {
+            eq_bytes: [
+                encoder.encode(&eq.left, Self::EQ_SLOTS)?,
+                encoder.encode(&eq.right, Self::EQ_SLOTS)?,
+            ],
+            drc_eq_bytes: [
+                encoder.encode_drc(&eq.left, Self::EQ_SLOTS)?,
+                encoder.encode_drc(&eq.right, Self::EQ_SLOTS)?,
+            ],
+            eq_configuration,
+        })
     }
 }
 
@@ -29,49 +42,31 @@ impl Packet for A3951EqUpdateCommand {
             self.eq_configuration.profile.id() as u8,
             (self.eq_configuration.profile.id() >> 8) as u8,
         ];
-        let hear_id = self.hear_id.clone().unwrap_or_default();
+        // HearID isn't configurable yet, so the defaults are sent
+        let hear_id = CustomHearID::default();
         let hear_id_eq_idx_bytes = hear_id.hear_id_eq_index.unwrap_or(0).to_be_bytes();
-        let no_drc_eq_bytes_left = self.eq_configuration.eq.left.to_8band_bytes();
-        let no_drc_eq_bytes_right = self.eq_configuration.eq.right.to_8band_bytes();
+        let [no_drc_eq_bytes_left, no_drc_eq_bytes_right] = &self.eq_bytes;
         // TODO: Refactor HearID and parsers to include these
         let hear_id_gender = 0xFF;
         let hear_id_age_range = 0xFF;
 
-        let (hear_id_eq_left, hear_id_eq_right) = match &self.hear_id {
-            Some(hear_id) => (
-                hear_id.clone().custom_values.left.to_8band_bytes(),
-                hear_id.clone().custom_values.right.to_8band_bytes(),
-            ),
-            None => (
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-            ),
-        };
+        let [hear_id_eq_left, hear_id_eq_right] = [
+            Self::DEFAULT_HEAR_ID_EQ.to_vec(),
+            Self::DEFAULT_HEAR_ID_EQ.to_vec(),
+        ];
 
         let hear_id_time: [u8; 4] = hear_id.base.time.to_be_bytes();
         let hear_id_type = hear_id.hearid_type.0;
 
-        let (hear_id_custom_left, hear_id_custom_right) = match &self.hear_id {
-            Some(hearid) => (
-                hearid.custom_values.left.to_8band_bytes(),
-                hearid.custom_values.right.to_8band_bytes(),
-            ),
-            None => (
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-                Self::DEFAULT_HEAR_ID_EQ.to_vec(),
-            ),
-        };
+        let [hear_id_custom_left, hear_id_custom_right] = [&hear_id_eq_left, &hear_id_eq_right];
 
-        let mut drc_eq_bytes_left = self.eq_configuration.eq.left.to_drc_bytes();
-        let mut drc_eq_bytes_right = self.eq_configuration.eq.right.to_drc_bytes();
-        drc_eq_bytes_left.truncate(8);
-        drc_eq_bytes_right.truncate(8);
+        let [drc_eq_bytes_left, drc_eq_bytes_right] = &self.drc_eq_bytes;
 
         let mut bytes = Vec::with_capacity(96);
         bytes.extend_from_slice(&profile_bytes);
         bytes.extend_from_slice(&hear_id_eq_idx_bytes);
-        bytes.extend_from_slice(&no_drc_eq_bytes_left);
-        bytes.extend_from_slice(&no_drc_eq_bytes_right);
+        bytes.extend_from_slice(no_drc_eq_bytes_left);
+        bytes.extend_from_slice(no_drc_eq_bytes_right);
         bytes.push(hear_id_gender);
         bytes.push(hear_id_age_range);
         bytes.push(0x00);
@@ -79,16 +74,17 @@ impl Packet for A3951EqUpdateCommand {
         bytes.extend_from_slice(&hear_id_eq_right);
         bytes.extend_from_slice(&hear_id_time);
         bytes.push(hear_id_type);
-        bytes.extend_from_slice(&hear_id_custom_left);
-        bytes.extend_from_slice(&hear_id_custom_right);
-        bytes.extend_from_slice(&drc_eq_bytes_left);
-        bytes.extend_from_slice(&drc_eq_bytes_right);
+        bytes.extend_from_slice(hear_id_custom_left);
+        bytes.extend_from_slice(hear_id_custom_right);
+        bytes.extend_from_slice(drc_eq_bytes_left);
+        bytes.extend_from_slice(drc_eq_bytes_right);
         bytes
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::devices::a3951_features;
     use crate::models::EQProfile;
 
     use super::*;
@@ -97,7 +93,8 @@ mod tests {
     #[test]
     fn test_deep_eq_update_command() {
         let eq_configuration = EQConfiguration::stereo_with_profile(EQProfile::Deep);
-        let command = A3951EqUpdateCommand::new(eq_configuration);
+        let encoder = EQEncoder::new(&a3951_features());
+        let command = A3951EqUpdateCommand::new(eq_configuration, &encoder).unwrap();
         assert_eq!(
             test_data::a3951::A3951_EQ_UPDATE_DEEP_NO_HEAR_ID.to_vec(),
             command.bytes()
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::models::DeviceFirmware;
+use crate::types::KnownProductCodes;
+
+/// Known firmware behaviour that differs from what the model normally supports
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum FirmwareQuirk {
+    /// The device sometimes advertises itself as "BES_BLE"
+    AdvertisesAsBesBle,
+    /// The earbuds report different firmware versions
+    TwsFirmwareMismatch,
+}
+
+impl FirmwareQuirk {
+    pub fn advertised_name(&self) -> Option<&'static str> {
+        match self {
+            FirmwareQuirk::AdvertisesAsBesBle => Some("BES_BLE"),
+            _ => None,
+        }
+    }
+}
+
+struct KnownFirmware {
+    model: KnownProductCodes,
+    quirks: &'static [FirmwareQuirk],
+}
+
+/// Only quirks observed on real devices belong here
+const KNOWN_FIRMWARE: &[KnownFirmware] = &[KnownFirmware {
+    model: KnownProductCodes::A3028,
+    quirks: &[FirmwareQuirk::AdvertisesAsBesBle],
+}];
+
+/// Looks up the known quirks of the model and checks the earbuds' firmware for a mismatch
+pub fn firmware_quirks(model: KnownProductCodes, fw: &DeviceFirmware) -> Vec<FirmwareQuirk> {
+    let mut quirks: Vec<FirmwareQuirk> = KNOWN_FIRMWARE
+        .iter()
+        .filter(|known| known.model == model)
+        .flat_map(|known| known.quirks.iter().copied())
+        .collect();
+    if fw.is_mismatched() {
+        quirks.push(FirmwareQuirk::TwsFirmwareMismatch);
+    }
+    quirks.sort();
+    quirks.dedup();
+    quirks
+}
+
+/// Resolves models which advertise a generic name because of a firmware bug
+pub fn model_from_advertised_name(name: &str) -> Option<KnownProductCodes> {
+    KNOWN_FIRMWARE
+        .iter()
+        .find(|known| {
+            known
+                .quirks
+                .iter()
+                .filter_map(FirmwareQuirk::advertised_name)
+                .any(|advertised| name.contains(advertised))
+        })
+        .map(|known| known.model)
+}
+
+#[cfg(test)]
+mod firmware_quirks_tests {
+    use crate::models::FirmwareVer;
+
+    use super::*;
+
+    #[test]
+    fn should_detect_mismatched_pairs() {
+        let fw = DeviceFirmware::new(FirmwareVer::new(2, 0), Some(FirmwareVer::new(1, 19)));
+        assert_eq!(
+            firmware_quirks(KnownProductCodes::A3040, &fw),
+            vec![FirmwareQuirk::TwsFirmwareMismatch]
+        );
+        let fw = DeviceFirmware::new(FirmwareVer::new(1, 19), None);
+        assert!(firmware_quirks(KnownProductCodes::A3040, &fw).is_empty());
+    }
+
+    #[test]
+    fn should_resolve_generic_names() {
+        assert_eq!(
+            model_from_advertised_name("BES_BLE"),
+            Some(KnownProductCodes::A3028)
+        );
+        assert_eq!(model_from_advertised_name("Q45"), None);
+    }
+}
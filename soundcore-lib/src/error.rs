@@ -61,6 +61,24 @@ pub enum SoundcoreLibError {
     NomParseError { error: String },
     #[error("Incompatible response")]
     IncompatibleResponse,
+    #[error("Invalid EQ preset: {0}")]
+    InvalidEQPreset(String),
+    #[error("EQ preset not found: {0}")]
+    EQPresetNotFound(String),
+    #[error("Invalid EQ: {0}")]
+    InvalidEQ(String),
+    #[error("Invalid device name: {0}")]
+    InvalidDeviceName(String),
+    #[error("Invalid or expired confirmation token")]
+    InvalidConfirmationToken,
+    #[error("Invalid config snapshot: {0}")]
+    InvalidConfigSnapshot(String),
+    #[cfg(feature = "ota")]
+    #[error("OTA error: {0}")]
+    Ota(String),
+    #[cfg(feature = "ota")]
+    #[error("OTA aborted")]
+    OtaAborted,
     #[error("Invalid MAC address: {addr}")]
     InvalidMACAddress { addr: String },
     // TODO: Remove btleplug-backend feature when device name resolution is fixed *see scanner.rs*
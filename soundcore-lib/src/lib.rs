@@ -11,5 +11,10 @@ pub mod types;
 pub mod device;
 pub mod device_manager;
 
+// The OTA protocol isn't verified against captures yet, so it has no public entry point
+#[cfg(feature = "ota")]
+#[allow(dead_code, unused_imports)]
+mod ota;
+
 #[cfg(any(test, feature = "mock"))]
 pub mod mocks;
@@ -2,6 +2,7 @@ pub use a3040_button_model::*;
 pub use a3909_button_model::*;
 pub use adaptive_anc_mode::*;
 pub use anc_mode::*;
+pub use anc_strength_mode::*;
 pub use auto_power::*;
 pub use battery::*;
 pub use button_model::*;
@@ -10,8 +11,10 @@ pub use custom_anc_value::*;
 pub use custom_trans_value::*;
 pub use customizable_trans_mode::*;
 pub use device_color::*;
+pub use earbud_side::*;
 pub use eq::*;
 pub use eq_configuration::*;
+pub use eq_conversion::*;
 pub use eq_profile::*;
 pub use feature_flags::*;
 pub use fw::*;
@@ -26,12 +29,14 @@ pub use prompt_language::*;
 pub use scene_based_anc_mode::*;
 pub use serial::*;
 pub use sound_mode::*;
+pub use sound_mode_cycle::*;
 pub use trans_mode::*;
 
 mod a3040_button_model;
 mod a3909_button_model;
 mod adaptive_anc_mode;
 mod anc_mode;
+mod anc_strength_mode;
 mod auto_power;
 mod battery;
 mod button_model;
@@ -40,8 +45,10 @@ mod custom_anc_value;
 mod custom_trans_value;
 mod customizable_trans_mode;
 mod device_color;
+mod earbud_side;
 mod eq;
 mod eq_configuration;
+mod eq_conversion;
 mod eq_profile;
 mod feature_flags;
 mod fw;
@@ -56,4 +63,5 @@ mod prompt_language;
 mod scene_based_anc_mode;
 mod serial;
 mod sound_mode;
+mod sound_mode_cycle;
 mod trans_mode;
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, FromRepr};
+use typeshare::typeshare;
+
+/// How the ANC strength is adjusted on newer devices
+// TODO: Verify the values against captures of devices supporting these modes
+#[repr(u8)]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Default,
+    FromRepr,
+    Display,
+    Hash,
+)]
+#[typeshare]
+pub enum ANCStrengthMode {
+    /// The strength is set using the custom ANC value
+    #[default]
+    Manual = 0,
+    /// The strength follows the environment noise
+    Environment = 1,
+    /// The strength follows the ear canal fit
+    EarCanal = 2,
+}
+
+impl ANCStrengthMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::from_repr(value)
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
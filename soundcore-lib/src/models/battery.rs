@@ -25,6 +25,21 @@ pub struct SingleBattery {
     pub level: u8,
 }
 
+impl SingleBattery {
+    /// Battery levels are reported in the 0-5 range
+    pub const MAX_LEVEL: u8 = 5;
+
+    /// None while the level is unknown, e.g. before the first state update
+    pub fn percent(&self) -> Option<u8> {
+        (self.level <= Self::MAX_LEVEL)
+            .then(|| (self.level as u16 * 100 / Self::MAX_LEVEL as u16) as u8)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.level == Self::MAX_LEVEL
+    }
+}
+
 impl From<SingleBattery> for Battery {
     fn from(b: SingleBattery) -> Self {
         Battery::Single(b)
@@ -51,3 +66,21 @@ impl Default for Battery {
         Battery::Single(SingleBattery::default())
     }
 }
+
+#[cfg(test)]
+mod battery_tests {
+    use super::*;
+
+    #[test]
+    fn should_only_report_known_percentages() {
+        let battery = |level| SingleBattery {
+            charging: false,
+            level,
+        };
+        assert_eq!(battery(0).percent(), Some(0));
+        assert_eq!(battery(3).percent(), Some(60));
+        assert!(battery(5).is_full());
+        assert_eq!(SingleBattery::default().percent(), None);
+        assert!(!SingleBattery::default().is_full());
+    }
+}
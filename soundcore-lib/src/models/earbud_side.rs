@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, FromRepr};
+use typeshare::typeshare;
+
+// TODO: Verify the values against captures of the find device command
+#[repr(u8)]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    FromRepr,
+    Display,
+    Hash,
+)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum EarbudSide {
+    Left = 0,
+    Right = 1,
+    Both = 2,
+}
+
+impl EarbudSide {
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+
+use super::{ParametricFilter, ParametricFilterKind, OCTAVE_Q};
+
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Default, Hash)]
 #[typeshare]
 pub struct StereoEQ {
@@ -26,47 +30,15 @@ impl MonoEQ {
     pub const MAX_FLOAT: f32 = 18.0;
     pub const MIN_FLOAT: f32 = 6.0;
     pub const DRC_ADJUSTMENT: f32 = 10.0;
+    /// The gain range in dB which can be sent to every device, 0 dB is stored as 120
+    pub const MIN_GAIN_DB: f32 = -6.0;
+    pub const MAX_GAIN_DB: f32 = 6.0;
+    /// The DRC transform is only defined for the first 8 bands
+    pub const DRC_BANDS: usize = 8;
+    /// The range plotted by frequency_response
+    pub const MIN_RESPONSE_FREQUENCY: f32 = 20.0;
+    pub const MAX_RESPONSE_FREQUENCY: f32 = 20000.0;
 
-    pub fn to_bytes(&self, bands: usize) -> Vec<u8> {
-        self.values
-            .iter()
-            .take(bands)
-            .map(|&v| Self::to_byte(&v))
-            .collect()
-    }
-
-    pub fn to_10band_bytes(&self) -> Vec<u8> {
-        let mut bytes: Vec<u8> = self
-            .values
-            .iter()
-            .take(8)
-            .map(|&v| Self::to_byte(&v))
-            .collect();
-        bytes.push(120u8);
-        bytes.push(0u8);
-        bytes
-    }
-
-    pub fn to_8band_bytes(&self) -> Vec<u8> {
-        self.values
-            .iter()
-            .take(8)
-            .map(|&v| Self::to_byte(&v))
-            .collect()
-    }
-
-    pub fn to_drc_bytes(&self) -> Vec<u8> {
-        Self::calculate_drc_adjustments(
-            self.values
-                .iter()
-                .take(8usize)
-                .map(|&v| (v as f32 / Self::DRC_ADJUSTMENT) - (Self::MAX_FLOAT - Self::MIN_FLOAT))
-                .collect(),
-        )
-        .iter()
-        .map(|&v| ((v + (Self::MAX_FLOAT - Self::MIN_FLOAT)) * Self::DRC_ADJUSTMENT).round() as u8) // Apply DRC adjustments to middle point
-        .collect()
-    }
     pub fn from_signed_bytes(bytes: Vec<i8>) -> Self {
         Self {
             values: bytes.iter().map(|&v| Self::from_signed(&v)).collect(),
@@ -83,11 +55,91 @@ impl MonoEQ {
         self.values.iter().map(|&v| Self::to_float(v)).collect()
     }
 
-    fn from_byte(value: &u8) -> u8 {
-        value.clamp(&Self::MIN_BYTE, &Self::MAX_BYTE).to_owned()
+    pub fn from_gains_db(gains: &[f32]) -> Self {
+        Self {
+            values: gains.iter().map(|&gain| Self::gain_to_byte(gain)).collect(),
+        }
+    }
+
+    /// The gain of each band in dB, clamped to MIN_GAIN_DB..=MAX_GAIN_DB
+    pub fn gains_db(&self) -> Vec<f32> {
+        self.values.iter().map(|&v| Self::byte_to_gain(v)).collect()
+    }
+
+    pub fn gain_db(&self, band: usize) -> Option<f32> {
+        self.values.get(band).map(|&v| Self::byte_to_gain(v))
+    }
+
+    /// Sets the gain of a band, rounded to 0.1 dB and clamped to MIN_GAIN_DB..=MAX_GAIN_DB
+    pub fn set_gain_db(&mut self, band: usize, gain: f32) -> SoundcoreLibResult<()> {
+        let value = self
+            .values
+            .get_mut(band)
+            .ok_or(SoundcoreLibError::InvalidArguments)?;
+        *value = Self::gain_to_byte(gain);
+        Ok(())
     }
 
-    fn to_byte(value: &u8) -> u8 {
+    /// The gains in dB of the first 8 bands after the DRC transform, missing bands are flat
+    pub fn drc_gains_db(&self) -> Vec<f32> {
+        let mut gains = [0.0; Self::DRC_BANDS];
+        self.gains_db()
+            .into_iter()
+            .zip(gains.iter_mut())
+            .for_each(|(gain, drc_gain)| *drc_gain = gain);
+        Self::calculate_drc_adjustments(gains).to_vec()
+    }
+
+    /// Approximates the curve at `points` log-spaced frequencies by modelling each band as
+    /// a one octave wide peaking filter. With `drc` the gains are transformed like they
+    /// are for devices supporting DRC.
+    pub fn frequency_response(
+        &self,
+        band_frequencies: &[u32],
+        drc: bool,
+        points: usize,
+    ) -> Vec<FrequencyResponsePoint> {
+        let gains = match drc {
+            true => self.drc_gains_db(),
+            false => self.gains_db(),
+        };
+        let filters = band_frequencies
+            .iter()
+            .zip(gains)
+            .map(|(&frequency, gain)| ParametricFilter {
+                kind: ParametricFilterKind::Peaking,
+                frequency: frequency as f32,
+                gain,
+                q: OCTAVE_Q,
+            })
+            .collect::<Vec<_>>();
+
+        let octaves = (Self::MAX_RESPONSE_FREQUENCY / Self::MIN_RESPONSE_FREQUENCY).log2();
+        (0..points)
+            .map(|idx| {
+                let position = idx as f32 / (points.max(2) - 1) as f32;
+                let frequency = Self::MIN_RESPONSE_FREQUENCY * 2f32.powf(octaves * position);
+                FrequencyResponsePoint {
+                    frequency,
+                    gain: filters.iter().map(|filter| filter.gain_at(frequency)).sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// The byte sent for a gain in dB, see MIN_GAIN_DB and MAX_GAIN_DB
+    pub(crate) fn gain_to_byte(gain: f32) -> u8 {
+        ((gain.clamp(Self::MIN_GAIN_DB, Self::MAX_GAIN_DB) + (Self::MAX_FLOAT - Self::MIN_FLOAT))
+            * Self::DRC_ADJUSTMENT)
+            .round() as u8
+    }
+
+    fn byte_to_gain(value: u8) -> f32 {
+        (value as f32 / Self::DRC_ADJUSTMENT - (Self::MAX_FLOAT - Self::MIN_FLOAT))
+            .clamp(Self::MIN_GAIN_DB, Self::MAX_GAIN_DB)
+    }
+
+    fn from_byte(value: &u8) -> u8 {
         value.clamp(&Self::MIN_BYTE, &Self::MAX_BYTE).to_owned()
     }
 
@@ -103,11 +155,8 @@ impl MonoEQ {
             .clamp(Self::MIN_BYTE, Self::MAX_BYTE)
     }
 
-    fn calculate_drc_adjustments(values: Vec<f32>) -> Vec<f32> {
-        // Input floats should be in the range ((MIN_FLOAT - MIN_FLOAT)  - (MAX_FLOAT - MIN_FLOAT)) and length >= 8
-        assert!(values.len() >= 8);
-        assert!(values.iter().all(|&v| ((-6.0)..=6.0).contains(&v)));
-
+    /// Ported from the original implementation, the inputs are gains in dB
+    pub(crate) fn calculate_drc_adjustments(values: [f32; 8]) -> [f32; 8] {
         // f64s is required to match the original implementation
         let d = values[0] as f64;
         let d2 = values[1] as f64;
@@ -165,15 +214,19 @@ impl MonoEQ {
                 + (d9 * 0.177f64))
                 - ((d10 * 0.71f64) * d3))
                 + (d12 * 1.5f64)) as f32,
-            0f32,
-            -120f32,
         ]
-        .iter()
         .map(|v| v / 10.0)
-        .collect()
     }
 }
 
+/// A point of an EQ frequency response, the gain is in dB
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[typeshare]
+pub struct FrequencyResponsePoint {
+    pub frequency: f32,
+    pub gain: f32,
+}
+
 impl From<MonoEQ> for StereoEQ {
     fn from(eq: MonoEQ) -> Self {
         Self {
@@ -205,12 +258,12 @@ mod eq_model {
 
     #[test]
     fn drc_transformation_check() {
-        let initial_floats = vec![
+        let initial_floats = [
             6.0, -6.0, 2.6000004, -3.0, 2.8000002, -1.6999998, 2.1999998, 0.39999962,
         ];
 
         // Extracted values from the original implementation
-        let expected_post_drc_floats = vec![
+        let expected_post_drc_floats = [
             1.193_512_2,
             -1.61997,
             1.232_412_1,
@@ -219,8 +272,6 @@ mod eq_model {
             -0.735985,
             0.583_195,
             -0.13796605,
-            0.0,
-            -12.0,
         ];
 
         let eq = MonoEQ::calculate_drc_adjustments(initial_floats);
@@ -229,8 +280,50 @@ mod eq_model {
 
     #[test]
     fn drc_acoustic_bytes_check() {
-        let expected_bytes = vec![125, 118, 123, 120, 124, 122, 124, 121, 120, 0];
-        let eq = EQProfile::Acoustic.eq().to_drc_bytes();
-        assert_eq!(expected_bytes, eq);
+        let expected_bytes = vec![125, 118, 123, 120, 124, 122, 124, 121];
+        let bytes: Vec<u8> = EQProfile::Acoustic
+            .eq()
+            .drc_gains_db()
+            .into_iter()
+            .map(MonoEQ::gain_to_byte)
+            .collect();
+        assert_eq!(expected_bytes, bytes);
+    }
+
+    #[test]
+    fn should_convert_gains_to_db() {
+        let mut eq = MonoEQ::from_vec(vec![120, 60, 180, 0, 125, 120, 120, 120]);
+        assert_eq!(eq.gain_db(0), Some(0.0));
+        assert_eq!(eq.gain_db(1), Some(-6.0));
+        assert_eq!(eq.gain_db(2), Some(6.0));
+        // Values below the DRC range are clamped
+        assert_eq!(eq.gain_db(3), Some(-6.0));
+        assert_eq!(eq.gain_db(8), None);
+
+        eq.set_gain_db(4, -2.54).unwrap();
+        eq.set_gain_db(5, 9.0).unwrap();
+        assert_eq!(eq.values[4..6], [95, 180]);
+        assert!(eq.set_gain_db(8, 0.0).is_err());
+        assert_eq!(MonoEQ::from_gains_db(&eq.gains_db()).values[3], 60);
+    }
+
+    #[test]
+    fn should_compute_frequency_response() {
+        let bands = [100, 200, 400, 800, 1600, 3200, 6400, 12800];
+        let flat = MonoEQ::from_gains_db(&[0.0; 8]).frequency_response(&bands, true, 32);
+        assert_eq!(flat.len(), 32);
+        assert_eq!(flat[0].frequency, 20.0);
+        assert!((flat[31].frequency - 20000.0).abs() < 1.0);
+        assert!(flat.iter().all(|point| point.gain.abs() < 0.01));
+
+        let mut eq = MonoEQ::from_gains_db(&[0.0; 8]);
+        eq.set_gain_db(3, 6.0).unwrap();
+        let response = eq.frequency_response(&bands, false, 64);
+        let peak = response
+            .iter()
+            .max_by(|a, b| a.gain.total_cmp(&b.gain))
+            .unwrap();
+        assert!((peak.frequency - 800.0).abs() < 100.0);
+        assert!((peak.gain - 6.0).abs() < 0.5);
     }
 }
@@ -43,6 +43,22 @@ impl EQConfiguration {
         })
     }
 
+    /// Separate curves for each channel, only supported by devices with 2 EQ channels
+    pub fn stereo_custom(left: MonoEQ, right: MonoEQ) -> Self {
+        EQConfiguration::Stereo(StereoEQConfiguration {
+            eq: StereoEQ { left, right },
+            profile: EQProfile::Custom,
+        })
+    }
+
+    /// Whether the left and right channels use different curves
+    pub fn is_per_channel(&self) -> bool {
+        match self {
+            EQConfiguration::Stereo(config) => config.eq.left != config.eq.right,
+            EQConfiguration::Mono(_) => false,
+        }
+    }
+
     pub fn get_profile(&self) -> EQProfile {
         match self {
             EQConfiguration::Stereo(config) => config.profile,
@@ -118,3 +134,29 @@ impl From<EQConfiguration> for MonoEQConfiguration {
         }
     }
 }
+
+#[cfg(test)]
+mod eq_configuration_tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_separate_channels_for_stereo_custom() {
+        let left = MonoEQ::from_signed_bytes(vec![10, 20, 30, 40, 50, 60, 70, 80]);
+        let right = MonoEQ::from_signed_bytes(vec![-10, -20, -30, -40, -50, -60, -70, -80]);
+        let config = EQConfiguration::stereo_custom(left.clone(), right.clone());
+
+        assert_eq!(config.get_profile(), EQProfile::Custom);
+        assert!(config.is_per_channel());
+        let stereo: StereoEQConfiguration = config.into();
+        assert_eq!(stereo.eq.left, left);
+        assert_eq!(stereo.eq.right, right);
+    }
+
+    #[test]
+    fn should_not_be_per_channel_for_equal_curves() {
+        let eq = MonoEQ::from_signed_bytes(vec![10, 20, 30, 40, 50, 60, 70, 80]);
+        assert!(!EQConfiguration::stereo_custom(eq.clone(), eq.clone()).is_per_channel());
+        assert!(!EQConfiguration::mono_custom(eq).is_per_channel());
+        assert!(!EQConfiguration::stereo_with_profile(EQProfile::Acoustic).is_per_channel());
+    }
+}
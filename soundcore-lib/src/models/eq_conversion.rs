@@ -0,0 +1,484 @@
+use std::f32::consts::PI;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+
+use super::{EQConfiguration, MonoEQ, MonoEQConfiguration};
+
+/// Center frequencies of the equalizer bands in Hz, shared by all 8 band models
+pub const EQ_BAND_FREQUENCIES: [u32; 8] = [100, 200, 400, 800, 1600, 3200, 6400, 12800];
+
+/// The sample rate used to evaluate the parametric filters
+const SAMPLE_RATE: f32 = 48000.0;
+/// Q of a peaking filter with a bandwidth of one octave, matching the band spacing
+pub(crate) const OCTAVE_Q: f32 = std::f32::consts::SQRT_2;
+const DEFAULT_SHELF_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum EQFileFormat {
+    /// Equalizer APO/AutoEq parametric filters, e.g. `Filter 1: ON PK Fc 105 Hz Gain -2.3 dB Q 0.70`
+    Parametric,
+    /// Equalizer APO/AutoEq graphic EQ, e.g. `GraphicEQ: 20 -1.5; 21 -1.5; ...`
+    Graphic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParametricFilterKind {
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParametricFilter {
+    pub kind: ParametricFilterKind,
+    pub frequency: f32,
+    pub gain: f32,
+    pub q: f32,
+}
+
+impl ParametricFilter {
+    /// The gain in dB of the filter at the given frequency, using the RBJ audio EQ cookbook biquads
+    pub fn gain_at(&self, frequency: f32) -> f32 {
+        let a = 10f32.powf(self.gain / 40.0);
+        let w0 = 2.0 * PI * self.frequency / SAMPLE_RATE;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * self.q);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        let (numerator, denominator) = match self.kind {
+            ParametricFilterKind::Peaking => (
+                [1.0 + alpha * a, -2.0 * cos_w0, 1.0 - alpha * a],
+                [1.0 + alpha / a, -2.0 * cos_w0, 1.0 - alpha / a],
+            ),
+            ParametricFilterKind::LowShelf => (
+                [
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha),
+                ],
+                [
+                    (a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    (a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha,
+                ],
+            ),
+            ParametricFilterKind::HighShelf => (
+                [
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha),
+                ],
+                [
+                    (a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    (a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha,
+                ],
+            ),
+        };
+
+        let w = 2.0 * PI * frequency / SAMPLE_RATE;
+        let magnitude = |c: [f32; 3]| {
+            let re = c[0] + c[1] * w.cos() + c[2] * (2.0 * w).cos();
+            let im = -c[1] * w.sin() - c[2] * (2.0 * w).sin();
+            (re * re + im * im).sqrt()
+        };
+        20.0 * (magnitude(numerator) / magnitude(denominator)).log10()
+    }
+}
+
+/// A set of parametric filters in the Equalizer APO format used by AutoEq's ParametricEQ.txt
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParametricEQ {
+    pub preamp: f32,
+    pub filters: Vec<ParametricFilter>,
+}
+
+impl ParametricEQ {
+    pub fn parse(input: &str) -> SoundcoreLibResult<Self> {
+        let mut eq = ParametricEQ::default();
+        for line in input.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(invalid_preset(format!("Unexpected line: {line}")));
+            };
+            if key.eq_ignore_ascii_case("preamp") {
+                eq.preamp = parse_number(value.trim().trim_end_matches("dB"))?;
+            } else if key.starts_with("Filter") {
+                if let Some(filter) = Self::parse_filter(value)? {
+                    eq.filters.push(filter);
+                }
+            } else {
+                warn!("Ignoring unsupported EQ line: {line}");
+            }
+        }
+
+        if eq.filters.is_empty() {
+            return Err(invalid_preset("No filters found".to_string()));
+        }
+        Ok(eq)
+    }
+
+    /// Parses the part after `Filter N:`, returns None for disabled or unsupported filters
+    fn parse_filter(value: &str) -> SoundcoreLibResult<Option<ParametricFilter>> {
+        let tokens = value.split_whitespace().collect::<Vec<_>>();
+        let (enabled, kind) = match tokens.as_slice() {
+            [enabled, kind, ..] => (*enabled, *kind),
+            _ => return Err(invalid_preset(format!("Invalid filter: {value}"))),
+        };
+        if !enabled.eq_ignore_ascii_case("ON") {
+            return Ok(None);
+        }
+        let kind = match kind.to_ascii_uppercase().as_str() {
+            "PK" | "PEQ" => ParametricFilterKind::Peaking,
+            "LS" | "LSC" => ParametricFilterKind::LowShelf,
+            "HS" | "HSC" => ParametricFilterKind::HighShelf,
+            _ => {
+                warn!("Ignoring unsupported filter type {kind}");
+                return Ok(None);
+            }
+        };
+
+        let param = |name: &str| -> SoundcoreLibResult<Option<f32>> {
+            tokens
+                .iter()
+                .position(|token| token.eq_ignore_ascii_case(name))
+                .and_then(|idx| tokens.get(idx + 1))
+                .map(|value| parse_number(value))
+                .transpose()
+        };
+
+        let frequency = param("Fc")?.ok_or(invalid_preset(format!("Missing Fc: {value}")))?;
+        let gain = param("Gain")?.ok_or(invalid_preset(format!("Missing gain: {value}")))?;
+        let q = match (param("Q")?, kind) {
+            (Some(q), _) => q,
+            (None, ParametricFilterKind::Peaking) => {
+                return Err(invalid_preset(format!("Missing Q: {value}")));
+            }
+            (None, _) => DEFAULT_SHELF_Q,
+        };
+        if frequency <= 0.0 || q <= 0.0 {
+            return Err(invalid_preset(format!("Invalid filter: {value}")));
+        }
+
+        Ok(Some(ParametricFilter {
+            kind,
+            frequency,
+            gain,
+            q,
+        }))
+    }
+
+    /// The combined gain of all filters, excluding the preamp
+    pub fn gain_at(&self, frequency: f32) -> f32 {
+        self.filters
+            .iter()
+            .map(|filter| filter.gain_at(frequency))
+            .sum()
+    }
+
+    /// Approximates the curve with a peaking filter per band
+    pub fn from_band_gains(band_frequencies: &[u32], gains: &[f32]) -> Self {
+        let max_gain = gains.iter().copied().fold(0.0, f32::max);
+        Self {
+            preamp: -max_gain,
+            filters: gains
+                .iter()
+                .zip(band_frequencies)
+                .map(|(&gain, &frequency)| ParametricFilter {
+                    kind: ParametricFilterKind::Peaking,
+                    frequency: frequency as f32,
+                    gain,
+                    q: OCTAVE_Q,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_apo_string(&self) -> String {
+        let mut lines = vec![format!("Preamp: {:.1} dB", self.preamp)];
+        for (idx, filter) in self.filters.iter().enumerate() {
+            let kind = match filter.kind {
+                ParametricFilterKind::Peaking => "PK",
+                ParametricFilterKind::LowShelf => "LSC",
+                ParametricFilterKind::HighShelf => "HSC",
+            };
+            lines.push(format!(
+                "Filter {}: ON {} Fc {} Hz Gain {:.1} dB Q {:.2}",
+                idx + 1,
+                kind,
+                filter.frequency,
+                filter.gain,
+                filter.q
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// A graphic EQ in the Equalizer APO format used by AutoEq's GraphicEQ.txt
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GraphicEQ {
+    /// Frequency and gain pairs, sorted by frequency
+    pub points: Vec<(f32, f32)>,
+}
+
+impl GraphicEQ {
+    pub fn parse(input: &str) -> SoundcoreLibResult<Self> {
+        let line = input
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("GraphicEQ:"))
+            .ok_or(invalid_preset("Missing GraphicEQ line".to_string()))?;
+
+        let mut points = line
+            .trim_start_matches("GraphicEQ:")
+            .split(';')
+            .map(str::trim)
+            .filter(|point| !point.is_empty())
+            .map(
+                |point| match point.split_whitespace().collect::<Vec<_>>()[..] {
+                    [frequency, gain] => Ok((parse_number(frequency)?, parse_number(gain)?)),
+                    _ => Err(invalid_preset(format!("Invalid point: {point}"))),
+                },
+            )
+            .collect::<SoundcoreLibResult<Vec<_>>>()?;
+
+        if points.is_empty() {
+            return Err(invalid_preset("No points found".to_string()));
+        }
+        if points.iter().any(|(frequency, _)| *frequency <= 0.0) {
+            return Err(invalid_preset("Frequencies must be positive".to_string()));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { points })
+    }
+
+    /// Interpolates linearly on a logarithmic frequency scale, like Equalizer APO does
+    pub fn gain_at(&self, frequency: f32) -> f32 {
+        let Some(upper) = self.points.iter().position(|(f, _)| *f >= frequency) else {
+            return self.points.last().map_or(0.0, |(_, gain)| *gain);
+        };
+        if upper == 0 {
+            return self.points[0].1;
+        }
+        let (f0, g0) = self.points[upper - 1];
+        let (f1, g1) = self.points[upper];
+        let t = (frequency.ln() - f0.ln()) / (f1.ln() - f0.ln());
+        g0 + (g1 - g0) * t
+    }
+
+    pub fn from_band_gains(band_frequencies: &[u32], gains: &[f32]) -> Self {
+        Self {
+            points: band_frequencies
+                .iter()
+                .map(|&frequency| frequency as f32)
+                .zip(gains.iter().copied())
+                .collect(),
+        }
+    }
+
+    pub fn to_apo_string(&self) -> String {
+        let points = self
+            .points
+            .iter()
+            .map(|(frequency, gain)| format!("{} {:.1}", frequency, gain))
+            .collect::<Vec<_>>();
+        format!("GraphicEQ: {}", points.join("; "))
+    }
+}
+
+/// Parses an Equalizer APO/AutoEq file and fits its curve onto the device's bands,
+/// see EQ_BAND_FREQUENCIES
+pub fn import_eq(
+    input: &str,
+    format: EQFileFormat,
+    band_frequencies: &[u32],
+) -> SoundcoreLibResult<EQConfiguration> {
+    let gains = match format {
+        EQFileFormat::Parametric => {
+            let eq = ParametricEQ::parse(input)?;
+            band_gains(|frequency| eq.gain_at(frequency), band_frequencies)
+        }
+        EQFileFormat::Graphic => {
+            let eq = GraphicEQ::parse(input)?;
+            band_gains(|frequency| eq.gain_at(frequency), band_frequencies)
+        }
+    };
+    Ok(EQConfiguration::mono_custom(MonoEQ::from_gains_db(&gains)))
+}
+
+/// Exports the curve of the left channel in the given format
+pub fn export_eq(eq: &EQConfiguration, format: EQFileFormat, band_frequencies: &[u32]) -> String {
+    let config: MonoEQConfiguration = eq.to_owned().into();
+    let gains = config.eq.gains_db();
+    match format {
+        EQFileFormat::Parametric => {
+            ParametricEQ::from_band_gains(band_frequencies, &gains).to_apo_string()
+        }
+        EQFileFormat::Graphic => {
+            GraphicEQ::from_band_gains(band_frequencies, &gains).to_apo_string()
+        }
+    }
+}
+
+/// Samples the curve at the band centers and fits the result within the device's range.
+/// Curves which don't fit are centered first, so only their extremes are clipped.
+fn band_gains(curve: impl Fn(f32) -> f32, band_frequencies: &[u32]) -> Vec<f32> {
+    let max_gain = MonoEQ::MAX_GAIN_DB - MonoEQ::MIN_GAIN_DB;
+    let gains = band_frequencies
+        .iter()
+        .map(|&frequency| curve(frequency as f32))
+        .collect::<Vec<_>>();
+
+    let (min, max) = gains.iter().fold((f32::MAX, f32::MIN), |(min, max), &g| {
+        (min.min(g), max.max(g))
+    });
+    let offset = if min >= -max_gain / 2.0 && max <= max_gain / 2.0 {
+        0.0
+    } else {
+        -(min + max) / 2.0
+    };
+    gains
+        .into_iter()
+        .map(|gain| (gain + offset).clamp(-max_gain / 2.0, max_gain / 2.0))
+        .collect()
+}
+
+fn parse_number(value: &str) -> SoundcoreLibResult<f32> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid_preset(format!("Invalid number: {value}")))
+}
+
+fn invalid_preset(reason: String) -> SoundcoreLibError {
+    SoundcoreLibError::InvalidEQPreset(reason)
+}
+
+#[cfg(test)]
+mod eq_conversion_tests {
+    use crate::models::EQProfile;
+
+    use super::*;
+
+    const BANDS: [u32; 8] = [100, 200, 400, 800, 1600, 3200, 6400, 12800];
+
+    const AUTOEQ_PARAMETRIC: &str = "Preamp: -6.4 dB
+Filter 1: ON LSC Fc 105 Hz Gain 5.5 dB Q 0.70
+Filter 2: ON PK Fc 160 Hz Gain -2.1 dB Q 0.58
+Filter 3: ON PK Fc 3110 Hz Gain 4.0 dB Q 2.17
+Filter 4: OFF PK Fc 5000 Hz Gain 9.0 dB Q 1.00
+Filter 5: ON HSC Fc 10000 Hz Gain -3.0 dB Q 0.70";
+
+    fn mono_values(config: &EQConfiguration) -> Vec<u8> {
+        MonoEQConfiguration::from(config.to_owned()).eq.values
+    }
+
+    #[test]
+    fn should_parse_parametric_eq() {
+        let eq = ParametricEQ::parse(AUTOEQ_PARAMETRIC).unwrap();
+        assert_eq!(eq.preamp, -6.4);
+        assert_eq!(eq.filters.len(), 4);
+        assert_eq!(
+            eq.filters[0],
+            ParametricFilter {
+                kind: ParametricFilterKind::LowShelf,
+                frequency: 105.0,
+                gain: 5.5,
+                q: 0.70
+            }
+        );
+        assert_eq!(eq.filters[3].kind, ParametricFilterKind::HighShelf);
+    }
+
+    #[test]
+    fn should_evaluate_filter_gain() {
+        let peak = ParametricFilter {
+            kind: ParametricFilterKind::Peaking,
+            frequency: 1000.0,
+            gain: 6.0,
+            q: 1.0,
+        };
+        assert!((peak.gain_at(1000.0) - 6.0).abs() < 0.01);
+        assert!(peak.gain_at(20.0).abs() < 0.1);
+
+        let shelf = ParametricFilter {
+            kind: ParametricFilterKind::LowShelf,
+            frequency: 100.0,
+            gain: -4.0,
+            q: DEFAULT_SHELF_Q,
+        };
+        assert!((shelf.gain_at(20.0) + 4.0).abs() < 0.2);
+        assert!(shelf.gain_at(10000.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn should_reject_invalid_parametric_eq() {
+        assert!(ParametricEQ::parse("Preamp: -6.4 dB").is_err());
+        assert!(ParametricEQ::parse("Filter 1: ON PK Fc 100 Hz Gain 1.0 dB").is_err());
+        assert!(ParametricEQ::parse("Filter 1: ON PK Fc abc Hz Gain 1.0 dB Q 1.0").is_err());
+        assert!(ParametricEQ::parse("not an eq").is_err());
+    }
+
+    #[test]
+    fn should_import_parametric_eq_within_range() {
+        let config = import_eq(AUTOEQ_PARAMETRIC, EQFileFormat::Parametric, &BANDS).unwrap();
+        let values = mono_values(&config);
+        assert_eq!(values.len(), 8);
+        assert!(values.iter().all(|v| (60..=180).contains(v)));
+        // The low shelf boosts the first band, the high shelf cuts the last one
+        assert!(values[0] > 120);
+        assert!(values[7] < 120);
+    }
+
+    #[test]
+    fn should_center_curves_exceeding_the_range() {
+        let input = "GraphicEQ: 20 10; 150 10; 300 12; 1000 14; 5000 16; 20000 18";
+        let config = import_eq(input, EQFileFormat::Graphic, &BANDS).unwrap();
+        let values = mono_values(&config);
+        // The curve spans ~7.4dB, so it's shifted down to fit instead of being clipped
+        assert_eq!(values[0], 83);
+        assert_eq!(values[7], 157);
+        assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn should_interpolate_graphic_eq() {
+        let eq = GraphicEQ::parse("GraphicEQ: 100 0; 400 -4; 20 2").unwrap();
+        assert_eq!(eq.points[0], (20.0, 2.0));
+        assert_eq!(eq.gain_at(10.0), 2.0);
+        assert!((eq.gain_at(200.0) + 2.0).abs() < 0.01);
+        assert_eq!(eq.gain_at(30000.0), -4.0);
+    }
+
+    #[test]
+    fn should_roundtrip_graphic_eq_export() {
+        let config = EQConfiguration::mono_custom(EQProfile::Acoustic.eq());
+        let exported = export_eq(&config, EQFileFormat::Graphic, &BANDS);
+        assert!(exported.starts_with("GraphicEQ: 100 "));
+
+        let imported = import_eq(&exported, EQFileFormat::Graphic, &BANDS).unwrap();
+        assert_eq!(mono_values(&imported), EQProfile::Acoustic.eq().values);
+    }
+
+    #[test]
+    fn should_export_parametric_eq() {
+        let config = EQConfiguration::mono_custom(MonoEQ::from_signed_bytes(vec![
+            30, 0, 0, 0, 0, 0, 0, -20,
+        ]));
+        let exported = export_eq(&config, EQFileFormat::Parametric, &BANDS);
+        let lines = exported.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Preamp: -3.0 dB");
+        assert_eq!(lines[1], "Filter 1: ON PK Fc 100 Hz Gain 3.0 dB Q 1.41");
+        assert_eq!(lines[8], "Filter 8: ON PK Fc 12800 Hz Gain -2.0 dB Q 1.41");
+        assert_eq!(ParametricEQ::parse(&exported).unwrap().filters.len(), 8);
+    }
+}
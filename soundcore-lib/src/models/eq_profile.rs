@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumCount, EnumIter, EnumString, FromRepr};
+use strum::{Display, EnumCount, EnumIter, EnumString, FromRepr, IntoEnumIterator};
 use typeshare::typeshare;
 
 use super::MonoEQ;
@@ -73,6 +73,18 @@ impl EQProfile {
         Self::from_repr(id.to_be())
     }
 
+    /// Whether this is one of the artist-tuned profiles
+    pub fn is_professional(&self) -> bool {
+        (self.id() & 0x00FF) == 0x00EE
+    }
+
+    /// The firmware presets with a known curve, excluding Custom
+    pub fn regular_profiles() -> Vec<EQProfile> {
+        Self::iter()
+            .filter(|profile| *profile != Self::Custom && !profile.is_professional())
+            .collect()
+    }
+
     pub fn eq(&self) -> MonoEQ {
         let eq: [i8; 8] = match self {
             Self::SoundcoreSignature => [0, 0, 0, 0, 0, 0, 0, 0],
@@ -97,8 +109,37 @@ impl EQProfile {
             Self::SpokenWord => [-30, -20, 10, 20, 20, 10, 0, -30],
             Self::TrebleBooster => [-20, -20, -20, -10, 10, 20, 20, 40],
             Self::TrebleReducer => [0, 0, 0, -20, -30, -40, -40, -60],
-            _ => [0, 0, 0, 0, 0, 0, 0, 0],
+            Self::Custom => [0, 0, 0, 0, 0, 0, 0, 0],
+            // The artist curves are unknown, which is why set_eq rejects these presets
+            Self::Foxes
+            | Self::Halestorm
+            | Self::Lecrae
+            | Self::Daya
+            | Self::CedricGervais
+            | Self::TheInfamousStringdusters
+            | Self::JohnPaulWhite => [0, 0, 0, 0, 0, 0, 0, 0],
         };
         MonoEQ::from_signed_bytes(eq.into())
     }
 }
+
+#[cfg(test)]
+mod eq_profile_tests {
+    use super::*;
+
+    #[test]
+    fn should_identify_professional_profiles() {
+        assert!(EQProfile::Foxes.is_professional());
+        assert!(EQProfile::JohnPaulWhite.is_professional());
+        assert!(!EQProfile::SoundcoreSignature.is_professional());
+        assert!(!EQProfile::Custom.is_professional());
+    }
+
+    #[test]
+    fn should_exclude_custom_and_professional_from_regular_profiles() {
+        let profiles = EQProfile::regular_profiles();
+        assert_eq!(profiles.len(), 22);
+        assert!(!profiles.contains(&EQProfile::Custom));
+        assert!(!profiles.iter().any(EQProfile::is_professional));
+    }
+}
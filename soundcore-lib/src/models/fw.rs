@@ -21,6 +21,12 @@ impl DeviceFirmware {
     pub fn secondary(&self) -> Option<FirmwareVer> {
         self.secondary
     }
+
+    /// Both earbuds of a TWS device should run the same firmware
+    pub fn is_mismatched(&self) -> bool {
+        self.secondary
+            .is_some_and(|secondary| secondary != self.primary)
+    }
 }
 
 #[derive(
@@ -77,4 +83,14 @@ mod fw_tests {
         let old = FirmwareVer::new(1, 21);
         assert!(new > old);
     }
+
+    #[test]
+    fn should_detect_mismatch() {
+        let new = FirmwareVer::new(1, 22);
+        let old = FirmwareVer::new(1, 21);
+        let fw = DeviceFirmware::new(new, Some(old));
+        assert!(fw.is_mismatched());
+        assert!(!DeviceFirmware::new(new, Some(new)).is_mismatched());
+        assert!(!DeviceFirmware::new(new, None).is_mismatched());
+    }
 }
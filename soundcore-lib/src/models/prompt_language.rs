@@ -1,9 +1,21 @@
 use serde::{Deserialize, Serialize};
-use strum::FromRepr;
+use strum::{Display, EnumIter, FromRepr};
 use typeshare::typeshare;
 
 #[derive(
-    Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Hash, FromRepr,
+    Debug,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Hash,
+    FromRepr,
+    EnumIter,
+    Display,
 )]
 #[repr(u8)]
 #[typeshare]
@@ -11,3 +23,9 @@ pub enum PromptLanguage {
     English = 0x00,
     Chinese = 0x01,
 }
+
+impl PromptLanguage {
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -3,7 +3,7 @@ use typeshare::typeshare;
 
 use crate::models::custom_trans_value::CustomTransparencyValue;
 
-use super::{ANCMode, CurrentSoundMode, CustomANCValue, TransparencyMode};
+use super::{ANCMode, ANCStrengthMode, CurrentSoundMode, CustomANCValue, TransparencyMode};
 
 #[derive(
     Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Default, Hash,
@@ -17,9 +17,18 @@ pub struct SoundMode {
     pub trans_mode: TransparencyMode,
     pub custom_anc: CustomANCValue,
     pub custom_trans: Option<CustomTransparencyValue>,
+    /// Only part of the customizable transparency layout, like the fields below.
+    /// See SoundModeFeatures for whether a model supports them.
+    #[serde(default)]
+    pub auto_anc: bool,
+    #[serde(default)]
+    pub anc_strength_mode: ANCStrengthMode,
 }
 
 impl SoundMode {
+    /// Shares the custom ANC byte with the custom ANC value
+    pub const AUTO_ANC_BIT: u8 = 0x01;
+
     pub fn to_bytes(&self) -> [u8; 4] {
         [
             self.current.as_u8(),
@@ -32,11 +41,18 @@ impl SoundMode {
     pub fn to_bytes_with_custom_transparency(&self) -> [u8; 6] {
         [
             self.current.as_u8(),
-            (self.custom_anc.as_u8() << 4) | 0x01, // TODO: 0x01 is the bit for auto ANC, some new devices support it
+            (self.custom_anc.as_u8() << 4) | self.anc_flags(),
             self.trans_mode.as_u8(),
             self.anc_mode.as_u8(),
-            0x00,
+            self.anc_strength_mode.as_u8(),
             self.custom_trans.unwrap_or_default().as_u8(),
         ]
     }
+
+    fn anc_flags(&self) -> u8 {
+        match self.auto_anc {
+            true => Self::AUTO_ANC_BIT,
+            false => 0,
+        }
+    }
 }
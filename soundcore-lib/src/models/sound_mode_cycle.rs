@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+
+/// The sound modes the physical button cycles through
+// TODO: Verify the bit layout against captures of devices supporting this
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SoundModeCycle {
+    pub anc: bool,
+    pub transparency: bool,
+    pub normal: bool,
+}
+
+impl Default for SoundModeCycle {
+    fn default() -> Self {
+        Self {
+            anc: true,
+            transparency: true,
+            normal: true,
+        }
+    }
+}
+
+impl SoundModeCycle {
+    const ANC_BIT: u8 = 0x01;
+    const TRANSPARENCY_BIT: u8 = 0x02;
+    const NORMAL_BIT: u8 = 0x04;
+    /// The button has to switch between at least two modes
+    const MIN_MODES: usize = 2;
+
+    pub fn from_byte(value: u8) -> Self {
+        Self {
+            anc: value & Self::ANC_BIT != 0,
+            transparency: value & Self::TRANSPARENCY_BIT != 0,
+            normal: value & Self::NORMAL_BIT != 0,
+        }
+    }
+
+    pub fn as_byte(&self) -> u8 {
+        [
+            (self.anc, Self::ANC_BIT),
+            (self.transparency, Self::TRANSPARENCY_BIT),
+            (self.normal, Self::NORMAL_BIT),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |byte, (_, bit)| byte | bit)
+    }
+
+    pub fn validate(&self) -> SoundcoreLibResult<()> {
+        let modes = [self.anc, self.transparency, self.normal]
+            .into_iter()
+            .filter(|enabled| *enabled)
+            .count();
+        if modes < Self::MIN_MODES {
+            return Err(SoundcoreLibError::FeatureNotSupported(format!(
+                "Cycling through less than {} sound modes",
+                Self::MIN_MODES
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sound_mode_cycle_tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_bytes() {
+        let cycle = SoundModeCycle {
+            normal: false,
+            ..Default::default()
+        };
+        assert_eq!(cycle.as_byte(), 0x03);
+        assert_eq!(SoundModeCycle::from_byte(cycle.as_byte()), cycle);
+        assert_eq!(SoundModeCycle::default().as_byte(), 0x07);
+    }
+
+    #[test]
+    fn should_require_two_modes() {
+        assert!(SoundModeCycle::default().validate().is_ok());
+        let cycle = SoundModeCycle {
+            anc: true,
+            transparency: false,
+            normal: false,
+        };
+        assert!(cycle.validate().is_err());
+    }
+}
@@ -0,0 +1,15 @@
+//! Firmware updates over the regular command channel.
+//! TODO: The command ids and payload layouts still need to be verified against captures of the vendor app.
+
+mod image;
+mod packets;
+mod updater;
+
+pub use image::*;
+pub use packets::*;
+pub use updater::*;
+
+#[cfg(test)]
+mod fake_device;
+#[cfg(test)]
+mod updater_tests;
@@ -0,0 +1,171 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use tokio::sync::mpsc;
+
+use crate::ble::{BLEConnection, BLEDeviceDescriptor, WriteType};
+use crate::btaddr::BluetoothAdrr;
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::ota::{crc32, OTA_CHUNK_ACK, OTA_READY, OTA_STATUS_OK, OTA_VERIFY_RESULT};
+use crate::parsers::generate_checksum;
+
+const STATUS_ERROR: u8 = 0x01;
+
+#[derive(Debug, Default)]
+struct FakeOtaState {
+    responses: Option<mpsc::Sender<Vec<u8>>>,
+    /// The size and CRC of the image being transferred
+    expected: Option<(u32, u32)>,
+    image: Vec<u8>,
+    resume_offsets: Vec<u32>,
+    drop_chunk_response_after: Option<usize>,
+    reject_chunk_once: Option<u32>,
+    rebooted: bool,
+    aborted: bool,
+}
+
+/// An in-process device speaking the OTA protocol.
+/// Every byte_channel call behaves like a new connection to the same device,
+/// the partially received image is kept until the device is aborted.
+#[derive(Debug, Default)]
+pub struct FakeOtaDevice {
+    state: Mutex<FakeOtaState>,
+}
+
+impl FakeOtaDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops answering after `chunks` chunks, like a dropped connection
+    pub fn drop_chunk_response_after(&self, chunks: usize) {
+        self.state.lock().unwrap().drop_chunk_response_after = Some(chunks);
+    }
+
+    /// Reports a CRC error for the first chunk sent at `offset`
+    pub fn reject_chunk_once(&self, offset: u32) {
+        self.state.lock().unwrap().reject_chunk_once = Some(offset);
+    }
+
+    pub fn received_image(&self) -> Vec<u8> {
+        self.state.lock().unwrap().image.to_owned()
+    }
+
+    /// The offsets reported at the start of each transfer
+    pub fn resume_offsets(&self) -> Vec<u32> {
+        self.state.lock().unwrap().resume_offsets.to_owned()
+    }
+
+    pub fn is_rebooted(&self) -> bool {
+        self.state.lock().unwrap().rebooted
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.state.lock().unwrap().aborted
+    }
+
+    fn handle(&self, id: u8, payload: &[u8]) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        match id {
+            0x81 => {
+                let size = u32::from_le_bytes(payload[0..4].try_into().ok()?);
+                let crc = u32::from_le_bytes(payload[4..8].try_into().ok()?);
+                if state.expected != Some((size, crc)) {
+                    state.expected = Some((size, crc));
+                    state.image.clear();
+                }
+                let offset = state.image.len() as u32;
+                state.resume_offsets.push(offset);
+                Some(response(OTA_READY, &offset.to_le_bytes()))
+            }
+            0x82 => {
+                let offset = u32::from_le_bytes(payload[0..4].try_into().ok()?);
+                let crc = u32::from_le_bytes(payload[4..8].try_into().ok()?);
+                let data = &payload[8..];
+                match state.drop_chunk_response_after {
+                    Some(0) => {
+                        state.drop_chunk_response_after = None;
+                        return None;
+                    }
+                    Some(chunks) => state.drop_chunk_response_after = Some(chunks - 1),
+                    None => {}
+                }
+                let rejected = state.reject_chunk_once == Some(offset);
+                if rejected {
+                    state.reject_chunk_once = None;
+                }
+                let ok = !rejected && crc32(data) == crc && offset as usize == state.image.len();
+                if ok {
+                    state.image.extend_from_slice(data);
+                }
+                let status = if ok { OTA_STATUS_OK } else { STATUS_ERROR };
+                Some(response(
+                    OTA_CHUNK_ACK,
+                    &[&offset.to_le_bytes()[..], &[status]].concat(),
+                ))
+            }
+            0x83 => {
+                let ok = state.expected == Some((state.image.len() as u32, crc32(&state.image)));
+                let status = if ok { OTA_STATUS_OK } else { STATUS_ERROR };
+                Some(response(OTA_VERIFY_RESULT, &[status]))
+            }
+            0x84 => {
+                state.rebooted = true;
+                None
+            }
+            0x85 => {
+                state.aborted = true;
+                state.expected = None;
+                state.image.clear();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn response(kind: [u8; 2], payload: &[u8]) -> Vec<u8> {
+    let length = (5 + kind.len() + 2 + payload.len() + 1) as u16;
+    let mut bytes = [
+        &[0x09, 0xff, 0x00, 0x00, 0x01][..],
+        &kind,
+        &length.to_le_bytes(),
+        payload,
+    ]
+    .concat();
+    bytes.push(generate_checksum(&bytes));
+    bytes
+}
+
+impl BLEConnection for FakeOtaDevice {
+    fn descriptor(&self) -> BLEDeviceDescriptor {
+        BLEDeviceDescriptor {
+            addr: BluetoothAdrr::from_str("00:11:22:33:44:66").unwrap(),
+            name: "Fake OTA Device".to_string(),
+        }
+    }
+
+    async fn byte_channel(&self) -> SoundcoreLibResult<mpsc::Receiver<Vec<u8>>> {
+        let (sender, receiver) = mpsc::channel(16);
+        self.state.lock().unwrap().responses = Some(sender);
+        Ok(receiver)
+    }
+
+    async fn write(&self, bytes: &[u8], _write_type: WriteType) -> SoundcoreLibResult<()> {
+        // Command prefix, command id, length and checksum
+        if bytes.len() < 10 || bytes[..6] != [0x08, 0xee, 0x00, 0x00, 0x00, 0x0f] {
+            return Err(SoundcoreLibError::InvalidArguments);
+        }
+        let Some(response) = self.handle(bytes[6], &bytes[9..bytes.len() - 1]) else {
+            return Ok(());
+        };
+        let sender = self.state.lock().unwrap().responses.to_owned();
+        if let Some(sender) = sender {
+            sender
+                .send(response)
+                .await
+                .map_err(|_| SoundcoreLibError::SendError)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+
+/// A firmware image, sent to the device as is
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FirmwareImage {
+    bytes: Vec<u8>,
+    crc: u32,
+}
+
+impl FirmwareImage {
+    pub fn from_bytes(bytes: Vec<u8>) -> SoundcoreLibResult<Self> {
+        if bytes.is_empty() || u32::try_from(bytes.len()).is_err() {
+            return Err(SoundcoreLibError::Ota(format!(
+                "Invalid firmware image size: {}",
+                bytes.len()
+            )));
+        }
+        let crc = crc32(&bytes);
+        Ok(Self { bytes, crc })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> SoundcoreLibResult<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn size(&self) -> u32 {
+        self.bytes.len() as u32
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+}
+
+/// CRC-32/ISO-HDLC, as used by zip
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod image_tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[test]
+    fn should_reject_empty_images() {
+        assert!(FirmwareImage::from_bytes(vec![]).is_err());
+        let image = FirmwareImage::from_bytes(b"123456789".to_vec()).unwrap();
+        assert_eq!(image.size(), 9);
+        assert_eq!(image.crc(), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,159 @@
+use nom::{
+    bytes::complete::take,
+    combinator::map,
+    error::VerboseError,
+    number::complete::{le_u16, le_u32, le_u8},
+    sequence::tuple,
+};
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::ota::crc32;
+use crate::packets::Packet;
+use crate::parsers::{parse_and_check_checksum, ParseResult};
+
+pub(crate) const OTA_READY: [u8; 2] = [0x0f, 0x01];
+pub(crate) const OTA_CHUNK_ACK: [u8; 2] = [0x0f, 0x02];
+pub(crate) const OTA_VERIFY_RESULT: [u8; 2] = [0x0f, 0x03];
+pub(crate) const OTA_STATUS_OK: u8 = 0x00;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum OtaCommand {
+    /// Starts or resumes the transfer of an image
+    Start {
+        size: u32,
+        crc: u32,
+        chunk_size: u16,
+    },
+    /// Each chunk carries the CRC of its data
+    Chunk {
+        offset: u32,
+        data: Vec<u8>,
+    },
+    Verify,
+    Reboot,
+    /// Discards the partially transferred image
+    Abort,
+}
+
+impl OtaCommand {
+    fn id(&self) -> u8 {
+        match self {
+            OtaCommand::Start { .. } => 0x81,
+            OtaCommand::Chunk { .. } => 0x82,
+            OtaCommand::Verify => 0x83,
+            OtaCommand::Reboot => 0x84,
+            OtaCommand::Abort => 0x85,
+        }
+    }
+}
+
+impl Packet for OtaCommand {
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x0f, self.id()]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        match self {
+            OtaCommand::Start {
+                size,
+                crc,
+                chunk_size,
+            } => [
+                &size.to_le_bytes()[..],
+                &crc.to_le_bytes(),
+                &chunk_size.to_le_bytes(),
+            ]
+            .concat(),
+            OtaCommand::Chunk { offset, data } => {
+                [&offset.to_le_bytes()[..], &crc32(data).to_le_bytes(), data].concat()
+            }
+            OtaCommand::Verify | OtaCommand::Reboot | OtaCommand::Abort => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum OtaResponse {
+    /// The device is ready to receive the image from the given offset,
+    /// which is non-zero when resuming an interrupted transfer
+    Ready {
+        offset: u32,
+    },
+    ChunkAck {
+        offset: u32,
+        ok: bool,
+    },
+    Verified {
+        ok: bool,
+    },
+}
+
+impl OtaResponse {
+    pub fn from_bytes(bytes: &[u8]) -> SoundcoreLibResult<Self> {
+        let bytes = parse_and_check_checksum::<VerboseError<&[u8]>>(bytes)?.0;
+        // The OTA responses aren't part of PACKET_KIND_MAP, so the header is parsed here
+        let (bytes, (_prefix, kind, _length)) =
+            tuple((take(5usize), take(2usize), le_u16::<_, VerboseError<&[u8]>>))(bytes)?;
+        Ok(match [kind[0], kind[1]] {
+            OTA_READY => parse_ready(bytes)?.1,
+            OTA_CHUNK_ACK => parse_chunk_ack(bytes)?.1,
+            OTA_VERIFY_RESULT => parse_verified(bytes)?.1,
+            _ => return Err(SoundcoreLibError::IncompatibleResponse),
+        })
+    }
+}
+
+fn parse_ready(bytes: &[u8]) -> ParseResult<'_, OtaResponse, VerboseError<&[u8]>> {
+    map(le_u32, |offset| OtaResponse::Ready { offset })(bytes)
+}
+
+fn parse_chunk_ack(bytes: &[u8]) -> ParseResult<'_, OtaResponse, VerboseError<&[u8]>> {
+    map(tuple((le_u32, le_u8)), |(offset, status)| {
+        OtaResponse::ChunkAck {
+            offset,
+            ok: status == OTA_STATUS_OK,
+        }
+    })(bytes)
+}
+
+fn parse_verified(bytes: &[u8]) -> ParseResult<'_, OtaResponse, VerboseError<&[u8]>> {
+    map(le_u8, |status| OtaResponse::Verified {
+        ok: status == OTA_STATUS_OK,
+    })(bytes)
+}
+
+#[cfg(test)]
+mod ota_packets_tests {
+    use crate::parsers::generate_checksum;
+
+    use super::*;
+
+    #[test]
+    fn should_build_chunk_command() {
+        let bytes = OtaCommand::Chunk {
+            offset: 0x0100,
+            data: b"123456789".to_vec(),
+        }
+        .bytes();
+        assert_eq!(bytes[5..7], [0x0f, 0x82]);
+        assert_eq!(bytes[9..13], [0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(bytes[13..17], 0xCBF4_3926u32.to_le_bytes());
+        assert_eq!(&bytes[17..26], b"123456789");
+    }
+
+    #[test]
+    fn should_parse_responses() {
+        let mut bytes = vec![
+            0x09, 0xff, 0x00, 0x00, 0x01, 0x0f, 0x02, 0x0f, 0x00, 0x80, 0x00, 0x00, 0x00, 0x01,
+        ];
+        bytes.push(generate_checksum(&bytes));
+        assert_eq!(
+            OtaResponse::from_bytes(&bytes).unwrap(),
+            OtaResponse::ChunkAck {
+                offset: 0x80,
+                ok: false
+            }
+        );
+        assert!(OtaResponse::from_bytes(&test_data::a3951::A3951_SOUND_MODE_UPDATE_BYTES).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+use typeshare::typeshare;
+
+use manager_fut::ManagerFuture;
+
+use crate::ble::{BLEConnection, WriteType};
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::ota::{FirmwareImage, OtaCommand, OtaResponse};
+use crate::packets::Packet;
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum OtaStage {
+    #[default]
+    Idle,
+    Handshake,
+    Transfer,
+    Verify,
+    Reboot,
+    Done,
+    Aborted,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct OtaProgress {
+    pub stage: OtaStage,
+    /// Bytes acknowledged by the device, including the ones from a resumed transfer
+    pub sent: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct OtaConfig {
+    pub chunk_size: u16,
+    pub response_timeout: Duration,
+    /// How many times a rejected chunk is sent again
+    pub max_retries: u8,
+}
+
+impl Default for OtaConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 128,
+            response_timeout: Duration::from_secs(3),
+            max_retries: 3,
+        }
+    }
+}
+
+/// Stops a running update after the current chunk
+#[derive(Debug, Clone)]
+pub struct OtaAbortHandle(Arc<AtomicBool>);
+
+impl OtaAbortHandle {
+    pub fn abort(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs firmware updates on a connection.
+/// The connection must not be used by a SoundcoreBLEDevice, since the updater needs its byte channel.
+pub struct OtaUpdater<C, F>
+where
+    C: BLEConnection,
+    F: ManagerFuture,
+{
+    connection: Arc<C>,
+    config: OtaConfig,
+    progress: watch::Sender<OtaProgress>,
+    aborted: Arc<AtomicBool>,
+    _future: PhantomData<F>,
+}
+
+impl<C, F> OtaUpdater<C, F>
+where
+    C: BLEConnection,
+    F: ManagerFuture,
+{
+    pub fn new(connection: Arc<C>) -> Self {
+        Self {
+            connection,
+            config: OtaConfig::default(),
+            progress: watch::channel(OtaProgress::default()).0,
+            aborted: Arc::new(AtomicBool::new(false)),
+            _future: PhantomData,
+        }
+    }
+
+    pub fn with_config(mut self, config: OtaConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn progress(&self) -> watch::Receiver<OtaProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Once aborted, the updater can't be used anymore
+    pub fn abort_handle(&self) -> OtaAbortHandle {
+        OtaAbortHandle(self.aborted.to_owned())
+    }
+
+    /// Sends the image, verifies it and reboots the device.
+    /// If the device still holds part of the same image from an interrupted update,
+    /// the transfer is resumed from there.
+    pub async fn run(&self, image: &FirmwareImage) -> SoundcoreLibResult<()> {
+        let mut responses = self.connection.byte_channel().await?;
+        let result = self.update(image, &mut responses).await;
+        let stage = match &result {
+            Ok(()) => OtaStage::Done,
+            Err(SoundcoreLibError::OtaAborted) => {
+                if let Err(e) = self.send(OtaCommand::Abort).await {
+                    warn!("Failed to abort the update on the device: {:?}", e);
+                }
+                OtaStage::Aborted
+            }
+            Err(_) => OtaStage::Failed,
+        };
+        self.progress.send_modify(|progress| progress.stage = stage);
+        result
+    }
+
+    async fn update(
+        &self,
+        image: &FirmwareImage,
+        responses: &mut mpsc::Receiver<Vec<u8>>,
+    ) -> SoundcoreLibResult<()> {
+        self.set_progress(OtaStage::Handshake, 0, image.size());
+        self.send(OtaCommand::Start {
+            size: image.size(),
+            crc: image.crc(),
+            chunk_size: self.config.chunk_size,
+        })
+        .await?;
+        let mut offset = match self.receive(responses).await? {
+            OtaResponse::Ready { offset } if offset <= image.size() => offset,
+            response => return Err(unexpected_response(response)),
+        };
+        if offset > 0 {
+            debug!("Resuming the firmware transfer at {}", offset);
+        }
+
+        while offset < image.size() {
+            self.check_aborted()?;
+            self.set_progress(OtaStage::Transfer, offset, image.size());
+            let end = (offset + self.config.chunk_size as u32).min(image.size());
+            self.send_chunk(
+                offset,
+                &image.bytes()[offset as usize..end as usize],
+                responses,
+            )
+            .await?;
+            offset = end;
+        }
+
+        self.check_aborted()?;
+        self.set_progress(OtaStage::Verify, offset, image.size());
+        self.send(OtaCommand::Verify).await?;
+        match self.receive(responses).await? {
+            OtaResponse::Verified { ok: true } => {}
+            OtaResponse::Verified { ok: false } => {
+                return Err(SoundcoreLibError::Ota(
+                    "The device rejected the image".to_string(),
+                ))
+            }
+            response => return Err(unexpected_response(response)),
+        }
+
+        self.set_progress(OtaStage::Reboot, offset, image.size());
+        self.send(OtaCommand::Reboot).await
+    }
+
+    async fn send_chunk(
+        &self,
+        offset: u32,
+        data: &[u8],
+        responses: &mut mpsc::Receiver<Vec<u8>>,
+    ) -> SoundcoreLibResult<()> {
+        for attempt in 0..=self.config.max_retries {
+            if attempt > 0 {
+                warn!("Chunk at {} was rejected, retrying ({})", offset, attempt);
+            }
+            self.send(OtaCommand::Chunk {
+                offset,
+                data: data.to_vec(),
+            })
+            .await?;
+            match self.receive(responses).await? {
+                OtaResponse::ChunkAck { offset: ack, ok } if ack == offset => {
+                    if ok {
+                        return Ok(());
+                    }
+                }
+                response => return Err(unexpected_response(response)),
+            }
+        }
+        Err(SoundcoreLibError::Ota(format!(
+            "Chunk at {} was rejected {} times",
+            offset,
+            self.config.max_retries + 1
+        )))
+    }
+
+    async fn send(&self, command: OtaCommand) -> SoundcoreLibResult<()> {
+        self.connection
+            .write(&command.bytes(), WriteType::WithResponse)
+            .await
+    }
+
+    /// Skips any packets that aren't OTA responses, e.g. state updates
+    async fn receive(
+        &self,
+        responses: &mut mpsc::Receiver<Vec<u8>>,
+    ) -> SoundcoreLibResult<OtaResponse> {
+        loop {
+            let bytes = F::timeout(self.config.response_timeout, responses.recv())
+                .await
+                .map_err(|_| {
+                    SoundcoreLibError::Ota("Timed out waiting for the device".to_string())
+                })?
+                .ok_or(SoundcoreLibError::NotConnected)?;
+            match OtaResponse::from_bytes(&bytes) {
+                Ok(response) => return Ok(response),
+                Err(e) => trace!("Skipping non OTA packet {:?}: {:?}", bytes, e),
+            }
+        }
+    }
+
+    fn check_aborted(&self) -> SoundcoreLibResult<()> {
+        match self.aborted.load(Ordering::Relaxed) {
+            true => Err(SoundcoreLibError::OtaAborted),
+            false => Ok(()),
+        }
+    }
+
+    fn set_progress(&self, stage: OtaStage, sent: u32, total: u32) {
+        self.progress
+            .send_replace(OtaProgress { stage, sent, total });
+    }
+}
+
+fn unexpected_response(response: OtaResponse) -> SoundcoreLibError {
+    SoundcoreLibError::Ota(format!("Unexpected response: {:?}", response))
+}
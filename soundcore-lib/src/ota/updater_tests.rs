@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use manager_fut::TokioFuture;
+
+use crate::error::SoundcoreLibError;
+use crate::ota::fake_device::FakeOtaDevice;
+use crate::ota::{FirmwareImage, OtaConfig, OtaStage, OtaUpdater};
+
+fn image() -> FirmwareImage {
+    FirmwareImage::from_bytes((0..1000u32).map(|i| (i * 7) as u8).collect()).unwrap()
+}
+
+fn updater(device: &Arc<FakeOtaDevice>) -> OtaUpdater<FakeOtaDevice, TokioFuture> {
+    OtaUpdater::new(device.to_owned()).with_config(OtaConfig {
+        chunk_size: 64,
+        response_timeout: Duration::from_millis(50),
+        max_retries: 1,
+    })
+}
+
+#[tokio::test]
+async fn should_update_firmware() {
+    let device = Arc::new(FakeOtaDevice::new());
+    let updater = updater(&device);
+    let progress = updater.progress();
+
+    updater.run(&image()).await.unwrap();
+
+    assert_eq!(device.received_image(), image().bytes());
+    assert!(device.is_rebooted());
+    assert_eq!(progress.borrow().stage, OtaStage::Done);
+    assert_eq!(progress.borrow().sent, image().size());
+}
+
+#[tokio::test]
+async fn should_retry_rejected_chunks() {
+    let device = Arc::new(FakeOtaDevice::new());
+    device.reject_chunk_once(128);
+
+    updater(&device).run(&image()).await.unwrap();
+    assert_eq!(device.received_image(), image().bytes());
+}
+
+#[tokio::test]
+async fn should_resume_an_interrupted_update() {
+    let device = Arc::new(FakeOtaDevice::new());
+    device.drop_chunk_response_after(5);
+
+    let interrupted = updater(&device);
+    assert!(interrupted.run(&image()).await.is_err());
+    assert_eq!(interrupted.progress().borrow().stage, OtaStage::Failed);
+    assert!(!device.is_rebooted());
+
+    updater(&device).run(&image()).await.unwrap();
+    // The transfer restarts at the chunk the device never answered
+    assert_eq!(device.resume_offsets(), vec![0, 5 * 64]);
+    assert_eq!(device.received_image(), image().bytes());
+    assert!(device.is_rebooted());
+}
+
+#[tokio::test]
+async fn should_abort_the_update() {
+    let device = Arc::new(FakeOtaDevice::new());
+    let updater = updater(&device);
+    updater.abort_handle().abort();
+
+    assert!(matches!(
+        updater.run(&image()).await,
+        Err(SoundcoreLibError::OtaAborted)
+    ));
+    assert_eq!(updater.progress().borrow().stage, OtaStage::Aborted);
+    assert!(device.is_aborted());
+    assert!(device.received_image().is_empty());
+    assert!(!device.is_rebooted());
+}
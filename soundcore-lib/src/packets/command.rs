@@ -1,7 +1,15 @@
 pub use bass_up::*;
 pub use eq::*;
+pub use management::*;
+pub use ring::*;
+pub use settings::*;
 pub use sound_mode::*;
+pub use sound_mode_cycle::*;
 
 mod bass_up;
 mod eq;
+mod management;
+mod ring;
+mod settings;
 mod sound_mode;
+mod sound_mode_cycle;
@@ -1,8 +1,10 @@
 use log::warn;
 
 use crate::{
+    api::{DeviceFeatureSet, FeatureFlags},
     devices::{A3040EqUpdateCommand, A3951EqUpdateCommand},
-    models::EQConfiguration,
+    error::{SoundcoreLibError, SoundcoreLibResult},
+    models::{EQConfiguration, MonoEQ},
     packets::Packet,
     types::KnownProductCodes,
 };
@@ -10,21 +12,201 @@ use crate::{
 pub struct EqCommandBuilder {
     eq: EQConfiguration,
     model: KnownProductCodes,
+    encoder: EQEncoder,
 }
 
 impl EqCommandBuilder {
-    pub fn new(eq: EQConfiguration, model: KnownProductCodes) -> Self {
-        Self { eq, model }
+    pub fn new(
+        eq: EQConfiguration,
+        model: KnownProductCodes,
+        feature_set: &DeviceFeatureSet,
+    ) -> Self {
+        Self {
+            eq,
+            model,
+            encoder: EQEncoder::new(feature_set),
+        }
     }
 
-    pub fn build(self) -> Vec<u8> {
-        match self.model {
-            KnownProductCodes::A3040 => A3040EqUpdateCommand::new(self.eq).bytes(),
-            KnownProductCodes::A3951 => A3951EqUpdateCommand::new(self.eq).bytes(),
+    pub fn build(self) -> SoundcoreLibResult<Vec<u8>> {
+        Ok(match self.model {
+            KnownProductCodes::A3040 => A3040EqUpdateCommand::new(self.eq, &self.encoder)?.bytes(),
+            KnownProductCodes::A3951 => A3951EqUpdateCommand::new(self.eq, &self.encoder)?.bytes(),
             _ => {
                 warn!("Unknown product code, using A3951 as default");
-                A3951EqUpdateCommand::new(self.eq).bytes()
+                A3951EqUpdateCommand::new(self.eq, &self.encoder)?.bytes()
             }
+        })
+    }
+}
+
+/// Encodes the curve of a single EQ channel for a model, based on its EqualizerFeatures
+/// and whether it uses DRC (see FeatureFlags::DRC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EQEncoder {
+    bands: usize,
+    drc: bool,
+}
+
+impl EQEncoder {
+    /// Sent for the slots of bands a model doesn't have
+    pub const FLAT_BYTE: u8 = 120;
+    /// The 10th slot is not a band on models with less than 10 bands and is sent as 0
+    pub const UNUSED_BYTE: u8 = 0;
+    const UNUSED_SLOT: usize = 9;
+    /// Used when the device did not report its equalizer features
+    const DEFAULT_BANDS: usize = 8;
+
+    pub fn new(feature_set: &DeviceFeatureSet) -> Self {
+        Self {
+            bands: feature_set
+                .equalizer_features
+                .as_ref()
+                .map_or(Self::DEFAULT_BANDS, |features| features.bands as usize),
+            drc: feature_set.flags.contains(&FeatureFlags::DRC),
+        }
+    }
+
+    pub fn bands(&self) -> usize {
+        self.bands
+    }
+
+    pub fn drc(&self) -> bool {
+        self.drc
+    }
+
+    /// The curve as sent in `slots` bytes, padding the slots past the model's bands
+    pub fn encode(&self, eq: &MonoEQ, slots: usize) -> SoundcoreLibResult<Vec<u8>> {
+        let mut bytes = self.band_values(eq)?.to_vec();
+        self.pad(&mut bytes, slots)?;
+        Ok(bytes)
+    }
+
+    /// The curve sent in the DRC slots of a command,
+    /// models without DRC receive the curve unchanged
+    pub fn encode_drc(&self, eq: &MonoEQ, slots: usize) -> SoundcoreLibResult<Vec<u8>> {
+        if !self.drc {
+            return self.encode(eq, slots);
+        }
+        if self.bands > MonoEQ::DRC_BANDS {
+            return Err(SoundcoreLibError::FeatureNotSupported(format!(
+                "DRC with {} bands",
+                self.bands
+            )));
+        }
+
+        let min = MonoEQ::gain_to_byte(MonoEQ::MIN_GAIN_DB);
+        let max = MonoEQ::gain_to_byte(MonoEQ::MAX_GAIN_DB);
+        let values = self.band_values(eq)?;
+        if values.iter().any(|value| !(min..=max).contains(value)) {
+            return Err(SoundcoreLibError::InvalidEQ(format!(
+                "DRC requires values within {min}..={max}, got {values:?}"
+            )));
+        }
+
+        let mut bytes = MonoEQ::from_vec(values.to_vec())
+            .drc_gains_db()
+            .into_iter()
+            .take(self.bands)
+            .map(Self::drc_byte)
+            .collect();
+        self.pad(&mut bytes, slots)?;
+        Ok(bytes)
+    }
+
+    /// The DRC transform can exceed the range of the curve, so the gains aren't clamped
+    fn drc_byte(gain: f32) -> u8 {
+        ((gain + (MonoEQ::MAX_FLOAT - MonoEQ::MIN_FLOAT)) * MonoEQ::DRC_ADJUSTMENT).round() as u8
+    }
+
+    fn band_values<'a>(&self, eq: &'a MonoEQ) -> SoundcoreLibResult<&'a [u8]> {
+        eq.values.get(..self.bands).ok_or_else(|| {
+            SoundcoreLibError::InvalidEQ(format!(
+                "Expected {} bands, got {}",
+                self.bands,
+                eq.values.len()
+            ))
+        })
+    }
+
+    fn pad(&self, bytes: &mut Vec<u8>, slots: usize) -> SoundcoreLibResult<()> {
+        if slots < self.bands {
+            return Err(SoundcoreLibError::FeatureNotSupported(format!(
+                "{} bands in a {} band command",
+                self.bands, slots
+            )));
         }
+        bytes.extend((self.bands..slots).map(|slot| match slot {
+            Self::UNUSED_SLOT => Self::UNUSED_BYTE,
+            _ => Self::FLAT_BYTE,
+        }));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod eq_encoder_tests {
+    use std::sync::Arc;
+
+    use crate::api::EqualizerFeatures;
+    use crate::models::EQProfile;
+
+    use super::*;
+
+    fn encoder(bands: u8, drc: bool) -> EQEncoder {
+        EQEncoder::new(&DeviceFeatureSet {
+            equalizer_features: Some(EqualizerFeatures {
+                bands,
+                channels: 2,
+                has_bass_up: false,
+            }),
+            flags: match drc {
+                true => Arc::new([FeatureFlags::DRC]),
+                false => Arc::new([]),
+            },
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn should_pad_8_band_curves() {
+        let eq = EQProfile::Acoustic.eq();
+        assert_eq!(
+            encoder(8, true).encode(&eq, 10).unwrap(),
+            vec![160, 130, 140, 140, 160, 160, 160, 140, 120, 0]
+        );
+        assert_eq!(
+            encoder(8, true).encode_drc(&eq, 10).unwrap(),
+            vec![125, 118, 123, 120, 124, 122, 124, 121, 120, 0]
+        );
+        assert_eq!(encoder(8, true).encode_drc(&eq, 8).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn should_only_apply_drc_when_supported() {
+        let eq = EQProfile::Acoustic.eq();
+        assert_eq!(
+            encoder(8, false).encode_drc(&eq, 8).unwrap(),
+            encoder(8, false).encode(&eq, 8).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_encode_10_band_curves() {
+        let eq = MonoEQ::from_vec(vec![100, 110, 120, 130, 140, 150, 160, 170, 130, 90]);
+        assert_eq!(encoder(10, false).encode(&eq, 10).unwrap(), eq.values);
+        assert!(encoder(10, true).encode_drc(&eq, 10).is_err());
+        assert!(encoder(10, false).encode(&eq, 8).is_err());
+    }
+
+    #[test]
+    fn should_reject_invalid_curves() {
+        let short = MonoEQ::from_vec(vec![120; 6]);
+        assert!(encoder(8, false).encode(&short, 8).is_err());
+
+        // Values outside of the ±6 dB range can't be DRC transformed
+        let loud = MonoEQ::from_vec(vec![20; 8]);
+        assert!(encoder(8, false).encode(&loud, 8).is_ok());
+        assert!(encoder(8, true).encode_drc(&loud, 8).is_err());
     }
 }
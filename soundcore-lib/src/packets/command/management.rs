@@ -0,0 +1,131 @@
+use log::warn;
+
+use crate::error::{SoundcoreLibError, SoundcoreLibResult};
+use crate::packets::Packet;
+use crate::types::KnownProductCodes;
+
+// None of these commands were confirmed by a capture yet,
+// so no model declares the POWER_OFF, FACTORY_RESET or RENAME feature flags
+
+pub struct PowerOffCommandBuilder {
+    model: KnownProductCodes,
+}
+
+impl PowerOffCommandBuilder {
+    pub fn new(model: KnownProductCodes) -> Self {
+        Self { model }
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        warn!("Unverified power off command for {:?}", self.model);
+        PowerOffCommand.bytes()
+    }
+}
+
+pub struct FactoryResetCommandBuilder {
+    model: KnownProductCodes,
+}
+
+impl FactoryResetCommandBuilder {
+    pub fn new(model: KnownProductCodes) -> Self {
+        Self { model }
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        warn!("Unverified factory reset command for {:?}", self.model);
+        FactoryResetCommand.bytes()
+    }
+}
+
+pub struct RenameCommandBuilder {
+    model: KnownProductCodes,
+    name: String,
+}
+
+impl RenameCommandBuilder {
+    /// The name is sent as UTF-8 and can't be longer than this many bytes
+    pub const MAX_NAME_LENGTH: usize = 30;
+
+    pub fn new(model: KnownProductCodes, name: String) -> Self {
+        Self { model, name }
+    }
+
+    pub fn build(self) -> SoundcoreLibResult<Vec<u8>> {
+        if self.name.trim().is_empty() || self.name.len() > Self::MAX_NAME_LENGTH {
+            return Err(SoundcoreLibError::InvalidDeviceName(self.name));
+        }
+        warn!("Unverified rename command for {:?}", self.model);
+        Ok(RenameCommand(self.name).bytes())
+    }
+}
+
+struct PowerOffCommand;
+
+impl Packet for PowerOffCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8e]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+struct FactoryResetCommand;
+
+impl Packet for FactoryResetCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8d]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+struct RenameCommand(String);
+
+impl Packet for RenameCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8f]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_commands_without_payload() {
+        let bytes = PowerOffCommandBuilder::new(KnownProductCodes::A3040).build();
+        assert_eq!(bytes[5..9], [0x01, 0x8e, 0x0a, 0x00]);
+        let bytes = FactoryResetCommandBuilder::new(KnownProductCodes::A3040).build();
+        assert_eq!(bytes[5..9], [0x01, 0x8d, 0x0a, 0x00]);
+    }
+
+    #[test]
+    fn should_validate_the_name() {
+        let bytes = RenameCommandBuilder::new(KnownProductCodes::A3040, "Desk".to_string())
+            .build()
+            .unwrap();
+        assert_eq!(bytes[7..13], [0x0e, 0x00, b'D', b'e', b's', b'k']);
+
+        assert!(
+            RenameCommandBuilder::new(KnownProductCodes::A3040, " ".to_string())
+                .build()
+                .is_err()
+        );
+        assert!(
+            RenameCommandBuilder::new(KnownProductCodes::A3040, "a".repeat(31))
+                .build()
+                .is_err()
+        );
+    }
+}
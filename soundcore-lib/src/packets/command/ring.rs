@@ -0,0 +1,63 @@
+use log::warn;
+
+use crate::models::EarbudSide;
+use crate::packets::Packet;
+use crate::types::KnownProductCodes;
+
+pub struct RingCommandBuilder {
+    model: KnownProductCodes,
+    side: EarbudSide,
+    enable: bool,
+}
+
+impl RingCommandBuilder {
+    pub fn new(model: KnownProductCodes, side: EarbudSide, enable: bool) -> Self {
+        Self {
+            model,
+            side,
+            enable,
+        }
+    }
+
+    /// No model declares FeatureFlags::FIND_DEVICE until the command is confirmed by a capture
+    pub fn build(&self) -> Vec<u8> {
+        warn!("Unverified ring command for {:?}", self.model);
+        RingCommand::new(self.side, self.enable).bytes()
+    }
+}
+
+/// Plays a locating tone on one or both earbuds
+struct RingCommand {
+    side: EarbudSide,
+    enable: bool,
+}
+
+impl RingCommand {
+    fn new(side: EarbudSide, enable: bool) -> Self {
+        Self { side, enable }
+    }
+}
+
+impl Packet for RingCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8b]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![self.side.as_u8(), self.enable as u8]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_ring_command() {
+        let bytes =
+            RingCommandBuilder::new(KnownProductCodes::A3951, EarbudSide::Right, true).build();
+        assert_eq!(bytes[..7], [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8b]);
+        assert_eq!(bytes[7..11], [0x0c, 0x00, 0x01, 0x01]);
+    }
+}
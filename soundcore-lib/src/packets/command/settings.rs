@@ -0,0 +1,96 @@
+use log::warn;
+
+use crate::models::{PromptLanguage, ThreeDimensionalEffect};
+use crate::packets::Packet;
+use crate::types::KnownProductCodes;
+
+pub struct PromptLanguageCommandBuilder {
+    model: KnownProductCodes,
+    language: PromptLanguage,
+}
+
+impl PromptLanguageCommandBuilder {
+    pub fn new(model: KnownProductCodes, language: PromptLanguage) -> Self {
+        Self { model, language }
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        match self.model {
+            KnownProductCodes::A3040 => PromptLanguageCommand(self.language).bytes(),
+            _ => {
+                warn!("Unknown or unhandled product code, using A3040 as default");
+                PromptLanguageCommand(self.language).bytes()
+            }
+        }
+    }
+}
+
+pub struct ThreeDimensionalEffectCommandBuilder {
+    model: KnownProductCodes,
+    effect: ThreeDimensionalEffect,
+}
+
+impl ThreeDimensionalEffectCommandBuilder {
+    pub fn new(model: KnownProductCodes, effect: ThreeDimensionalEffect) -> Self {
+        Self { model, effect }
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        match self.model {
+            KnownProductCodes::A3040 => ThreeDimensionalEffectCommand(self.effect).bytes(),
+            _ => {
+                warn!("Unknown or unhandled product code, using A3040 as default");
+                ThreeDimensionalEffectCommand(self.effect).bytes()
+            }
+        }
+    }
+}
+
+struct PromptLanguageCommand(PromptLanguage);
+
+impl Packet for PromptLanguageCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x01, 0x8c]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![self.0.as_u8()]
+    }
+}
+
+struct ThreeDimensionalEffectCommand(ThreeDimensionalEffect);
+
+impl Packet for ThreeDimensionalEffectCommand {
+    // Mirrors the BassUp command, the update packet uses the same id without the set bit
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x02, 0x86]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![self.0 .0 as u8]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_prompt_language_command() {
+        let bytes =
+            PromptLanguageCommandBuilder::new(KnownProductCodes::A3040, PromptLanguage::Chinese)
+                .build();
+        assert_eq!(bytes[5..7], [0x01, 0x8c]);
+        assert_eq!(bytes[9], 0x01);
+    }
+
+    #[test]
+    fn should_build_three_dimensional_effect_command() {
+        let bytes =
+            ThreeDimensionalEffectCommandBuilder::new(KnownProductCodes::A3040, true.into())
+                .build();
+        assert_eq!(bytes[5..7], [0x02, 0x86]);
+        assert_eq!(bytes[9], 0x01);
+    }
+}
@@ -52,6 +52,7 @@ mod tests {
             ),
             custom_anc: CustomANCValue::from_u8(0),
             custom_trans: None,
+            ..Default::default()
         };
         let builder = SoundModeCommandBuilder::new(sound_mode, KnownProductCodes::A3027);
         let bytes = builder.build();
@@ -0,0 +1,64 @@
+use log::warn;
+
+use crate::models::SoundModeCycle;
+use crate::packets::Packet;
+use crate::types::KnownProductCodes;
+
+pub struct SoundModeCycleCommandBuilder {
+    cycle: SoundModeCycle,
+    model: KnownProductCodes,
+}
+
+impl SoundModeCycleCommandBuilder {
+    pub fn new(cycle: SoundModeCycle, model: KnownProductCodes) -> Self {
+        Self { cycle, model }
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        match self.model {
+            KnownProductCodes::A3947 => SoundModeCycleCommand::new(self.cycle).bytes(),
+            _ => {
+                warn!("Unknown or unhandled product code, using A3947 as default");
+                SoundModeCycleCommand::new(self.cycle).bytes()
+            }
+        }
+    }
+}
+
+struct SoundModeCycleCommand {
+    cycle: SoundModeCycle,
+}
+
+impl SoundModeCycleCommand {
+    fn new(cycle: SoundModeCycle) -> Self {
+        Self { cycle }
+    }
+}
+
+impl Packet for SoundModeCycleCommand {
+    // TODO: Verify the command id against a capture
+    fn command(&self) -> [u8; 7] {
+        [0x08, 0xee, 0x00, 0x00, 0x00, 0x06, 0x82]
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        vec![self.cycle.as_byte()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_sound_mode_cycle_command() {
+        let cycle = SoundModeCycle {
+            normal: false,
+            ..Default::default()
+        };
+        let bytes = SoundModeCycleCommandBuilder::new(cycle, KnownProductCodes::A3947).build();
+        assert_eq!(bytes[..7], [0x08, 0xee, 0x00, 0x00, 0x00, 0x06, 0x82]);
+        assert_eq!(bytes[9], 0x03);
+        assert_eq!(bytes.len(), 11);
+    }
+}
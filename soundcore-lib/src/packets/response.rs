@@ -3,8 +3,10 @@ use nom::error::VerboseError;
 
 pub use bass_up::*;
 pub use info::*;
+pub use ldac::*;
 pub use sound_mode::*;
 pub use state::*;
+pub use three_dimensional_effect::*;
 
 use crate::api::SoundcoreDeviceState;
 use crate::packets::response::eq_info_update::{parse_eq_info_update, EqInfoUpdate};
@@ -18,8 +20,10 @@ mod bass_up;
 mod battery;
 mod eq_info_update;
 mod info;
+mod ldac;
 mod sound_mode;
 mod state;
+mod three_dimensional_effect;
 
 #[derive(Debug)]
 pub enum ResponsePacket {
@@ -28,6 +32,8 @@ pub enum ResponsePacket {
     DeviceInfo(DeviceInfoResponse),
     BassUpUpdate(BassUpUpdateResponse),
     EqInfoUpdate(EqInfoUpdate),
+    ThreeDimensionalEffectUpdate(ThreeDimensionalEffectUpdateResponse),
+    LDACUpdate(LDACUpdateResponse),
     Unknown,
 }
 
@@ -37,7 +43,11 @@ pub trait StateTransformationPacket {
 
 impl ResponsePacket {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, nom::Err<VerboseError<&[u8]>>> {
-        let bytes = parse_and_check_checksum(bytes)?.0;
+        Self::from_verified_bytes(parse_and_check_checksum(bytes)?.0)
+    }
+
+    /// Parses a packet whose checksum was already verified and stripped
+    pub(crate) fn from_verified_bytes(bytes: &[u8]) -> Result<Self, nom::Err<VerboseError<&[u8]>>> {
         let (bytes, packet_header) = parse_packet_header(bytes)?;
 
         Ok(match packet_header.kind {
@@ -50,6 +60,10 @@ impl ResponsePacket {
             ResponsePacketKind::InfoUpdate => Self::DeviceInfo(parse_device_info_packet(bytes)?.1),
             ResponsePacketKind::BassUpUpdate => Self::BassUpUpdate(parse_bass_up_update(bytes)?.1),
             ResponsePacketKind::EqInfoUpdate => Self::EqInfoUpdate(parse_eq_info_update(bytes)?.1),
+            ResponsePacketKind::ThreeDimensionalEffectUpdate => {
+                Self::ThreeDimensionalEffectUpdate(parse_three_dimensional_effect_update(bytes)?.1)
+            }
+            ResponsePacketKind::LDACUpdate => Self::LDACUpdate(parse_ldac_update(bytes)?.1),
             _ => {
                 // TODO: Have an array of Acks and handle those properly
                 error!(
@@ -123,6 +137,8 @@ impl StateTransformationPacket for ResponsePacket {
             ResponsePacket::BassUpUpdate(packet) => packet.transform_state(state),
             ResponsePacket::EqInfoUpdate(packet) => packet.transform_state(state),
             ResponsePacket::DeviceInfo(packet) => packet.transform_state(state),
+            ResponsePacket::ThreeDimensionalEffectUpdate(packet) => packet.transform_state(state),
+            ResponsePacket::LDACUpdate(packet) => packet.transform_state(state),
             // No-op
             _ => {
                 debug!("No state transformation implementation!");
@@ -136,6 +152,10 @@ impl StateTransformationPacket for ResponsePacket {
 mod response_test {
     use test_data::a3951::*;
 
+    use crate::api::SoundcoreDeviceState;
+    use crate::packets::StateTransformationPacket;
+    use crate::parsers::generate_checksum;
+
     use super::ResponsePacket;
 
     #[test]
@@ -155,4 +175,25 @@ mod response_test {
         let packet = ResponsePacket::from_bytes(&A3951_INFO_UPDATE_BYTES).unwrap();
         assert!(matches!(packet, ResponsePacket::DeviceInfo(_)));
     }
+
+    #[test]
+    fn toggle_updates() {
+        let state = SoundcoreDeviceState {
+            three_dimensional_effect: Some(false.into()),
+            ldac: Some(false.into()),
+            ..Default::default()
+        };
+        let packets = [[0x02, 0x06], [0x01, 0x7F]].map(|kind| {
+            let mut bytes = vec![
+                0x09, 0xFF, 0x00, 0x00, 0x01, kind[0], kind[1], 0x0B, 0x00, 0x01,
+            ];
+            bytes.push(generate_checksum(&bytes));
+            ResponsePacket::from_bytes(&bytes).unwrap()
+        });
+        let state = packets
+            .into_iter()
+            .fold(state, |state, packet| packet.transform_state(&state));
+        assert_eq!(state.three_dimensional_effect, Some(true.into()));
+        assert_eq!(state.ldac, Some(true.into()));
+    }
 }
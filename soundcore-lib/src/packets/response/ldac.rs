@@ -0,0 +1,34 @@
+use log::{debug, warn};
+use nom::combinator::map;
+use nom::error::context;
+use serde::{Deserialize, Serialize};
+
+use crate::api::SoundcoreDeviceState;
+use crate::models::LDAC;
+use crate::packets::StateTransformationPacket;
+use crate::parsers::{bool_parser, ParseError, ParseResult};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+pub struct LDACUpdateResponse(pub LDAC);
+
+pub fn parse_ldac_update<'a, E: ParseError<'a>>(
+    bytes: &'a [u8],
+) -> ParseResult<'a, LDACUpdateResponse, E> {
+    context("parse_ldac_update", map(bool_parser, LDACUpdateResponse))(bytes)
+}
+
+impl StateTransformationPacket for LDACUpdateResponse {
+    fn transform_state(self, state: &SoundcoreDeviceState) -> SoundcoreDeviceState {
+        let mut state = state.to_owned();
+        match state.ldac {
+            Some(ldac) => {
+                debug!("Updating LDAC state from {:?} to {:?}", ldac, self.0);
+                state.ldac = Some(self.0);
+            }
+            None => {
+                warn!("LDACUpdateResponse received without a previous LDAC state");
+            }
+        }
+        state
+    }
+}
@@ -11,9 +11,9 @@ use a3951::*;
 use crate::api::SoundcoreDeviceState;
 use crate::devices::parse_a3947_state_update;
 use crate::models::{
-    AmbientSoundNotice, AutoPowerOff, BassUp, DeviceColor, DeviceFirmware, HearingProtect,
-    InEarBeep, PowerOnBatteryNotice, PromptLanguage, SerialNumber, SupportTwoCnn,
-    ThreeDimensionalEffect, LDAC,
+    AmbientSoundNotice, AutoPowerOff, BassUp, DeviceColor, DeviceFirmware, GameMode,
+    HearingProtect, InEarBeep, PowerOnBatteryNotice, PromptLanguage, SerialNumber, SingleBattery,
+    SoundModeCycle, SupportTwoCnn, ThreeDimensionalEffect, LDAC,
 };
 use crate::packets::StateTransformationPacket;
 use crate::parsers::{TaggedData, TaggedParseResult};
@@ -34,6 +34,7 @@ use crate::{
 pub struct DeviceStateResponse {
     pub feature_set: DeviceFeatureSet,
     pub battery: Battery,
+    pub case_battery: Option<SingleBattery>,
     pub sound_mode: SoundMode,
     pub eq: EQConfiguration,
     pub sn: Option<SerialNumber>,
@@ -58,10 +59,12 @@ pub struct DeviceStateResponse {
     pub power_on_battery_notice: Option<PowerOnBatteryNotice>,
     // Other
     pub three_dimensional_effect: Option<ThreeDimensionalEffect>,
+    pub game_mode: Option<GameMode>,
     pub device_color: Option<DeviceColor>,
     pub ldac: Option<LDAC>,
     pub prompt_language: Option<PromptLanguage>,
     pub hearing_protect: Option<HearingProtect>,
+    pub sound_mode_cycle: Option<SoundModeCycle>,
 }
 
 // TODO: Add more parsers
@@ -107,6 +110,7 @@ impl From<DeviceStateResponse> for SoundcoreDeviceState {
         SoundcoreDeviceState {
             feature_set: value.feature_set,
             battery: value.battery,
+            case_battery: value.case_battery,
             sound_mode: value.sound_mode,
             serial: value.sn,
             eq_configuration: value.eq,
@@ -127,11 +131,13 @@ impl From<DeviceStateResponse> for SoundcoreDeviceState {
             ambient_sound_notice: value.ambient_sound_notice,
             power_on_battery_notice: value.power_on_battery_notice,
             three_dimensional_effect: value.three_dimensional_effect,
+            game_mode: value.game_mode,
             device_color: value.device_color,
             ldac: value.ldac,
             prompt_language: value.prompt_language,
             hearing_protect: value.hearing_protect,
             hear_id_has_data: value.hear_id_has_data,
+            sound_mode_cycle: value.sound_mode_cycle,
         }
     }
 }
@@ -0,0 +1,40 @@
+use log::{debug, warn};
+use nom::combinator::map;
+use nom::error::context;
+use serde::{Deserialize, Serialize};
+
+use crate::api::SoundcoreDeviceState;
+use crate::models::ThreeDimensionalEffect;
+use crate::packets::StateTransformationPacket;
+use crate::parsers::{bool_parser, ParseError, ParseResult};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+pub struct ThreeDimensionalEffectUpdateResponse(pub ThreeDimensionalEffect);
+
+pub fn parse_three_dimensional_effect_update<'a, E: ParseError<'a>>(
+    bytes: &'a [u8],
+) -> ParseResult<'a, ThreeDimensionalEffectUpdateResponse, E> {
+    context(
+        "parse_three_dimensional_effect_update",
+        map(bool_parser, ThreeDimensionalEffectUpdateResponse),
+    )(bytes)
+}
+
+impl StateTransformationPacket for ThreeDimensionalEffectUpdateResponse {
+    fn transform_state(self, state: &SoundcoreDeviceState) -> SoundcoreDeviceState {
+        let mut state = state.to_owned();
+        match state.three_dimensional_effect {
+            Some(effect) => {
+                debug!(
+                    "Updating ThreeDimensionalEffect state from {:?} to {:?}",
+                    effect, self.0
+                );
+                state.three_dimensional_effect = Some(self.0);
+            }
+            None => {
+                warn!("ThreeDimensionalEffectUpdateResponse received without a previous ThreeDimensionalEffect state");
+            }
+        }
+        state
+    }
+}
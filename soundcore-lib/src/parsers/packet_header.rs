@@ -64,7 +64,7 @@ mod packet_header_parser {
         let result = super::parse_packet_header::<nom::error::VerboseError<&[u8]>>(&bytes);
         assert!(result.is_ok());
         let (remaining, packet_kind) = result.unwrap();
-        assert_eq!(remaining, &[]);
+        assert_eq!(remaining, &[] as &[u8]);
         assert_eq!(
             packet_kind,
             ResponsePacketHeader {
@@ -80,7 +80,7 @@ mod packet_header_parser {
         let result = super::parse_packet_kind::<nom::error::VerboseError<&[u8]>>(&bytes);
         assert!(result.is_ok());
         let (remaining, packet_kind) = result.unwrap();
-        assert_eq!(remaining, &[]);
+        assert_eq!(remaining, &[] as &[u8]);
         assert_eq!(packet_kind, ResponsePacketKind::Unknown);
     }
 
@@ -90,6 +90,6 @@ mod packet_header_parser {
         let result = super::parse_packet_prefix::<nom::error::VerboseError<&[u8]>>(&bytes);
         assert!(result.is_ok());
         let (remaining, _) = result.unwrap();
-        assert_eq!(remaining, &[]);
+        assert_eq!(remaining, &[] as &[u8]);
     }
 }
@@ -7,7 +7,8 @@ use nom::{
 };
 
 use crate::models::{
-    ANCMode, CurrentSoundMode, CustomANCValue, CustomTransparencyValue, SoundMode, TransparencyMode,
+    ANCMode, ANCStrengthMode, CurrentSoundMode, CustomANCValue, CustomTransparencyValue, SoundMode,
+    TransparencyMode,
 };
 
 use super::{ParseError, ParseResult};
@@ -39,6 +40,7 @@ pub fn parse_scene_based_sound_mode_non_customizable_trans<'a, E: ParseError<'a>
                 trans_mode,
                 custom_anc,
                 custom_trans: None,
+                ..Default::default()
             },
         ),
     )(bytes)
@@ -55,18 +57,18 @@ pub fn parse_adaptive_sound_mode_customizable_trans<'a, E: ParseError<'a>>(
                 le_u8,
                 parse_customizable_trans_mode,
                 parse_adaptive_anc_mode,
-                le_u8,
+                parse_anc_strength_mode,
                 parse_custom_trans,
             )),
-            |(current_mode, custom_anc, trans_mode, anc_mode, _e, custom_trans)| {
-                let custom_anc_value = CustomANCValue::from_u8(custom_anc >> 4);
-                let _unk = custom_anc & 0x0F;
+            |(current_mode, custom_anc, trans_mode, anc_mode, anc_strength_mode, custom_trans)| {
                 SoundMode {
                     current: current_mode,
                     anc_mode,
                     trans_mode,
                     custom_trans: Some(custom_trans),
-                    custom_anc: custom_anc_value,
+                    custom_anc: CustomANCValue::from_u8(custom_anc >> 4),
+                    auto_anc: custom_anc & SoundMode::AUTO_ANC_BIT != 0,
+                    anc_strength_mode,
                 }
             },
         ),
@@ -114,6 +116,17 @@ fn parse_customizable_trans_mode<'a, E: ParseError<'a>>(
     )(bytes)
 }
 
+fn parse_anc_strength_mode<'a, E: ParseError<'a>>(
+    bytes: &'a [u8],
+) -> ParseResult<'a, ANCStrengthMode, E> {
+    context(
+        "parse_anc_strength_mode",
+        map(le_u8, |value| {
+            ANCStrengthMode::from_u8(value).unwrap_or_default()
+        }),
+    )(bytes)
+}
+
 fn parse_custom_anc<'a, E: ParseError<'a>>(bytes: &'a [u8]) -> ParseResult<CustomANCValue, E> {
     context("parse_custom_anc", map(le_u8, CustomANCValue::from_u8))(bytes)
 }
@@ -148,11 +161,20 @@ mod test {
                 trans_mode: TransparencyMode::Customizable(CustomizableTransparencyMode::Custom),
                 custom_anc: CustomANCValue(0x3),
                 custom_trans: Some(CustomTransparencyValue(0x5)),
+                auto_anc: true,
+                ..Default::default()
             },
             output.1
         );
     }
 
+    #[test]
+    fn should_default_unknown_anc_strength_modes() {
+        let bytes = [0x0, 0x31, 0x1, 0x1, 0xFE, 0x5];
+        let output = parse_sound_mode::<nom::error::VerboseError<&[u8]>>(&bytes).unwrap();
+        assert_eq!(output.1.anc_strength_mode, ANCStrengthMode::Manual);
+    }
+
     #[test]
     fn should_handle_variable_a3040_custom_values() {
         for i in 0..=5 {
@@ -168,6 +190,8 @@ mod test {
                     ),
                     custom_anc: CustomANCValue(i),
                     custom_trans: Some(CustomTransparencyValue(i)),
+                    auto_anc: true,
+                    ..Default::default()
                 },
                 output.1
             );
@@ -187,6 +211,7 @@ mod test {
                 trans_mode: TransparencyMode::NonCustomizable(
                     NonCustomizableTransparencyMode::Vocal
                 ),
+                ..Default::default()
             },
             output.1
         );
@@ -206,9 +231,28 @@ mod test {
                     trans_mode: TransparencyMode::NonCustomizable(
                         NonCustomizableTransparencyMode::Vocal
                     ),
+                    ..Default::default()
                 },
                 output.1
             );
         }
     }
+
+    #[test]
+    fn should_round_trip_a3040_sound_mode_flags() {
+        let sound_mode = SoundMode {
+            current: CurrentSoundMode::ANC,
+            anc_mode: ANCMode::Adaptive(AdaptiveANCMode::Custom),
+            trans_mode: TransparencyMode::Customizable(CustomizableTransparencyMode::TalkMode),
+            custom_anc: CustomANCValue(4),
+            custom_trans: Some(CustomTransparencyValue(2)),
+            auto_anc: false,
+            anc_strength_mode: ANCStrengthMode::Environment,
+        };
+        let bytes = sound_mode.to_bytes_with_custom_transparency();
+        assert_eq!(bytes, [0x00, 0x40, 0x00, 0x00, 0x01, 0x02]);
+
+        let output = parse_sound_mode::<nom::error::VerboseError<&[u8]>>(&bytes).unwrap();
+        assert_eq!(sound_mode, output.1);
+    }
 }
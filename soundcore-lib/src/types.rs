@@ -22,7 +22,6 @@ pub enum KnownProductCodes {
 pub static SOUNDCORE_NAME_PRODUCT_CODE_MAP: phf::Map<&'static str, KnownProductCodes> = phf_map! {
     "Q35" => KnownProductCodes::A3027,
     "Q30" => KnownProductCodes::A3028,
-    "BES_BLE" => KnownProductCodes::A3028, /* Q30 has a FW Bug causing it to appear sometimes as BES_BLE */
     "Life Tune" => KnownProductCodes::A3029,
     "Q45" => KnownProductCodes::A3040,
     "A2 NC" => KnownProductCodes::A3935,
@@ -9,6 +9,7 @@ pub fn parse_a3947_state_packet() {
         ResponsePacket::DeviceState(resp) => {
             println!("{:?}", resp);
             assert_eq!(resp.tag, KnownProductCodes::A3947);
+            assert_eq!(resp.data.sound_mode_cycle, None);
         }
         _ => panic!("Parsed as wrong packet type"),
     }
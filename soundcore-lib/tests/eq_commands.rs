@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use soundcore_lib::{
+    api::DeviceFeatureSet,
+    devices::{
+        a3027_features, a3028_features, a3029_features, a3040_features, a3930_features,
+        a3951_features,
+    },
+    models::{EQConfiguration, EQProfile, MonoEQ, MonoEQConfiguration},
+    packets::EqCommandBuilder,
+    types::KnownProductCodes,
+};
+
+fn build(eq: EQConfiguration, model: KnownProductCodes, feature_set: &DeviceFeatureSet) -> Vec<u8> {
+    EqCommandBuilder::new(eq, model, feature_set)
+        .build()
+        .expect("The command should be built")
+}
+
+#[test]
+fn a3040_eq_command() {
+    let eq = MonoEQConfiguration {
+        profile: EQProfile::Acoustic,
+        eq: EQProfile::Acoustic.eq(),
+    };
+    assert_eq!(
+        build(eq.into(), KnownProductCodes::A3040, &a3040_features()),
+        test_data::a3040::SET_EQ_ACOUSTIC_NO_HEAR_ID
+    );
+}
+
+#[test]
+fn a3951_eq_command() {
+    let eq = EQConfiguration::stereo_with_profile(EQProfile::Deep);
+    assert_eq!(
+        build(eq, KnownProductCodes::A3951, &a3951_features()),
+        test_data::a3951::A3951_EQ_UPDATE_DEEP_NO_HEAR_ID
+    );
+}
+
+/// Models without a dedicated command send the A3951 one, with a DRC curve only if they declare DRC
+#[test]
+fn fallback_eq_commands() {
+    let without_drc = DeviceFeatureSet {
+        flags: Arc::new([]),
+        ..a3951_features()
+    };
+    let models = [
+        (KnownProductCodes::A3027, a3027_features()),
+        (KnownProductCodes::A3028, a3028_features()),
+        (KnownProductCodes::A3029, a3029_features()),
+        (KnownProductCodes::A3930, a3930_features()),
+        // A3947 does not report any features yet
+        (KnownProductCodes::A3947, DeviceFeatureSet::default()),
+    ];
+    for (model, feature_set) in models {
+        let eq = EQConfiguration::stereo_with_profile(EQProfile::Deep);
+        assert_eq!(
+            build(eq.clone(), model, &feature_set),
+            build(eq, KnownProductCodes::A3951, &without_drc),
+            "{model:?}"
+        );
+    }
+}
+
+#[test]
+fn drc_eq_command_should_reject_out_of_range_values() {
+    let eq = EQConfiguration::stereo_custom(
+        MonoEQ::from_signed_bytes(vec![-120; 8]),
+        MonoEQ::from_signed_bytes(vec![-120; 8]),
+    );
+    assert!(
+        EqCommandBuilder::new(eq, KnownProductCodes::A3951, &a3951_features())
+            .build()
+            .is_err()
+    );
+}